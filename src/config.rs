@@ -1,8 +1,11 @@
-use std::ops::Range;
+use bevy::color::Color;
 
 // Map
 pub const MAP_HEIGHT: f32 = 768.0;
 pub const MAP_WIDTH: f32 = 768.0;
+// Chunks further than this from the camera are despawned, and enemies further than this
+// from the player are moved back near them
+pub const CHUNK_DESPAWN_DISTANCE: f32 = 1080.0;
 
 // Borders
 pub const BORDER_THICKNESS: f32 = 100.0;
@@ -10,15 +13,154 @@ pub const BORDER_THICKNESS: f32 = 100.0;
 // Player
 pub const PLAYER_SPEED: f32 = 5000.;
 pub const PLAYER_HEALTH: f32 = 10.00;
+// Stat cards can't take more than this fraction off damage or cast delays
+pub const PLAYER_MAX_ARMOR: f32 = 0.6;
+pub const PLAYER_MAX_CAST_DELAY_REDUCTION: f32 = 0.6;
+// Dashes move this fast for their duration, the player can't be hurt for a little longer
+pub const DASH_SPEED: f32 = 400.0;
+pub const DASH_DURATION: f32 = 0.15;
+pub const DASH_INVINCIBILITY: f32 = 0.25;
+pub const DASH_COOLDOWN: f32 = 1.5;
+// On death time slows down while the death animation plays, for this many real seconds
+pub const DEATH_TIME_SCALE: f32 = 0.25;
+pub const DEATH_SEQUENCE_DURATION: f32 = 2.0;
 
 // Enemy
 pub const ENEMY_SPEED: f32 = 40.0;
 pub const ENEMY_HEALTH: f32 = 55.0;
 pub const ENEMY_DAMAGE: f32 = 1.0;
-pub const SPAWN_RADIUS: Range<f32> = 400.0..500.0;
-pub const RANGED_ENEMY_DIST: u32 = 200;
+// How quickly enemies turn towards the velocity their behaviors want, per second
+pub const ENEMY_STEERING: f32 = 6.3;
+pub const RANGED_ENEMY_DIST: f32 = 200.0;
+pub const RANGED_FLEE_HEALTH: f32 = 0.25;
+// Enemy wands use the player's gems, so their damage is scaled down to enemy numbers
+pub const ENEMY_WAND_COOLDOWN: f32 = 3.0;
+pub const ENEMY_SPELL_DAMAGE_SCALE: f32 = 0.025;
+// Tanks stop for the windup, then charge at this multiple of their speed
+pub const TANK_CHARGE_RANGE: f32 = 150.0;
+pub const TANK_CHARGE_WINDUP: f32 = 0.8;
+pub const TANK_CHARGE_DURATION: f32 = 0.6;
+pub const TANK_CHARGE_SPEED: f32 = 5.0;
+// Exploders light their fuse once this close to the player, then blow up after the fuse
+pub const EXPLODER_TRIGGER_RANGE: f32 = 40.0;
+pub const EXPLODER_FUSE: f32 = 0.6;
+pub const EXPLODER_RADIUS: f32 = 50.0;
+pub const EXPLODER_DAMAGE: f32 = 2.0;
+// Splitters split this many times, every copy is smaller and has less health
+pub const SPLITTER_SPLITS: u32 = 2;
+pub const SPLITTER_COPIES: u32 = 2;
+pub const SPLITTER_SHRINK: f32 = 0.7;
+pub const HEALER_DIST: f32 = 150.0;
+pub const HEALER_RADIUS: f32 = 120.0;
+pub const HEALER_AMOUNT: f32 = 10.0;
+pub const HEALER_INTERVAL: f32 = 2.0;
+// Summoners circle the player this far away
+pub const SUMMONER_DIST: f32 = 250.0;
+pub const SUMMONER_INTERVAL: f32 = 6.0;
+pub const SUMMONER_COUNT: u32 = 3;
+// Enemies closer than this to the player walk straight at them instead of following the flow field
+pub const FLOW_FIELD_DIRECT_RANGE: f32 = 48.0;
+// Size of the cells of the grid used to find enemies near a point
+pub const ENEMY_GRID_CELL_SIZE: f32 = 64.0;
+
+// Elites, the chance of an enemy being an elite starts at the first wave and grows every wave
+pub const ELITE_FIRST_WAVE: u32 = 3;
+pub const ELITE_BASE_CHANCE: f32 = 0.02;
+pub const ELITE_CHANCE_PER_WAVE: f32 = 0.01;
+pub const ELITE_MAX_CHANCE: f32 = 0.2;
+pub const ELITE_SECOND_AFFIX_CHANCE: f32 = 0.3;
+pub const ELITE_HEALTH_MULTIPLIER: f32 = 3.0;
+pub const ELITE_XP_MULTIPLIER: u32 = 3;
+pub const ELITE_TINT: Color = Color::srgb(1.0, 0.85, 0.2);
+pub const ELITE_FAST_SPEED: f32 = 1.6;
+pub const ELITE_ARMOR: f32 = 0.5;
+// Vampiric elites heal this much health every time they hurt the player
+pub const ELITE_VAMPIRIC_FLAT_HEAL: f32 = 10.0;
+pub const ELITE_REFLECT_CHANCE: f32 = 0.25;
+
+// Spawning, enemies spawn this far outside the camera's view and inside the arena's edges
+pub const SPAWN_VIEW_MARGIN: f32 = 32.0;
+pub const SPAWN_ARENA_MARGIN: f32 = 16.0;
+pub const SPAWN_PACK_RADIUS: f32 = 40.0;
+// The telegraph marks a spawn this long before the enemy appears
+pub const SPAWN_TELEGRAPH_DURATION: f32 = 0.6;
+pub const SPAWN_TELEGRAPH_SIZE: f32 = 16.0;
+pub const SPAWN_TELEGRAPH_COLOR: Color = Color::srgba(1.0, 0.2, 0.2, 0.6);
+// Seconds between checks for enemies too far from the player
+pub const ENEMY_LEASH_INTERVAL: f32 = 1.0;
+
+// Intermission, the next wave starts this many seconds after leaving the shop
+pub const INTERMISSION_DURATION: f32 = 5.0;
+
+// Damage numbers rise this fast until their lifetime runs out
+pub const DAMAGE_NUMBER_LIFETIME: f32 = 0.8;
+pub const DAMAGE_NUMBER_RISE: f32 = 30.0;
+pub const DAMAGE_NUMBER_COLOR: Color = Color::WHITE;
+pub const PLAYER_DAMAGE_NUMBER_COLOR: Color = Color::srgb(1.0, 0.25, 0.25);
+// Health bars hide once an enemy hasn't been hurt for this long
+pub const HEALTH_BAR_DURATION: f32 = 3.0;
+pub const HEALTH_BAR_WIDTH: f32 = 16.0;
+pub const HEALTH_BAR_HEIGHT: f32 = 2.0;
+pub const HEALTH_BAR_OFFSET: f32 = 14.0;
+pub const HEALTH_BAR_BACKGROUND: Color = Color::srgba(0.0, 0.0, 0.0, 0.6);
+pub const HEALTH_BAR_FILL: Color = Color::srgb(0.85, 0.15, 0.15);
 
 // Experience Mechanic
 pub const BASE_ENEMY_XP: u32 = 10;
 pub const EXPERIENCE_SPEED: f32 = 200.;
+// Drops are pulled in from the pickup radius stat, and picked up at this reach while that stat is at its base,
+// the reach grows along with the stat
 pub const EXPERIENCE_RADIUS: f32 = 50.;
+pub const EXPERIENCE_PICKUP_RADIUS: f32 = 10.;
+// Orbs this close together merge every interval, past the cap new experience joins the nearest orb
+pub const EXPERIENCE_MERGE_RADIUS: f32 = 24.;
+pub const EXPERIENCE_MERGE_INTERVAL: f32 = 0.5;
+pub const MAX_EXPERIENCE_ORBS: usize = 300;
+// Orbs worth at least this much are drawn at this scale
+pub const EXPERIENCE_SIZE_TIERS: [(u32, f32); 4] = [(0, 1.0), (50, 1.4), (200, 1.8), (1000, 2.4)];
+
+// Pickups, bombs damage every enemy on screen
+pub const POTION_HEAL: f32 = 3.0;
+pub const BOMB_DAMAGE: f32 = 200.0;
+pub const PICKUP_LIFETIME: f32 = 35.0;
+
+// Bosses, phases start once the boss's health fraction drops below each threshold
+pub const BOSS_HEALTH_MULTIPLIER: f32 = 40.0;
+pub const BOSS_XP_MULTIPLIER: u32 = 10;
+pub const BOSS_SCALE: f32 = 2.5;
+pub const BOSS_GOLD: u32 = 25;
+pub const BOSS_PHASE_THRESHOLDS: [f32; 2] = [0.66, 0.33];
+
+// Level up offers, charges are granted every interval levels
+pub const STARTING_REROLLS: u32 = 1;
+pub const STARTING_BANISHES: u32 = 0;
+pub const REROLL_LEVEL_INTERVAL: u32 = 3;
+pub const BANISH_LEVEL_INTERVAL: u32 = 5;
+pub const SKIP_HEAL: f32 = 2.0;
+pub const SKIP_EXPERIENCE: u32 = 40;
+// Chance of a passive stat card being offered next to the gems
+pub const STAT_CARD_CHANCE: f64 = 0.4;
+
+// Wand
+pub const STARTING_WAND_SLOTS: usize = 6;
+// The shop stops selling slots here, so the wand still fits on one row of the wand editor
+pub const MAX_WAND_SLOTS: usize = 12;
+pub const STASH_SLOTS: usize = 3;
+
+// Shop, prices grow by this fraction every wave
+pub const SHOP_PRICE_PER_WAVE: f32 = 0.2;
+pub const SHOP_GEM_COUNT: usize = 3;
+pub const GEM_PRICES: [u32; 4] = [10, 20, 40, 80];
+pub const WAND_SLOT_PRICE: u32 = 30;
+pub const HEAL_PRICE: u32 = 15;
+pub const SHOP_HEAL: f32 = 3.0;
+pub const REMOVE_GEM_PRICE: u32 = 10;
+
+// Gem offers, weights are indexed by rarity: common, uncommon, rare, legendary
+pub const RARITY_BASE_WEIGHTS: [f32; 4] = [60.0, 30.0, 9.0, 1.0];
+pub const RARITY_WEIGHT_PER_WAVE: [f32; 4] = [-2.0, 0.5, 1.0, 0.4];
+pub const RARITY_WEIGHT_PER_LEVEL: [f32; 4] = [-1.0, 0.5, 0.5, 0.2];
+pub const RARE_PITY_OFFERS: u32 = 4;
+
+// Replays, the recording is written to disk this often so a crash loses at most this many seconds
+pub const REPLAY_FLUSH_INTERVAL: f32 = 5.0;
@@ -10,7 +10,10 @@ use bevy::prelude::*;
 
 use crate::{screen::GameState, AppSet};
 
-use super::{audio::sfx::Sfx, player_mods::movement::PlayerMovement};
+use super::{
+    audio::sfx::Sfx,
+    player_mods::{dash::Dash, death::Dying, movement::PlayerMovement},
+};
 
 pub(super) fn plugin(app: &mut App) {
     // Animate and play sound effects based on controls.
@@ -36,15 +39,27 @@ pub(super) fn plugin(app: &mut App) {
 
 /// Update the sprite direction and animation state (idling/walking).
 fn update_player_animation_movement(
-    mut player_query: Query<(&PlayerMovement, &mut Sprite, &mut PlayerAnimation)>,
+    mut player_query: Query<(
+        &PlayerMovement,
+        &mut Sprite,
+        &mut PlayerAnimation,
+        Option<&Dash>,
+        Has<Dying>,
+    )>,
 ) {
-    for (controller, mut sprite, mut animation) in &mut player_query {
+    for (controller, mut sprite, mut animation, dash, dying) in &mut player_query {
+        if dying {
+            animation.update_state(PlayerAnimationState::Death);
+            continue;
+        }
         let dx = controller.0.x;
         if dx != 0.0 {
             sprite.flip_x = dx < 0.0;
         }
 
-        let animation_state = if controller.0 == Vec2::ZERO {
+        let animation_state = if dash.is_some_and(|dash| dash.is_dashing()) {
+            PlayerAnimationState::Dashing
+        } else if controller.0 == Vec2::ZERO {
             PlayerAnimationState::Idling
         } else {
             PlayerAnimationState::Walking
@@ -112,6 +127,7 @@ pub struct PlayerAnimation {
 pub enum PlayerAnimationState {
     Idling,
     Walking,
+    Dashing,
     Death,
 }
 
@@ -142,15 +158,29 @@ impl PlayerAnimation {
         }
     }
 
-    /// The number of walking frames.
-    #[allow(dead_code)]
+    /// The sheet has no dash frames, so the dash plays the stride of the walk from this frame.
+    const DASH_FIRST_FRAME: usize = 2;
+    /// The number of dash frames.
+    const DASH_FRAMES: usize = 4;
+    /// Short, so the whole stride plays out during the dash.
+    const DASH_INTERVAL: Duration = Duration::from_millis(35);
+
+    fn dashing() -> Self {
+        Self {
+            timer: Timer::new(Self::DASH_INTERVAL, TimerMode::Repeating),
+            frame: 0,
+            state: PlayerAnimationState::Dashing,
+        }
+    }
+
+    /// The number of death frames.
     const DEATH_FRAMES: usize = 4;
-    /// The duration of each walking frame.
+    /// The duration of each death frame.
     const DEATH_INTERVAL: Duration = Duration::from_millis(100);
 
     fn death() -> Self {
         Self {
-            timer: Timer::new(Self::DEATH_INTERVAL, TimerMode::Once),
+            timer: Timer::new(Self::DEATH_INTERVAL, TimerMode::Repeating),
             frame: 0,
             state: PlayerAnimationState::Death,
         }
@@ -166,12 +196,14 @@ impl PlayerAnimation {
         if !self.timer.finished() {
             return;
         }
-        self.frame = (self.frame + 1)
-            % match self.state {
-                PlayerAnimationState::Idling => Self::IDLE_FRAMES,
-                PlayerAnimationState::Walking => Self::WALKING_FRAMES,
-                PlayerAnimationState::Death => Self::WALKING_FRAMES,
-            };
+        self.frame = match self.state {
+            PlayerAnimationState::Idling => (self.frame + 1) % Self::IDLE_FRAMES,
+            PlayerAnimationState::Walking => (self.frame + 1) % Self::WALKING_FRAMES,
+            // the dash holds its last frame if it outlasts the stride
+            PlayerAnimationState::Dashing => (self.frame + 1).min(Self::DASH_FRAMES - 1),
+            // the death animation stops on its last frame
+            PlayerAnimationState::Death => (self.frame + 1).min(Self::DEATH_FRAMES - 1),
+        };
     }
 
     /// Update animation state if it changes.
@@ -180,6 +212,7 @@ impl PlayerAnimation {
             match state {
                 PlayerAnimationState::Idling => *self = Self::idling(),
                 PlayerAnimationState::Walking => *self = Self::walking(),
+                PlayerAnimationState::Dashing => *self = Self::dashing(),
                 PlayerAnimationState::Death => *self = Self::death(),
             }
         }
@@ -195,6 +228,7 @@ impl PlayerAnimation {
         match self.state {
             PlayerAnimationState::Idling => self.frame,
             PlayerAnimationState::Walking => 8 + self.frame,
+            PlayerAnimationState::Dashing => 8 + Self::DASH_FIRST_FRAME + self.frame,
             PlayerAnimationState::Death => 16 + self.frame,
         }
     }
@@ -1,4 +1,5 @@
 pub mod particles;
+pub mod ron_loader;
 pub mod spell_gfx;
 
 use bevy::{
@@ -160,6 +161,7 @@ pub enum SfxAsset {
     EnemyCollision,
     LevelUp,
     PickUpExperience,
+    PickUpGold,
     PickUpGem,
     PlaceGem,
     Step1,
@@ -201,6 +203,10 @@ impl SfxAssets {
             SfxAsset::PickUpExperience,
             asset_server.load("audio/sfx/pick_up_experience.wav"),
         );
+        assets.insert(
+            SfxAsset::PickUpGold,
+            asset_server.load("audio/sfx/pick_up_gold.wav"),
+        );
         assets.insert(
             SfxAsset::PickUpGem,
             asset_server.load("audio/sfx/pick_up_gem.wav"),
@@ -0,0 +1,74 @@
+//! A loader for assets authored as RON files, like waves, characters and fusion recipes.
+
+use std::{fmt, marker::PhantomData};
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+};
+use serde::de::DeserializeOwned;
+
+/// Loads a `T` from the RON files with the given extensions.
+pub struct RonAssetLoader<T> {
+    extensions: &'static [&'static str],
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> RonAssetLoader<T> {
+    pub fn new(extensions: &'static [&'static str]) -> Self {
+        RonAssetLoader {
+            extensions,
+            _marker: PhantomData,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum RonAssetError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+}
+
+impl fmt::Display for RonAssetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RonAssetError::Io(e) => write!(f, "failed to read asset: {}", e),
+            RonAssetError::Ron(e) => write!(f, "failed to parse asset: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for RonAssetError {}
+
+impl From<std::io::Error> for RonAssetError {
+    fn from(e: std::io::Error) -> Self {
+        RonAssetError::Io(e)
+    }
+}
+
+impl From<ron::error::SpannedError> for RonAssetError {
+    fn from(e: ron::error::SpannedError) -> Self {
+        RonAssetError::Ron(e)
+    }
+}
+
+impl<T: Asset + DeserializeOwned> AssetLoader for RonAssetLoader<T> {
+    type Asset = T;
+    type Settings = ();
+    type Error = RonAssetError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<T, RonAssetError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        self.extensions
+    }
+}
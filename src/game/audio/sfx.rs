@@ -1,9 +1,16 @@
 use bevy::{audio::PlaybackMode, prelude::*};
 use rand::prelude::SliceRandom;
+use rand::Rng;
 
 use crate::game::assets::{SfxAsset, SfxAssets};
+use crate::game::rng::{GameRng, RngStream};
 
-pub(super) fn play_sfx(trigger: Trigger<Sfx>, mut commands: Commands, sfxs: Res<SfxAssets>) {
+pub(super) fn play_sfx(
+    trigger: Trigger<Sfx>,
+    mut commands: Commands,
+    sfxs: Res<SfxAssets>,
+    mut rng: ResMut<GameRng>,
+) {
     let event = trigger.event();
     let source = match event {
         Sfx::ButtonHover => &sfxs[&SfxAsset::ButtonHover],
@@ -12,9 +19,10 @@ pub(super) fn play_sfx(trigger: Trigger<Sfx>, mut commands: Commands, sfxs: Res<
         Sfx::EnemyCollision => &sfxs[&SfxAsset::EnemyCollision],
         Sfx::LevelUp => &sfxs[&SfxAsset::LevelUp],
         Sfx::PickUpExperience => &sfxs[&SfxAsset::PickUpExperience],
+        Sfx::PickUpGold => &sfxs[&SfxAsset::PickUpGold],
         Sfx::PickUpGem => &sfxs[&SfxAsset::PickUpGem],
         Sfx::PlaceGem => &sfxs[&SfxAsset::PlaceGem],
-        Sfx::Step => random_step(&sfxs),
+        Sfx::Step => random_step(&sfxs, rng.stream(RngStream::Cosmetic)),
         Sfx::WizardDies => &sfxs[&SfxAsset::WizardDies],
         Sfx::WizardGetsHit => &sfxs[&SfxAsset::WizardGetsHit],
     }
@@ -35,6 +43,7 @@ pub enum Sfx {
     EnemyCollision,
     LevelUp,
     PickUpExperience,
+    PickUpGold,
     PickUpGem,
     PlaceGem,
     Step,
@@ -42,13 +51,13 @@ pub enum Sfx {
     WizardGetsHit,
 }
 
-fn random_step(sfxs: &SfxAssets) -> &Handle<AudioSource> {
+fn random_step<'a>(sfxs: &'a SfxAssets, rng: &mut impl Rng) -> &'a Handle<AudioSource> {
     [
         &sfxs[&SfxAsset::Step1],
         &sfxs[&SfxAsset::Step2],
         &sfxs[&SfxAsset::Step3],
         &sfxs[&SfxAsset::Step4],
     ]
-    .choose(&mut rand::thread_rng())
+    .choose(rng)
     .unwrap()
 }
@@ -0,0 +1,209 @@
+//! A benchmark scene for dev builds, see `docs/benchmark.md`.
+//!
+//! While it runs the player can't be hurt, the wave timer stops, and the arena is
+//! kept topped up with enemies and homing projectiles. Frame times are recorded
+//! after a warmup and summed up in the "Benchmark" window.
+
+use std::sync::Arc;
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use rand::Rng;
+
+use crate::{
+    game::{
+        enemy::{EnemyCount, EnemyKind, Wave},
+        player_mods::damage::Invincibility,
+        projectiles::{ProjectileLifetime, ProjectileTeam},
+        rng::{GameRng, RngStream},
+        spawn::player::Player,
+        spawn_placement::{SpawnArea, SpawnEnemy},
+        spell_system::{
+            build_effects,
+            casting::{InstantCaster, SpellCastValues, SpellCaster},
+            spells::find_spell,
+        },
+    },
+    screen::{GameState, Screen},
+};
+
+const BENCHMARK_ENEMIES: u32 = 2000;
+const BENCHMARK_PROJECTILES: u32 = 1000;
+/// Caps how much is spawned in one frame, so topping up doesn't spike the frame times.
+const BENCHMARK_SPAWNS_PER_FRAME: u32 = 100;
+/// Seconds before frame times are recorded, while the arena fills up.
+const BENCHMARK_WARMUP: f32 = 5.0;
+const BENCHMARK_DURATION: f32 = 20.0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<BenchmarkReport>();
+    app.add_systems(Update, benchmark_window);
+    app.add_systems(
+        Update,
+        (populate_benchmark, record_benchmark)
+            .chain()
+            .run_if(resource_exists::<Benchmark>.and_then(in_state(GameState::Running))),
+    );
+    app.add_systems(OnExit(Screen::Playing), stop_benchmark);
+}
+
+/// A running benchmark.
+#[derive(Resource, Debug, Default)]
+struct Benchmark {
+    elapsed: f32,
+    frame_times: Vec<f32>,
+    enemies: u64,
+    projectiles: u64,
+}
+
+/// The results of the last benchmark.
+#[derive(Resource, Debug, Default)]
+struct BenchmarkReport(Option<String>);
+
+fn benchmark_window(
+    mut commands: Commands,
+    mut contexts: EguiContexts,
+    report: Res<BenchmarkReport>,
+    benchmark: Option<Res<Benchmark>>,
+    screen: Res<State<Screen>>,
+) {
+    egui::Window::new("Benchmark").show(contexts.ctx_mut(), |ui| {
+        if let Some(benchmark) = benchmark {
+            ui.label(format!(
+                "Running: {:.0}s / {:.0}s",
+                benchmark.elapsed,
+                BENCHMARK_WARMUP + BENCHMARK_DURATION
+            ));
+        } else if *screen.get() != Screen::Playing {
+            ui.label("Start a run to benchmark it");
+        } else if ui.button("Start").clicked() {
+            commands.add(start_benchmark);
+        }
+        if let Some(report) = &report.0 {
+            ui.label(report.as_str());
+        }
+    });
+}
+
+fn start_benchmark(world: &mut World) {
+    info!(
+        "Benchmark started with {} enemies and {} projectiles",
+        BENCHMARK_ENEMIES, BENCHMARK_PROJECTILES
+    );
+    world.insert_resource(Benchmark::default());
+    world.resource_mut::<Wave>().timer.pause();
+    let mut player_query = world.query_filtered::<Entity, With<Player>>();
+    if let Ok(player) = player_query.get_single(world) {
+        world.entity_mut(player).insert(Invincibility {
+            timer: Timer::from_seconds(3600., TimerMode::Once),
+        });
+    }
+}
+
+fn stop_benchmark(world: &mut World) {
+    if world.remove_resource::<Benchmark>().is_none() {
+        return;
+    }
+    world.resource_mut::<Wave>().timer.unpause();
+    let mut player_query = world.query_filtered::<Entity, With<Player>>();
+    if let Ok(player) = player_query.get_single(world) {
+        world.entity_mut(player).remove::<Invincibility>();
+    }
+}
+
+/// Tops the enemies and projectiles back up to the benchmark's numbers.
+fn populate_benchmark(
+    mut commands: Commands,
+    enemy_count: Res<EnemyCount>,
+    spawn_area: SpawnArea,
+    mut rng: ResMut<GameRng>,
+    player_query: Query<&GlobalTransform, With<Player>>,
+    projectile_query: Query<(), With<ProjectileLifetime>>,
+) {
+    let Ok(player) = player_query.get_single() else {
+        return;
+    };
+    let bounds = spawn_area.bounds();
+    let rng = rng.stream(RngStream::Spawning);
+
+    let missing = BENCHMARK_ENEMIES.saturating_sub(enemy_count.0);
+    // a mix of kinds, mostly basic like a real wave
+    let kinds = [
+        EnemyKind::Basic,
+        EnemyKind::Basic,
+        EnemyKind::Basic,
+        EnemyKind::Ranged,
+        EnemyKind::Tank,
+        EnemyKind::Exploder,
+    ];
+    for n in 0..missing.min(BENCHMARK_SPAWNS_PER_FRAME) {
+        commands.trigger(SpawnEnemy {
+            kind: kinds[n as usize % kinds.len()],
+            position: bounds.random_position(rng),
+        });
+    }
+
+    let missing = BENCHMARK_PROJECTILES.saturating_sub(projectile_query.iter().len() as u32);
+    let spells = ["Homing: Enemies", "Zap"]
+        .into_iter()
+        .filter_map(find_spell)
+        .collect::<Vec<_>>();
+    let effects = Arc::new(build_effects(&spells));
+    for _ in 0..missing.min(BENCHMARK_SPAWNS_PER_FRAME) {
+        let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+        commands.spawn((
+            Name::new("Benchmark Caster"),
+            SpellCaster::Instant(InstantCaster::new(
+                SpellCastValues::for_team(ProjectileTeam::Player),
+                effects.clone(),
+            )),
+            SpatialBundle::from_transform(
+                Transform::from_translation(player.translation())
+                    .with_rotation(Quat::from_rotation_z(angle)),
+            ),
+            StateScoped(Screen::Playing),
+        ));
+    }
+}
+
+fn record_benchmark(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    mut benchmark: ResMut<Benchmark>,
+    mut report: ResMut<BenchmarkReport>,
+    enemy_count: Res<EnemyCount>,
+    projectile_query: Query<(), With<ProjectileLifetime>>,
+) {
+    benchmark.elapsed += time.delta_seconds();
+    if benchmark.elapsed < BENCHMARK_WARMUP {
+        return;
+    }
+    benchmark.frame_times.push(time.delta_seconds());
+    benchmark.enemies += enemy_count.0 as u64;
+    benchmark.projectiles += projectile_query.iter().len() as u64;
+    if benchmark.elapsed < BENCHMARK_WARMUP + BENCHMARK_DURATION {
+        return;
+    }
+
+    let frames = benchmark.frame_times.len();
+    let mut frame_times = benchmark.frame_times.clone();
+    frame_times.sort_by(|a, b| b.total_cmp(a));
+    let average = frame_times.iter().sum::<f32>() / frames as f32;
+    // the slowest 1% of frames
+    let slowest = (frames / 100).max(1);
+    let worst = frame_times[..slowest].iter().sum::<f32>() / slowest as f32;
+    let summary = format!(
+        "{} frames, average {:.1} FPS ({:.2}ms), 1% low {:.1} FPS ({:.2}ms)\n\
+         {} enemies and {} projectiles on average",
+        frames,
+        1. / average,
+        average * 1000.,
+        1. / worst,
+        worst * 1000.,
+        benchmark.enemies / frames as u64,
+        benchmark.projectiles / frames as u64,
+    );
+    info!("Benchmark finished: {}", summary);
+    report.0 = Some(summary);
+    commands.add(stop_benchmark);
+}
@@ -0,0 +1,235 @@
+//! Bosses, which show up every few waves (see [`WaveSchedule`](super::waves::WaveSchedule)).
+//!
+//! A boss moves through phases as it loses health, and every phase swaps in a
+//! harder set of attacks. Killing a boss opens a gem offer with a rare gem in it.
+
+use std::sync::Arc;
+
+use bevy::color::palettes::css::{DARK_RED, RED};
+use bevy::prelude::*;
+
+use crate::{
+    config::BOSS_PHASE_THRESHOLDS,
+    game::{
+        enemy::{EnemyDeath, EnemyKind},
+        enemy_casting::{EnemyAim, EnemyWand},
+        player_mods::death::Dying,
+        projectiles::ProjectileTeam,
+        spawn::player::Player,
+        spell_system::{
+            casting::SpellCastValues,
+            spells::enemy::{AimedVolleySpell, RadialBurstSpell},
+            storage::SpellPool,
+            triggers::TimerSpellTrigger,
+            SpellEffect,
+        },
+        Damageable,
+    },
+    screen::{GameState, Screen},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Boss>();
+    app.observe(on_boss_death);
+    app.observe(remove_boss_health_bar);
+    app.add_systems(
+        Update,
+        (setup_boss, update_boss_phase, update_boss_health_bar)
+            .chain()
+            .run_if(in_state(GameState::Running)),
+    );
+}
+
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+pub struct Boss {
+    pub phase: usize,
+}
+
+/// The child of a boss that casts its attacks.
+#[derive(Component)]
+struct BossAttack;
+
+#[derive(Component)]
+struct BossHealthBar;
+
+#[derive(Component)]
+struct BossHealthFill;
+
+/// The attacks of a phase, and the seconds between them.
+fn phase_attacks(phase: usize) -> (Vec<Arc<dyn SpellEffect>>, f32) {
+    let radial =
+        |count| -> Arc<dyn SpellEffect> { Arc::new(RadialBurstSpell { count, speed: 90.0 }) };
+    let volley = |count, arc| -> Arc<dyn SpellEffect> {
+        Arc::new(AimedVolleySpell {
+            count,
+            arc,
+            speed: 140.0,
+        })
+    };
+    match phase {
+        0 => (vec![radial(12)], 3.0),
+        1 => (vec![volley(5, 40.0), radial(16)], 2.5),
+        _ => (vec![volley(7, 60.0), radial(24), volley(7, 60.0)], 1.5),
+    }
+}
+
+fn attack_trigger(phase: usize) -> TimerSpellTrigger {
+    let (spells, cooldown) = phase_attacks(phase);
+    TimerSpellTrigger {
+        values: SpellCastValues::for_team(ProjectileTeam::Enemy),
+        spells: Arc::new(spells),
+        timer: Timer::from_seconds(cooldown, TimerMode::Repeating),
+    }
+}
+
+fn setup_boss(
+    mut commands: Commands,
+    boss_query: Query<(Entity, &Boss), Added<Boss>>,
+    bar_query: Query<(), With<BossHealthBar>>,
+) {
+    // bosses share a single health bar
+    let mut has_bar = !bar_query.is_empty();
+    for (entity, boss) in &boss_query {
+        commands.entity(entity).with_children(|parent| {
+            parent.spawn((
+                Name::new("Boss Attack"),
+                SpatialBundle::default(),
+                EnemyWand,
+                EnemyAim::default(),
+                BossAttack,
+                attack_trigger(boss.phase),
+            ));
+        });
+        if !has_bar {
+            spawn_boss_health_bar(&mut commands);
+            has_bar = true;
+        }
+    }
+}
+
+fn spawn_boss_health_bar(commands: &mut Commands) {
+    commands
+        .spawn((
+            Name::new("Boss Health Bar"),
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Percent(3.),
+                    left: Val::Percent(25.),
+                    width: Val::Percent(50.),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            BossHealthBar,
+            StateScoped(Screen::Playing),
+        ))
+        .with_children(|children| {
+            children.spawn(TextBundle::from_section(
+                "Boss",
+                TextStyle {
+                    font_size: 30.,
+                    ..default()
+                },
+            ));
+            children
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.),
+                        height: Val::Px(12.),
+                        ..default()
+                    },
+                    background_color: BackgroundColor(Color::from(DARK_RED)),
+                    ..default()
+                })
+                .with_children(|children| {
+                    children.spawn((
+                        NodeBundle {
+                            style: Style {
+                                width: Val::Percent(100.),
+                                height: Val::Percent(100.),
+                                ..default()
+                            },
+                            background_color: BackgroundColor(Color::from(RED)),
+                            ..default()
+                        },
+                        BossHealthFill,
+                    ));
+                });
+        });
+}
+
+fn update_boss_phase(
+    mut boss_query: Query<(&mut Boss, &Damageable, &Children)>,
+    mut attack_query: Query<&mut TimerSpellTrigger, With<BossAttack>>,
+) {
+    for (mut boss, health, children) in &mut boss_query {
+        let fraction = health.health / health.max_health;
+        let phase = BOSS_PHASE_THRESHOLDS
+            .iter()
+            .filter(|threshold| fraction <= **threshold)
+            .count();
+        if phase == boss.phase {
+            continue;
+        }
+
+        info!("Boss entered phase {}", phase + 1);
+        boss.phase = phase;
+        for child in children {
+            if let Ok(mut trigger) = attack_query.get_mut(*child) {
+                *trigger = attack_trigger(phase);
+            }
+        }
+    }
+}
+
+fn update_boss_health_bar(
+    boss_query: Query<&Damageable, With<Boss>>,
+    mut fill_query: Query<&mut Style, With<BossHealthFill>>,
+) {
+    // with more than one boss alive, the bar shows the healthiest one
+    let fraction = boss_query
+        .iter()
+        .map(|health| health.health / health.max_health)
+        .fold(0., f32::max);
+    for mut style in &mut fill_query {
+        style.width = Val::Percent(fraction.clamp(0., 1.) * 100.);
+    }
+}
+
+/// Takes the health bar down with the last boss, however it was removed.
+fn remove_boss_health_bar(
+    trigger: Trigger<OnRemove, Boss>,
+    mut commands: Commands,
+    boss_query: Query<Entity, With<Boss>>,
+    bar_query: Query<Entity, With<BossHealthBar>>,
+) {
+    // the boss being removed still has its component here
+    if boss_query.iter().any(|boss| boss != trigger.entity()) {
+        return;
+    }
+    for bar in &bar_query {
+        commands.entity(bar).despawn_recursive();
+    }
+}
+
+fn on_boss_death(
+    trigger: Trigger<EnemyDeath>,
+    mut pool: ResMut<SpellPool>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+    dying_query: Query<(), (With<Player>, With<Dying>)>,
+) {
+    if trigger.event().kind != EnemyKind::Boss {
+        return;
+    }
+
+    info!("Boss defeated");
+    pool.guarantee_rare();
+    // the boss's reward can't be taken by a dying player
+    if dying_query.is_empty() {
+        next_game_state.set(GameState::GemSelection);
+    }
+}
@@ -0,0 +1,303 @@
+//! Playable characters, each with their own starting wand, stats and passive.
+//!
+//! Characters are authored in [`CHARACTERS_PATH`]. A character with an unlock condition
+//! stays locked until the player's [`Progress`] over every run so far meets it. Native
+//! builds keep the progress in [`PROGRESS_PATH`], web builds only for the session.
+
+#[cfg(not(target_family = "wasm"))]
+use std::{fs, path::Path};
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::STARTING_WAND_SLOTS,
+    game::{
+        assets::ron_loader::RonAssetLoader,
+        enemy::{EnemyDeath, Wave},
+        levelling::PlayerLevel,
+        player_mods::{health::HealEvent, stats::PlayerStats},
+        replay::{is_playing_back, ReplayMode},
+        spawn::player::Player,
+    },
+    screen::{GameState, Screen},
+};
+
+pub const CHARACTERS_PATH: &str = "characters/roster.characters.ron";
+#[cfg(not(target_family = "wasm"))]
+pub const PROGRESS_PATH: &str = "saves/progress.ron";
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<CharacterRoster>();
+    app.register_asset_loader(RonAssetLoader::<CharacterRoster>::new(&["characters.ron"]));
+    app.register_type::<Passive>();
+    app.init_resource::<SelectedCharacter>();
+    app.insert_resource(Progress::load());
+    app.observe(on_enemy_death);
+    // watching a replay doesn't count towards unlocks
+    app.add_systems(
+        Update,
+        track_progress.run_if(in_state(GameState::Running).and_then(not(is_playing_back))),
+    );
+    app.add_systems(OnExit(Screen::Playing), save_progress);
+}
+
+#[derive(Asset, TypePath, Deserialize, Debug, Clone, Default)]
+pub struct CharacterRoster {
+    pub characters: Vec<CharacterDefinition>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct CharacterDefinition {
+    pub name: String,
+    pub description: String,
+    pub wand: StartingWand,
+    #[serde(default)]
+    pub stats: BaseStats,
+    #[serde(default)]
+    pub passive: Passive,
+    /// Locked until this is met, unlocked from the start if there's none.
+    #[serde(default)]
+    pub unlock: Option<Unlock>,
+}
+
+impl Default for CharacterDefinition {
+    fn default() -> Self {
+        CharacterDefinition {
+            name: String::from("Wizard"),
+            description: String::from("A wizard with a trusty Zap."),
+            wand: StartingWand {
+                gems: vec![String::from("Zap")],
+                slots: STARTING_WAND_SLOTS,
+            },
+            stats: BaseStats::default(),
+            passive: Passive::None,
+            unlock: None,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct StartingWand {
+    /// Gem names making up the wand, in order.
+    pub gems: Vec<String>,
+    #[serde(default = "default_wand_slots")]
+    pub slots: usize,
+}
+
+fn default_wand_slots() -> usize {
+    STARTING_WAND_SLOTS
+}
+
+/// Replaces the base value of a stat, stats left out keep the usual base.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct BaseStats {
+    pub max_health: Option<f32>,
+    pub move_speed: Option<f32>,
+    pub pickup_radius: Option<f32>,
+    pub cast_delay_reduction: Option<f32>,
+    pub damage: Option<f32>,
+    pub regen: Option<f32>,
+    pub armor: Option<f32>,
+}
+
+impl BaseStats {
+    pub fn player_stats(&self) -> PlayerStats {
+        let mut stats = PlayerStats::default();
+        for (value, base) in [
+            (&mut stats.max_health, self.max_health),
+            (&mut stats.move_speed, self.move_speed),
+            (&mut stats.pickup_radius, self.pickup_radius),
+            (&mut stats.cast_delay_reduction, self.cast_delay_reduction),
+            (&mut stats.damage, self.damage),
+            (&mut stats.regen, self.regen),
+            (&mut stats.armor, self.armor),
+        ] {
+            if let Some(base) = base {
+                value.base = base;
+            }
+        }
+        stats
+    }
+}
+
+/// Something only one character can do, added to the player.
+#[derive(Component, Deserialize, Debug, Clone, Copy, Default, PartialEq, Reflect)]
+#[reflect(Component)]
+pub enum Passive {
+    #[default]
+    None,
+    /// Heals this much for every enemy killed.
+    Vampirism(f32),
+    /// Multiplies the gold picked up.
+    Greed(f32),
+    /// Multiplies the dash cooldown.
+    Nimble(f32),
+}
+
+impl Passive {
+    pub fn description(&self) -> String {
+        match self {
+            Passive::None => String::from("No passive"),
+            Passive::Vampirism(heal) => format!("Heals {} for every kill", heal),
+            Passive::Greed(multiplier) => format!("Picks up {}x gold", multiplier),
+            Passive::Nimble(multiplier) => {
+                format!("Dashes {:.0}% more often", (1. / multiplier - 1.) * 100.)
+            }
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unlock {
+    ReachWave(u32),
+    ReachLevel(u32),
+    /// Enemies killed over every run.
+    Kills(u32),
+}
+
+impl Unlock {
+    pub fn is_met(&self, progress: &Progress) -> bool {
+        match *self {
+            Unlock::ReachWave(wave) => progress.best_wave >= wave,
+            Unlock::ReachLevel(level) => progress.best_level >= level,
+            Unlock::Kills(kills) => progress.kills >= kills,
+        }
+    }
+
+    pub fn description(&self) -> String {
+        match self {
+            Unlock::ReachWave(wave) => format!("Reach wave {}", wave),
+            Unlock::ReachLevel(level) => format!("Reach level {}", level),
+            Unlock::Kills(kills) => format!("Kill {} enemies", kills),
+        }
+    }
+}
+
+/// The roster used by every run, loaded on the loading screen.
+#[derive(Resource, Debug)]
+pub struct CharacterRosterHandle(pub Handle<CharacterRoster>);
+
+/// The name of the character the next run is played as.
+#[derive(Resource, Debug, Clone, PartialEq, Eq)]
+pub struct SelectedCharacter(pub String);
+
+impl Default for SelectedCharacter {
+    fn default() -> Self {
+        SelectedCharacter(CharacterDefinition::default().name)
+    }
+}
+
+/// Looks up characters in the loaded roster, falling back to
+/// [`CharacterDefinition::default`] if it failed to load.
+#[derive(SystemParam)]
+pub struct Characters<'w> {
+    rosters: Res<'w, Assets<CharacterRoster>>,
+    handle: Option<Res<'w, CharacterRosterHandle>>,
+    selected: Res<'w, SelectedCharacter>,
+}
+
+impl Characters<'_> {
+    pub fn all(&self) -> Vec<CharacterDefinition> {
+        self.handle
+            .as_ref()
+            .and_then(|handle| self.rosters.get(&handle.0))
+            .filter(|roster| !roster.characters.is_empty())
+            .map_or_else(
+                || vec![CharacterDefinition::default()],
+                |roster| roster.characters.clone(),
+            )
+    }
+
+    pub fn selected(&self) -> CharacterDefinition {
+        let mut characters = self.all();
+        match characters
+            .iter()
+            .position(|character| character.name == self.selected.0)
+        {
+            Some(index) => characters.swap_remove(index),
+            None => {
+                warn!(
+                    "Unknown character {:?}, playing the first one",
+                    self.selected.0
+                );
+                characters.swap_remove(0)
+            }
+        }
+    }
+}
+
+/// What the player has achieved over every run, for unlocking characters.
+#[derive(Resource, Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Progress {
+    pub best_wave: u32,
+    pub best_level: u32,
+    pub kills: u32,
+}
+
+impl Progress {
+    #[cfg(not(target_family = "wasm"))]
+    fn load() -> Self {
+        let Ok(contents) = fs::read_to_string(PROGRESS_PATH) else {
+            return Progress::default();
+        };
+        ron::from_str(&contents).unwrap_or_else(|e| {
+            warn!("Failed to read progress {}: {}", PROGRESS_PATH, e);
+            Progress::default()
+        })
+    }
+
+    #[cfg(target_family = "wasm")]
+    fn load() -> Self {
+        Progress::default()
+    }
+}
+
+fn on_enemy_death(
+    _trigger: Trigger<EnemyDeath>,
+    mut commands: Commands,
+    mut progress: ResMut<Progress>,
+    replay: Res<ReplayMode>,
+    passive_query: Query<&Passive, With<Player>>,
+) {
+    if !matches!(*replay, ReplayMode::Playback(_)) {
+        progress.kills += 1;
+    }
+    if let Ok(Passive::Vampirism(heal)) = passive_query.get_single() {
+        commands.trigger(HealEvent(*heal));
+    }
+}
+
+fn track_progress(
+    mut progress: ResMut<Progress>,
+    wave: Res<Wave>,
+    level_query: Query<&PlayerLevel, With<Player>>,
+) {
+    if wave.number() > progress.best_wave {
+        progress.best_wave = wave.number();
+    }
+    if let Ok(level) = level_query.get_single() {
+        if level.level > progress.best_level {
+            progress.best_level = level.level;
+        }
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn save_progress(progress: Res<Progress>) {
+    let result = ron::to_string(progress.as_ref())
+        .map_err(std::io::Error::other)
+        .and_then(|contents| {
+            if let Some(dir) = Path::new(PROGRESS_PATH).parent() {
+                fs::create_dir_all(dir)?;
+            }
+            fs::write(PROGRESS_PATH, contents)
+        });
+    if let Err(e) = result {
+        warn!("Failed to save progress: {}", e);
+    }
+}
+
+#[cfg(target_family = "wasm")]
+fn save_progress() {}
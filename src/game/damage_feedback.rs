@@ -0,0 +1,183 @@
+//! Shows what hits do: damage numbers rising from whatever got hit, and health bars
+//! over enemies that were hurt recently. Both can be turned off in [`Settings`].
+
+use bevy::{prelude::*, sprite::Anchor};
+use rand::Rng;
+
+use crate::{
+    config::*,
+    game::{
+        enemy::Enemy,
+        projectiles::{DamageTaken, ProjectileTeam},
+        rng::{GameRng, RngStream},
+        settings::Settings,
+        Damageable,
+    },
+    screen::{GameState, Screen},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<DamageNumber>();
+    app.register_type::<HealthBar>();
+    app.observe(spawn_damage_number);
+    app.observe(show_health_bar);
+    app.add_systems(
+        Update,
+        (float_damage_numbers, update_health_bars).run_if(in_state(GameState::Running)),
+    );
+}
+
+/// Rises and fades out, then despawns.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct DamageNumber {
+    timer: Timer,
+}
+
+/// An enemy's health bar, hidden again once `timer` runs out without the enemy getting hurt.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct HealthBar {
+    bar: Entity,
+    fill: Entity,
+    timer: Timer,
+}
+
+fn spawn_damage_number(
+    trigger: Trigger<DamageTaken>,
+    mut commands: Commands,
+    settings: Res<Settings>,
+    mut rng: ResMut<GameRng>,
+) {
+    let damage = trigger.event();
+    if !settings.damage_numbers || damage.amount <= 0. {
+        return;
+    }
+    // there are no critical hits yet, so numbers are only colored by who dealt them
+    let color = match damage.source {
+        ProjectileTeam::Player => DAMAGE_NUMBER_COLOR,
+        ProjectileTeam::Enemy => PLAYER_DAMAGE_NUMBER_COLOR,
+    };
+    // small amounts would all round to the same number
+    let text = if damage.amount < 10. {
+        format!("{:.1}", damage.amount)
+    } else {
+        format!("{:.0}", damage.amount)
+    };
+    // spread out so hits in quick succession don't stack on top of each other
+    let offset = Vec3::new(
+        rng.stream(RngStream::Cosmetic).gen_range(-8.0..8.0),
+        12.,
+        5.,
+    );
+    commands.spawn((
+        Name::new("Damage Number"),
+        DamageNumber {
+            timer: Timer::from_seconds(DAMAGE_NUMBER_LIFETIME, TimerMode::Once),
+        },
+        Text2dBundle {
+            text: Text::from_section(
+                text,
+                TextStyle {
+                    font_size: 12.,
+                    color,
+                    ..default()
+                },
+            ),
+            transform: Transform::from_translation(damage.position + offset),
+            ..default()
+        },
+        StateScoped(Screen::Playing),
+    ));
+}
+
+fn float_damage_numbers(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut number_query: Query<(Entity, &mut DamageNumber, &mut Transform, &mut Text)>,
+) {
+    for (entity, mut number, mut transform, mut text) in &mut number_query {
+        if number.timer.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        transform.translation.y += DAMAGE_NUMBER_RISE * time.delta_seconds();
+        let alpha = 1. - number.timer.fraction();
+        for section in &mut text.sections {
+            section.style.color.set_alpha(alpha);
+        }
+    }
+}
+
+fn show_health_bar(
+    trigger: Trigger<DamageTaken>,
+    mut commands: Commands,
+    settings: Res<Settings>,
+    mut enemy_query: Query<(&Damageable, Option<&mut HealthBar>), With<Enemy>>,
+) {
+    let entity = trigger.entity();
+    let Ok((health, health_bar)) = enemy_query.get_mut(entity) else {
+        return;
+    };
+    if !settings.health_bars || health.health <= 0. {
+        return;
+    }
+    if let Some(mut health_bar) = health_bar {
+        health_bar.timer.reset();
+        return;
+    }
+
+    let mut fill = Entity::PLACEHOLDER;
+    let bar = commands
+        .spawn((
+            Name::new("Health Bar"),
+            SpriteBundle {
+                sprite: Sprite {
+                    color: HEALTH_BAR_BACKGROUND,
+                    custom_size: Some(Vec2::new(HEALTH_BAR_WIDTH, HEALTH_BAR_HEIGHT)),
+                    ..default()
+                },
+                transform: Transform::from_xyz(0., HEALTH_BAR_OFFSET, 1.),
+                ..default()
+            },
+        ))
+        .with_children(|children| {
+            fill = children
+                .spawn(SpriteBundle {
+                    sprite: Sprite {
+                        color: HEALTH_BAR_FILL,
+                        custom_size: Some(Vec2::new(HEALTH_BAR_WIDTH, HEALTH_BAR_HEIGHT)),
+                        anchor: Anchor::CenterLeft,
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(-HEALTH_BAR_WIDTH / 2., 0., 0.1),
+                    ..default()
+                })
+                .id();
+        })
+        .id();
+    commands.entity(entity).add_child(bar).insert(HealthBar {
+        bar,
+        fill,
+        timer: Timer::from_seconds(HEALTH_BAR_DURATION, TimerMode::Once),
+    });
+}
+
+fn update_health_bars(
+    time: Res<Time>,
+    mut commands: Commands,
+    settings: Res<Settings>,
+    mut enemy_query: Query<(Entity, &Damageable, &mut HealthBar)>,
+    mut fill_query: Query<&mut Transform>,
+) {
+    for (entity, health, mut health_bar) in &mut enemy_query {
+        if !settings.health_bars || health_bar.timer.tick(time.delta()).finished() {
+            commands.entity(health_bar.bar).despawn_recursive();
+            commands.entity(entity).remove::<HealthBar>();
+            continue;
+        }
+        if let Ok(mut transform) = fill_query.get_mut(health_bar.fill) {
+            transform.scale.x = (health.health / health.max_health).clamp(0., 1.);
+        }
+    }
+}
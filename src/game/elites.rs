@@ -0,0 +1,241 @@
+//! Elite enemies, ordinary enemies promoted with one or two affixes.
+//!
+//! Elites are tougher, tinted and tagged with their affixes, and drop more experience.
+//! The chance of a spawned enemy being an elite grows with the wave number.
+
+use bevy::prelude::*;
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{
+    config::*,
+    game::{
+        enemy::{Enemy, EnemyDeath, EnemyKind, Wave},
+        enemy_abilities::spawn_explosion,
+        enemy_behavior::EnemySpeed,
+        levelling::Experience,
+        projectiles::HitByProjectileEvent,
+        rng::{GameRng, RngStream},
+        spawn::player::Player,
+        spell_system::casting::{InstantCaster, SpellCaster},
+        Damageable,
+    },
+    screen::{GameState, Screen},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Elite>();
+    app.register_type::<Armor>();
+    app.observe(vampiric_heal);
+    app.observe(reflect_hit);
+    app.observe(volatile_death);
+    app.add_systems(Update, promote_elites.run_if(in_state(GameState::Running)));
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+pub enum Affix {
+    /// Moves faster.
+    Fast,
+    /// Ignores part of the damage it takes.
+    Armored,
+    /// Heals whenever it hurts the player.
+    Vampiric,
+    /// Sometimes shoots back when it's hit.
+    Reflective,
+    /// Explodes when it dies.
+    Volatile,
+}
+
+impl Affix {
+    const ALL: [Affix; 5] = [
+        Affix::Fast,
+        Affix::Armored,
+        Affix::Vampiric,
+        Affix::Reflective,
+        Affix::Volatile,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Affix::Fast => "Fast",
+            Affix::Armored => "Armored",
+            Affix::Vampiric => "Vampiric",
+            Affix::Reflective => "Reflective",
+            Affix::Volatile => "Volatile",
+        }
+    }
+}
+
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct Elite {
+    pub affixes: Vec<Affix>,
+}
+
+impl Elite {
+    pub fn has(&self, affix: Affix) -> bool {
+        self.affixes.contains(&affix)
+    }
+}
+
+/// Fraction of projectile damage ignored.
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub struct Armor(pub f32);
+
+/// The chance of an enemy spawned in this wave being an elite.
+fn elite_chance(wave: u32) -> f32 {
+    if wave < ELITE_FIRST_WAVE {
+        return 0.;
+    }
+    (ELITE_BASE_CHANCE + ELITE_CHANCE_PER_WAVE * (wave - ELITE_FIRST_WAVE) as f32)
+        .min(ELITE_MAX_CHANCE)
+}
+
+fn promote_elites(
+    mut commands: Commands,
+    wave: Res<Wave>,
+    mut rng: ResMut<GameRng>,
+    mut enemy_query: Query<
+        (
+            Entity,
+            &EnemyKind,
+            &mut Damageable,
+            &mut Experience,
+            &mut EnemySpeed,
+            &mut Sprite,
+            &mut Name,
+        ),
+        Added<Enemy>,
+    >,
+) {
+    let chance = elite_chance(wave.number());
+    if chance <= 0. {
+        return;
+    }
+    for (entity, kind, mut health, mut xp, mut speed, mut sprite, mut name) in &mut enemy_query {
+        if *kind == EnemyKind::Boss {
+            continue;
+        }
+        let rng = rng.stream(RngStream::Spawning);
+        if rng.gen::<f32>() >= chance {
+            continue;
+        }
+        let count = if rng.gen::<f32>() < ELITE_SECOND_AFFIX_CHANCE {
+            2
+        } else {
+            1
+        };
+        let affixes = Affix::ALL
+            .choose_multiple(rng, count)
+            .copied()
+            .collect::<Vec<_>>();
+
+        health.max_health *= ELITE_HEALTH_MULTIPLIER;
+        health.health = health.max_health;
+        xp.0 *= ELITE_XP_MULTIPLIER;
+        sprite.color = ELITE_TINT;
+        let tag = affixes
+            .iter()
+            .map(|affix| affix.name())
+            .collect::<Vec<_>>()
+            .join(" ");
+        *name = Name::new(format!("{} {}", tag, name.as_str()));
+
+        let mut elite = commands.entity(entity);
+        for affix in &affixes {
+            match affix {
+                Affix::Fast => speed.0 *= ELITE_FAST_SPEED,
+                Affix::Armored => {
+                    elite.insert(Armor(ELITE_ARMOR));
+                }
+                Affix::Vampiric | Affix::Reflective | Affix::Volatile => {}
+            }
+        }
+        elite.insert(Elite { affixes });
+        elite.with_children(|children| {
+            children.spawn(Text2dBundle {
+                text: Text::from_section(
+                    tag,
+                    TextStyle {
+                        font_size: 12.,
+                        color: ELITE_TINT,
+                        ..default()
+                    },
+                ),
+                transform: Transform::from_xyz(0., 22., 1.),
+                ..default()
+            });
+        });
+    }
+}
+
+/// Vampiric elites heal a flat amount every time they hurt the player.
+fn vampiric_heal(
+    trigger: Trigger<HitByProjectileEvent>,
+    player_query: Query<(), With<Player>>,
+    mut elite_query: Query<(&Elite, &mut Damageable)>,
+) {
+    if player_query.get(trigger.entity()).is_err() {
+        return;
+    }
+    let Ok((elite, mut health)) = elite_query.get_mut(trigger.event().projectile) else {
+        return;
+    };
+    if elite.has(Affix::Vampiric) {
+        health.health = (health.health + ELITE_VAMPIRIC_FLAT_HEAL).min(health.max_health);
+    }
+}
+
+/// Reflective elites sometimes fire a bolt back at the player when they're hit.
+fn reflect_hit(
+    trigger: Trigger<HitByProjectileEvent>,
+    mut commands: Commands,
+    mut rng: ResMut<GameRng>,
+    wave: Res<Wave>,
+    elite_query: Query<(&Elite, &GlobalTransform)>,
+    player_query: Query<&GlobalTransform, With<Player>>,
+) {
+    let Ok((elite, transform)) = elite_query.get(trigger.entity()) else {
+        return;
+    };
+    let Ok(player) = player_query.get_single() else {
+        return;
+    };
+    if !elite.has(Affix::Reflective)
+        || rng.stream(RngStream::Spells).gen::<f32>() >= ELITE_REFLECT_CHANCE
+    {
+        return;
+    }
+
+    let direction = player.translation() - transform.translation();
+    // scaled with the wave like the bolts of the enemy wands
+    let loadout = wave.loadout();
+    commands.spawn((
+        Name::new("Reflected Bolt"),
+        SpellCaster::Instant(InstantCaster::new(
+            loadout.values.clone(),
+            loadout.bolt.clone(),
+        )),
+        SpatialBundle::from_transform(
+            Transform::from_translation(transform.translation())
+                .with_rotation(Quat::from_rotation_z(-direction.x.atan2(direction.y))),
+        ),
+        StateScoped(Screen::Playing),
+    ));
+}
+
+fn volatile_death(
+    trigger: Trigger<EnemyDeath>,
+    mut commands: Commands,
+    wave: Res<Wave>,
+    elite_query: Query<&Elite>,
+) {
+    let death = trigger.event();
+    if elite_query
+        .get(death.entity)
+        .is_ok_and(|elite| elite.has(Affix::Volatile))
+    {
+        let damage = EXPLODER_DAMAGE * wave.definition().stats.damage;
+        spawn_explosion(&mut commands, death.position, damage);
+    }
+}
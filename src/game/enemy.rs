@@ -4,21 +4,26 @@ use avian2d::collision::Collider;
 use avian2d::prelude::{CollisionLayers, LinearVelocity, LockedAxes, RigidBody};
 use bevy::utils::HashMap;
 use bevy::{app::App, math::vec3, prelude::*, time::common_conditions::on_timer};
-use rand::Rng;
-use std::{f32::consts::PI, ops::Neg, time::Duration};
+use rand::seq::SliceRandom;
+use std::time::Duration;
 
 use super::animation::EnemyAnimation;
-use super::ItemDrop;
-use crate::game::enemy_casting::add_enemy_aim;
+use crate::game::enemy_casting::{add_enemy_aim, EnemyLoadout};
 use crate::{
     config::*,
     game::{
         assets::{ImageAsset, ImageAssets},
-        levelling::Experience,
+        boss::Boss,
+        enemy_abilities::{Exploder, Healer, Splitter, Summoner},
+        enemy_behavior::{Charge, Chase, Circle, EnemySpeed, FleeWhenLow, KeepDistance},
+        levelling::{Experience, SpawnExperience},
         physics::GameLayer,
-        player_mods::health::HealEvent,
+        player_mods::{death::player_not_dying, health::HealEvent},
         projectiles::{ProjectileDamage, ProjectileTeam},
+        rng::{GameRng, RngStream},
         spawn::player::Player,
+        spawn_placement::{telegraph_spawn, SpawnArea, SpawnEnemy, SpawnTelegraph},
+        waves::{EnemyScaling, KindCounts, WaveDefinition, WaveEvent, Waves},
         Damageable,
     },
     screen::{GameState, Screen},
@@ -26,84 +31,108 @@ use crate::{
 
 pub(super) fn plugin(app: &mut App) {
     app.observe(clear_wave);
+    app.observe(dissolve_wave);
+    app.observe(spawn_enemy);
+    app.observe(count_added_enemy);
+    app.observe(count_removed_enemy);
     app.register_type::<Enemy>();
     app.register_type::<EnemyKind>();
     app.init_resource::<Wave>();
     app.init_resource::<WaveState>();
+    app.init_resource::<EnemyCount>();
     app.add_systems(Startup, setup);
     app.add_systems(OnEnter(Screen::Playing), reset_wave);
     app.add_systems(
         Update,
         (
-            tick_wave,
+            tick_wave.run_if(player_not_dying),
+            run_wave_events.run_if(resource_equals(WaveState::Active)),
             spawn_enemies.run_if(
                 on_timer(Duration::from_secs_f32(1.)).and_then(resource_equals(WaveState::Active)),
             ),
-            chase_player,
             clear_dead_enemies,
         )
             .run_if(in_state(GameState::Running)),
     );
 }
 
+/// The current wave, defined by the wave schedule.
 #[derive(Resource, Debug, Clone, Reflect)]
 #[reflect(Resource)]
 pub struct Wave {
     number: u32,
-    spawn_rate_per_sec: u32,
-    max_enemies: u32,
-    ranged_enemies: u32,
-    tank_enemies: u32,
+    definition: WaveDefinition,
+    /// Built once per wave and shared by the wand of every ranged enemy spawned in it.
+    #[reflect(ignore)]
+    loadout: EnemyLoadout,
+    /// Index of the first event of the definition that hasn't happened yet.
+    next_event: usize,
     pub timer: Timer,
 }
 
 impl Wave {
-    fn new(num: u32, srps: u32, me: u32, re: u32, te: u32, dur: f64) -> Self {
+    pub fn new(number: u32, mut definition: WaveDefinition) -> Self {
+        definition.events.sort_by(|a, b| a.at().total_cmp(&b.at()));
         Wave {
-            number: num,
-            spawn_rate_per_sec: srps,
-            max_enemies: me,
-            ranged_enemies: re,
-            tank_enemies: te,
-            timer: Timer::new(Duration::from_secs_f64(dur), TimerMode::Once),
+            number,
+            loadout: EnemyLoadout::for_wave(&definition),
+            timer: Timer::new(
+                Duration::from_secs_f32(definition.duration),
+                TimerMode::Once,
+            ),
+            definition,
+            next_event: 0,
         }
     }
 
-    fn increment(self) -> Self {
-        match self.number {
-            // todo tweak numbers to be more balanced
-            n @ 1..=5 => Wave::new(n + 1, n * 2, n * 15, n * 2, 1, 45.),
-            n @ 6..=10 => Wave::new(n + 1, n * 2, n * 20, n * 3, n * 5, 50.),
-            n @ 11..=15 => Wave::new(n + 1, n * 3, n * 25, n * 5, n * 5, 55.),
-            n @ 16..=20 => Wave::new(n + 1, n * 3, n * 30, n * 10, n * 8, 60.),
-            n @ 21..=25 => Wave::new(n + 1, n * 4, n * 35, n * 10, n * 10, 65.),
-            n @ 26..=30 => Wave::new(n + 1, n * 4, n * 40, n * 10, n * 12, 70.),
-            n @ 31..=35 => Wave::new(n + 1, n * 5, n * 45, n * 20, n * 14, 75.),
-            n @ 36..=40 => Wave::new(n + 1, n * 5, n * 50, n * 20, n * 16, 80.),
-            n @ 41..=45 => Wave::new(n + 1, n * 6, n * 55, n * 20, n * 18, 85.),
-            n @ 46..=50 => Wave::new(n + 1, n * 6, n * 60, n * 20, n * 20, 90.),
-            51..=u32::MAX => self,
-            _ => unreachable!("Wave number out of bounds!"),
-        }
+    pub fn number(&self) -> u32 {
+        self.number
+    }
+
+    pub fn definition(&self) -> &WaveDefinition {
+        &self.definition
+    }
+
+    pub fn loadout(&self) -> &EnemyLoadout {
+        &self.loadout
+    }
+
+    /// Swaps in a new definition, keeping the time already spent in the wave.
+    pub fn redefine(&mut self, definition: WaveDefinition) {
+        let elapsed = self.timer.elapsed();
+        *self = Wave::new(self.number, definition);
+        self.timer.set_elapsed(elapsed.min(self.timer.duration()));
+        let elapsed = elapsed.as_secs_f32();
+        self.next_event = self
+            .definition
+            .events
+            .iter()
+            .take_while(|event| event.at() <= elapsed)
+            .count();
     }
 }
 
 impl Default for Wave {
     fn default() -> Self {
-        Wave {
-            number: 1,
-            spawn_rate_per_sec: 10,
-            max_enemies: 30,
-            ranged_enemies: 1,
-            tank_enemies: 1,
-            timer: Timer::new(Duration::from_secs_f64(45.), TimerMode::Once),
-        }
+        Wave::new(1, WaveDefinition::default())
     }
 }
 
 #[derive(Component)]
 pub struct WaveText;
 
+/// How many enemies are alive, kept up to date as they're added and removed.
+#[derive(Resource, Debug, Default)]
+pub struct EnemyCount(pub u32);
+
+fn count_added_enemy(_trigger: Trigger<OnAdd, Enemy>, mut count: ResMut<EnemyCount>) {
+    count.0 += 1;
+}
+
+fn count_removed_enemy(_trigger: Trigger<OnRemove, Enemy>, mut count: ResMut<EnemyCount>) {
+    count.0 = count.0.saturating_sub(1);
+}
+
 #[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
 #[reflect(Component)]
 pub struct Enemy;
@@ -113,14 +142,26 @@ pub struct Enemy;
 pub enum EnemyKind {
     #[default]
     Basic,
-    Ranged {
-        proximity: u32,
-    },
+    Ranged,
     Tank,
+    Boss,
+    Exploder,
+    Splitter,
+    Healer,
+    Summoner,
 }
 
+/// Triggered when an enemy dies, before it's despawned.
+#[derive(Event, Debug)]
+pub struct EnemyDeath {
+    pub entity: Entity,
+    pub kind: EnemyKind,
+    pub position: Vec3,
+}
+
+/// An enemy, moving the way its behavior components say (see [`enemy_behavior`](super::enemy_behavior)).
 #[derive(Bundle)]
-struct EnemyBundle {
+pub(super) struct EnemyBundle<B: Bundle> {
     name: Name,
     tag: Enemy,
     breed: EnemyKind,
@@ -134,13 +175,15 @@ struct EnemyBundle {
     rigid_body: RigidBody,
     linear_velocity: LinearVelocity,
     damage: ProjectileDamage,
+    speed: EnemySpeed,
+    behavior: B,
 }
 
-impl EnemyBundle {
-    fn basic(x: f32, y: f32, diff: u32, sprites: &Res<ImageAssets>) -> Self {
-        let hp_modifier = diff as f32 * 1.1;
-        let xp_modifier = (diff as f32 * 1.02).floor() as u32;
-        let dmg_modifier = diff as f32 * 1.125;
+impl EnemyBundle<Chase> {
+    pub(super) fn basic(x: f32, y: f32, stats: &EnemyScaling, sprites: &Res<ImageAssets>) -> Self {
+        let hp_modifier = stats.health;
+        let xp_modifier = stats.experience;
+        let dmg_modifier = stats.damage;
         EnemyBundle {
             name: Name::new("Enemy"),
             tag: Enemy,
@@ -151,7 +194,7 @@ impl EnemyBundle {
                 team: ProjectileTeam::Enemy,
                 invincibility_timer: Some(Duration::from_secs_f32(0.05)),
             },
-            xp: Experience(BASE_ENEMY_XP * xp_modifier),
+            xp: Experience((BASE_ENEMY_XP as f32 * xp_modifier).round() as u32),
             sprite: SpriteBundle {
                 texture: sprites[&ImageAsset::BasicEnemy].clone_weak(),
                 transform: Transform::from_translation(vec3(x, y, 2.0)),
@@ -176,15 +219,38 @@ impl EnemyBundle {
                 damage: (ENEMY_DAMAGE * dmg_modifier).ceil(),
                 hits_remaining: 1000,
                 knockback_force: 0.4,
+                gem: None,
             },
+            speed: EnemySpeed::default(),
+            behavior: Chase,
         }
     }
 
-    fn ranged(x: f32, y: f32, diff: u32, sprites: &Res<ImageAssets>) -> EnemyBundle {
-        let mut ranged = Self::basic(x, y, diff, sprites);
-        ranged.breed = EnemyKind::Ranged {
-            proximity: RANGED_ENEMY_DIST,
-        };
+    /// A tank that doesn't charge, it attacks with spells instead.
+    fn boss(x: f32, y: f32, stats: &EnemyScaling, sprites: &Res<ImageAssets>) -> Self {
+        let mut boss = EnemyBundle::tank(x, y, stats, sprites).with_behavior(Chase);
+        boss.name = Name::new("Boss");
+        boss.breed = EnemyKind::Boss;
+        boss.health.max_health *= BOSS_HEALTH_MULTIPLIER;
+        boss.health.health = boss.health.max_health;
+        boss.xp.0 *= BOSS_XP_MULTIPLIER;
+        boss.sprite.transform.scale = Vec3::splat(BOSS_SCALE);
+        boss.damage.knockback_force = 5.0;
+        boss
+    }
+}
+
+impl EnemyBundle<(KeepDistance, FleeWhenLow)> {
+    fn ranged(x: f32, y: f32, stats: &EnemyScaling, sprites: &Res<ImageAssets>) -> Self {
+        let mut ranged = EnemyBundle::basic(x, y, stats, sprites).with_behavior((
+            KeepDistance {
+                distance: RANGED_ENEMY_DIST,
+            },
+            FleeWhenLow {
+                threshold: RANGED_FLEE_HEALTH,
+            },
+        ));
+        ranged.breed = EnemyKind::Ranged;
         ranged.health.max_health *= 0.75;
         ranged.sprite.texture = sprites[&ImageAsset::RangedEnemy].clone_weak();
         ranged.collision_box = Collider::circle(16.0);
@@ -192,9 +258,16 @@ impl EnemyBundle {
         ranged.damage.knockback_force = 1.5;
         ranged
     }
+}
 
-    fn tank(x: f32, y: f32, diff: u32, sprites: &Res<ImageAssets>) -> EnemyBundle {
-        let mut tank = Self::basic(x, y, diff, sprites);
+impl EnemyBundle<Charge> {
+    fn tank(x: f32, y: f32, stats: &EnemyScaling, sprites: &Res<ImageAssets>) -> Self {
+        let mut tank = EnemyBundle::basic(x, y, stats, sprites).with_behavior(Charge::new(
+            TANK_CHARGE_RANGE,
+            TANK_CHARGE_WINDUP,
+            TANK_CHARGE_DURATION,
+            TANK_CHARGE_SPEED,
+        ));
         tank.breed = EnemyKind::Tank;
         tank.health.max_health *= 1.5;
         // tank.health.invincibility_timer = Some(Duration::from_secs_f32(0.5));
@@ -206,6 +279,120 @@ impl EnemyBundle {
     }
 }
 
+impl EnemyBundle<(Chase, Exploder)> {
+    /// A fast and fragile enemy that blows up next to the player.
+    fn exploder(x: f32, y: f32, stats: &EnemyScaling, sprites: &Res<ImageAssets>) -> Self {
+        let mut exploder = EnemyBundle::basic(x, y, stats, sprites).with_behavior((
+            Chase,
+            Exploder::new(
+                EXPLODER_TRIGGER_RANGE,
+                EXPLODER_FUSE,
+                EXPLODER_DAMAGE * stats.damage,
+            ),
+        ));
+        exploder.name = Name::new("Exploder");
+        exploder.breed = EnemyKind::Exploder;
+        exploder.health.max_health *= 0.6;
+        exploder.health.health = exploder.health.max_health;
+        exploder.speed.0 *= 1.6;
+        exploder.sprite.sprite.color = Color::srgb(1.0, 0.6, 0.3);
+        exploder
+    }
+}
+
+impl EnemyBundle<(Chase, Splitter)> {
+    /// Splits into smaller splitters when it dies, `splits_left` is how many more times it can split.
+    pub(super) fn splitter(
+        x: f32,
+        y: f32,
+        stats: &EnemyScaling,
+        sprites: &Res<ImageAssets>,
+        splits_left: u32,
+    ) -> Self {
+        let size = SPLITTER_SHRINK.powi(SPLITTER_SPLITS.saturating_sub(splits_left) as i32);
+        let mut splitter = EnemyBundle::basic(x, y, stats, sprites)
+            .with_behavior((Chase, Splitter { splits_left }));
+        splitter.name = Name::new("Splitter");
+        splitter.breed = EnemyKind::Splitter;
+        splitter.health.max_health *= 1.2 * size;
+        splitter.health.health = splitter.health.max_health;
+        splitter.xp.0 = (splitter.xp.0 as f32 * size).ceil() as u32;
+        splitter.sprite.transform.scale = Vec3::splat(1.2 * size);
+        splitter.sprite.sprite.color = Color::srgb(0.5, 1.0, 0.5);
+        splitter
+    }
+}
+
+impl EnemyBundle<(KeepDistance, Healer)> {
+    fn healer(x: f32, y: f32, stats: &EnemyScaling, sprites: &Res<ImageAssets>) -> Self {
+        let mut healer = EnemyBundle::basic(x, y, stats, sprites).with_behavior((
+            KeepDistance {
+                distance: HEALER_DIST,
+            },
+            Healer {
+                radius: HEALER_RADIUS,
+                amount: HEALER_AMOUNT * stats.health,
+                timer: Timer::from_seconds(HEALER_INTERVAL, TimerMode::Repeating),
+            },
+        ));
+        healer.name = Name::new("Healer");
+        healer.breed = EnemyKind::Healer;
+        healer.health.max_health *= 0.8;
+        healer.health.health = healer.health.max_health;
+        healer.sprite.texture = sprites[&ImageAsset::RangedEnemy].clone_weak();
+        healer.sprite.sprite.color = Color::srgb(0.5, 1.0, 0.6);
+        healer.collision_box = Collider::circle(16.0);
+        healer
+    }
+}
+
+impl EnemyBundle<(Circle, Summoner)> {
+    fn summoner(x: f32, y: f32, stats: &EnemyScaling, sprites: &Res<ImageAssets>) -> Self {
+        let mut summoner = EnemyBundle::basic(x, y, stats, sprites).with_behavior((
+            Circle {
+                radius: SUMMONER_DIST,
+                clockwise: true,
+            },
+            Summoner {
+                count: SUMMONER_COUNT,
+                timer: Timer::from_seconds(SUMMONER_INTERVAL, TimerMode::Repeating),
+            },
+        ));
+        summoner.name = Name::new("Summoner");
+        summoner.breed = EnemyKind::Summoner;
+        summoner.health.max_health *= 2.0;
+        summoner.health.health = summoner.health.max_health;
+        summoner.xp.0 *= 2;
+        summoner.sprite.texture = sprites[&ImageAsset::TankEnemy].clone_weak();
+        summoner.sprite.sprite.color = Color::srgb(0.7, 0.5, 1.0);
+        summoner.collision_box = Collider::ellipse(30.0, 20.0);
+        summoner
+    }
+}
+
+impl<B: Bundle> EnemyBundle<B> {
+    /// Swaps the movement behaviors, keeping everything else.
+    fn with_behavior<C: Bundle>(self, behavior: C) -> EnemyBundle<C> {
+        EnemyBundle {
+            name: self.name,
+            tag: self.tag,
+            breed: self.breed,
+            health: self.health,
+            xp: self.xp,
+            sprite: self.sprite,
+            state: self.state,
+            collision_box: self.collision_box,
+            collision_layers: self.collision_layers,
+            locked_axes: self.locked_axes,
+            rigid_body: self.rigid_body,
+            linear_velocity: self.linear_velocity,
+            damage: self.damage,
+            speed: self.speed,
+            behavior,
+        }
+    }
+}
+
 #[derive(Resource)]
 struct EnemyAtlases {
     map: HashMap<EnemyKind, Handle<TextureAtlasLayout>>,
@@ -219,61 +406,58 @@ impl EnemyAtlases {
         let ranged_layout: TextureAtlasLayout =
             TextureAtlasLayout::from_grid(UVec2::splat(32), 4, 1, Some(UVec2::splat(0)), None);
         let ranged_handle = texture_atlas_layouts.add(ranged_layout);
-        enemy_sprites.map.insert(
-            EnemyKind::Ranged {
-                proximity: RANGED_ENEMY_DIST,
-            },
-            ranged_handle,
-        );
+        enemy_sprites
+            .map
+            .insert(EnemyKind::Healer, ranged_handle.clone());
+        enemy_sprites.map.insert(EnemyKind::Ranged, ranged_handle);
 
         let tank_layout: TextureAtlasLayout =
             TextureAtlasLayout::from_grid(UVec2::new(64, 48), 4, 1, Some(UVec2::splat(0)), None);
         let tank_handle = texture_atlas_layouts.add(tank_layout);
+        enemy_sprites
+            .map
+            .insert(EnemyKind::Summoner, tank_handle.clone());
         enemy_sprites.map.insert(EnemyKind::Tank, tank_handle);
         enemy_sprites
     }
 }
 
 #[derive(Bundle)]
-struct AnimatedEnemyBundle {
-    base: EnemyBundle,
+struct AnimatedEnemyBundle<B: Bundle> {
+    base: EnemyBundle<B>,
     texture_atlas: TextureAtlas,
     animation: EnemyAnimation,
 }
 
-impl AnimatedEnemyBundle {
+impl AnimatedEnemyBundle<(KeepDistance, FleeWhenLow)> {
     fn ranged(
         x: f32,
         y: f32,
-        diff: u32,
+        stats: &EnemyScaling,
         sprites: &Res<ImageAssets>,
         enemy_sprites: &EnemyAtlases,
     ) -> Self {
         AnimatedEnemyBundle {
-            base: EnemyBundle::ranged(x, y, diff, sprites),
+            base: EnemyBundle::ranged(x, y, stats, sprites),
             texture_atlas: TextureAtlas {
-                layout: enemy_sprites
-                    .map
-                    .get(&EnemyKind::Ranged {
-                        proximity: RANGED_ENEMY_DIST,
-                    })
-                    .unwrap()
-                    .clone(),
+                layout: enemy_sprites.map.get(&EnemyKind::Ranged).unwrap().clone(),
                 index: 0,
             },
             animation: EnemyAnimation::new(),
         }
     }
+}
 
+impl AnimatedEnemyBundle<Charge> {
     fn tank(
         x: f32,
         y: f32,
-        diff: u32,
+        stats: &EnemyScaling,
         sprites: &Res<ImageAssets>,
         enemy_sprites: &EnemyAtlases,
     ) -> Self {
         AnimatedEnemyBundle {
-            base: EnemyBundle::tank(x, y, diff, sprites),
+            base: EnemyBundle::tank(x, y, stats, sprites),
             texture_atlas: TextureAtlas {
                 layout: enemy_sprites.map.get(&EnemyKind::Tank).unwrap().clone(),
                 index: 0,
@@ -283,11 +467,71 @@ impl AnimatedEnemyBundle {
     }
 }
 
+impl AnimatedEnemyBundle<(KeepDistance, Healer)> {
+    fn healer(
+        x: f32,
+        y: f32,
+        stats: &EnemyScaling,
+        sprites: &Res<ImageAssets>,
+        enemy_sprites: &EnemyAtlases,
+    ) -> Self {
+        AnimatedEnemyBundle {
+            base: EnemyBundle::healer(x, y, stats, sprites),
+            texture_atlas: TextureAtlas {
+                layout: enemy_sprites.map.get(&EnemyKind::Healer).unwrap().clone(),
+                index: 0,
+            },
+            animation: EnemyAnimation::new(),
+        }
+    }
+}
+
+impl AnimatedEnemyBundle<(Circle, Summoner)> {
+    fn summoner(
+        x: f32,
+        y: f32,
+        stats: &EnemyScaling,
+        sprites: &Res<ImageAssets>,
+        enemy_sprites: &EnemyAtlases,
+    ) -> Self {
+        AnimatedEnemyBundle {
+            base: EnemyBundle::summoner(x, y, stats, sprites),
+            texture_atlas: TextureAtlas {
+                layout: enemy_sprites.map.get(&EnemyKind::Summoner).unwrap().clone(),
+                index: 0,
+            },
+            animation: EnemyAnimation::new(),
+        }
+    }
+}
+
+impl AnimatedEnemyBundle<Chase> {
+    /// A much bigger and tougher tank.
+    fn boss(
+        x: f32,
+        y: f32,
+        stats: &EnemyScaling,
+        sprites: &Res<ImageAssets>,
+        enemy_sprites: &EnemyAtlases,
+    ) -> Self {
+        AnimatedEnemyBundle {
+            base: EnemyBundle::boss(x, y, stats, sprites),
+            texture_atlas: TextureAtlas {
+                layout: enemy_sprites.map.get(&EnemyKind::Tank).unwrap().clone(),
+                index: 0,
+            },
+            animation: EnemyAnimation::new(),
+        }
+    }
+}
+
+/// Whether a wave is being fought, or the next one is counting down.
 #[derive(Resource, Debug, Default, PartialEq)]
 pub enum WaveState {
     #[default]
     Active,
-    Inactive,
+    /// Between waves, the next wave starts once the timer runs out.
+    Intermission(Timer),
 }
 
 #[derive(Debug, Event)]
@@ -298,34 +542,62 @@ pub struct ExpireTimer {
     pub timer: Timer,
 }
 
-fn reset_wave(mut wave: ResMut<Wave>) {
-    *wave = Wave::default();
+fn reset_wave(mut wave: ResMut<Wave>, mut wave_state: ResMut<WaveState>, waves: Waves) {
+    *wave = Wave::new(1, waves.definition(1));
+    *wave_state = WaveState::Active;
 }
 
 fn tick_wave(
-    // _trigger: Trigger<StartWave>,
     mut commands: Commands,
     time: Res<Time>,
     mut curr_wave: ResMut<Wave>,
+    mut wave_state: ResMut<WaveState>,
+    waves: Waves,
     mut wave_text_query: Query<&mut Text, With<WaveText>>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+    boss_query: Query<(), With<Boss>>,
+    telegraph_query: Query<&SpawnTelegraph>,
 ) {
-    curr_wave.timer.tick(time.delta());
+    match wave_state.as_mut() {
+        WaveState::Active => {
+            // a boss wave only ends once the boss is dead
+            if !boss_query.is_empty()
+                || telegraph_query
+                    .iter()
+                    .any(|telegraph| telegraph.kind == EnemyKind::Boss)
+            {
+                return;
+            }
+            curr_wave.timer.tick(time.delta());
+            if !curr_wave.timer.finished() {
+                return;
+            }
 
-    if curr_wave.timer.finished() {
-        commands.insert_resource(WaveState::Inactive);
-        commands.trigger(HealEvent(2.5 + (curr_wave.clone().number as f32 * 1.05)));
+            info!("Wave {} is over", curr_wave.number);
+            commands.trigger(HealEvent(2.5 + (curr_wave.number as f32 * 1.05)));
+            commands.trigger(DissolveWave);
 
-        let new_wave = curr_wave.into_inner().clone().increment();
-        commands.remove_resource::<Wave>();
-        commands.insert_resource(new_wave.clone());
+            let number = curr_wave.number + 1;
+            *curr_wave = Wave::new(number, waves.definition(number));
+            // commands.trigger(Sfx::WaveComplete);
+            let mut text = wave_text_query.single_mut();
+            text.sections[0].value = format!("Wave {:?}", curr_wave.number);
 
-        // commands.trigger(Sfx::WaveComplete);
-        let mut text = wave_text_query.single_mut();
-        text.sections[0].value = format!("Wave {:?}", new_wave.number);
-        commands.trigger(StartWave);
-    } else {
-        // Continue with current wave
-        commands.insert_resource(WaveState::Active);
+            // the countdown starts once the player leaves the shop
+            *wave_state = WaveState::Intermission(Timer::from_seconds(
+                INTERMISSION_DURATION,
+                TimerMode::Once,
+            ));
+            next_game_state.set(GameState::Shop);
+        }
+        WaveState::Intermission(timer) => {
+            timer.tick(time.delta());
+            if timer.finished() {
+                info!("Wave {} starts", curr_wave.number);
+                *wave_state = WaveState::Active;
+                commands.trigger(StartWave);
+            }
+        }
     }
 }
 
@@ -336,161 +608,218 @@ fn setup(mut commands: Commands, texture_atlas_layouts: ResMut<Assets<TextureAtl
 fn spawn_enemies(
     mut commands: Commands,
     wave: Res<Wave>,
-    wave_state: Res<WaveState>,
-    images: Res<ImageAssets>,
-    enemy_sprites_map: Res<EnemyAtlases>,
+    spawn_area: SpawnArea,
+    mut rng: ResMut<GameRng>,
     player_query: Query<&Transform, With<Player>>,
-    enemy_query: Query<&Transform, (With<Enemy>, Without<Player>)>,
+    enemy_count: Res<EnemyCount>,
+    telegraph_query: Query<&SpawnTelegraph>,
+    boss_query: Query<(), With<Boss>>,
 ) {
-    let curr_enemies = enemy_query.iter().len() as u32;
-    if curr_enemies >= wave.max_enemies
+    let definition = wave.definition();
+    // enemies that are about to spawn count towards the limit too
+    let curr_enemies = enemy_count.0 + telegraph_query.iter().len() as u32;
+    // bosses get the player's full attention
+    if curr_enemies >= definition.max_enemies
+        || !boss_query.is_empty()
+        || telegraph_query
+            .iter()
+            .any(|telegraph| telegraph.kind == EnemyKind::Boss)
         || player_query.is_empty()
-        || wave_state.eq(&WaveState::Inactive)
     {
         return;
     }
 
-    let e_sprites = enemy_sprites_map.as_ref();
-
-    let enemy_spawn_limit: u32 = (wave.max_enemies - curr_enemies).min(wave.spawn_rate_per_sec);
+    let enemy_spawn_limit: u32 = (definition.max_enemies - curr_enemies).min(definition.spawn_rate);
     let player_pos = player_query.single().translation.truncate();
-
-    for n in 0..enemy_spawn_limit {
-        let (x, y) = get_random_pos_around(player_pos);
-        let ranged_enemy_limit = wave.ranged_enemies;
-        let tank_enemy_limit = wave.tank_enemies;
-        match n {
-            _ if (0..=tank_enemy_limit).contains(&n) => commands.spawn((
-                AnimatedEnemyBundle::tank(x, y, wave.number, &images, e_sprites),
-            )),
-            _ if (tank_enemy_limit + 1..=ranged_enemy_limit).contains(&n) => {
-                let re = commands.spawn((AnimatedEnemyBundle::ranged(
-                    x,
-                    y,
-                    wave.number,
-                    &images,
-                    e_sprites,
-                ),));
-                let re_id = re.id();
-                add_enemy_aim(re_id, &mut commands);
-
-                commands.entity(re_id)
-            }
-            _ if (ranged_enemy_limit + 1..=enemy_spawn_limit).contains(&n) => {
-                commands.spawn(EnemyBundle::basic(x, y, wave.number, &images))
-            }
-            _ => unreachable!("Enemy ranges not exhaustive at wave: {wave:?}"),
-        };
+    let rng = rng.stream(RngStream::Spawning);
+    let formation = definition
+        .formations
+        .choose(rng)
+        .copied()
+        .unwrap_or_default();
+    let positions = spawn_area
+        .bounds()
+        .formation(formation, enemy_spawn_limit, player_pos, rng);
+
+    for (n, position) in positions.into_iter().enumerate() {
+        telegraph_spawn(
+            &mut commands,
+            spawn_kind(&definition.kinds, n as u32),
+            position,
+        );
     }
 }
 
-fn get_random_pos_around(pos: Vec2) -> (f32, f32) {
-    let mut rng = rand::thread_rng();
-    let angle = rng.gen_range(0.0..PI * 2.0);
-    let dist = rng.gen_range(SPAWN_RADIUS);
-
-    let offset_x = angle.cos() * dist;
-    let offset_y = angle.sin() * dist;
-
-    let random_x = pos.x + offset_x;
-    let random_y = pos.y + offset_y;
+/// Spawns the enemy a telegraph was marking.
+fn spawn_enemy(
+    trigger: Trigger<SpawnEnemy>,
+    mut commands: Commands,
+    wave: Res<Wave>,
+    images: Res<ImageAssets>,
+    enemy_sprites_map: Res<EnemyAtlases>,
+) {
+    let SpawnEnemy { kind, position } = *trigger.event();
+    let (x, y) = (position.x, position.y);
+    let definition = wave.definition();
+    let stats = &definition.stats;
+    let e_sprites = enemy_sprites_map.as_ref();
+    match kind {
+        EnemyKind::Tank => {
+            commands.spawn(AnimatedEnemyBundle::tank(x, y, stats, &images, e_sprites));
+        }
+        EnemyKind::Ranged => {
+            let re_id = commands
+                .spawn(AnimatedEnemyBundle::ranged(x, y, stats, &images, e_sprites))
+                .id();
+            add_enemy_aim(re_id, wave.loadout(), &mut commands);
+        }
+        EnemyKind::Exploder => {
+            commands.spawn(EnemyBundle::exploder(x, y, stats, &images));
+        }
+        EnemyKind::Splitter => {
+            commands.spawn(EnemyBundle::splitter(x, y, stats, &images, SPLITTER_SPLITS));
+        }
+        EnemyKind::Healer => {
+            commands.spawn(AnimatedEnemyBundle::healer(x, y, stats, &images, e_sprites));
+        }
+        EnemyKind::Summoner => {
+            commands.spawn(AnimatedEnemyBundle::summoner(
+                x, y, stats, &images, e_sprites,
+            ));
+        }
+        EnemyKind::Boss => {
+            commands.spawn((
+                AnimatedEnemyBundle::boss(x, y, stats, &images, e_sprites),
+                Boss::default(),
+            ));
+        }
+        EnemyKind::Basic => {
+            commands.spawn(EnemyBundle::basic(x, y, stats, &images));
+        }
+    }
+}
 
-    (random_x, random_y)
+/// The kind of the `n`th enemy of a spawn batch, special kinds come first and the rest are basic.
+fn spawn_kind(kinds: &KindCounts, n: u32) -> EnemyKind {
+    let counts = [
+        (EnemyKind::Tank, kinds.tanks),
+        (EnemyKind::Ranged, kinds.ranged),
+        (EnemyKind::Exploder, kinds.exploders),
+        (EnemyKind::Splitter, kinds.splitters),
+        (EnemyKind::Healer, kinds.healers),
+        (EnemyKind::Summoner, kinds.summoners),
+    ];
+    let mut limit = 0;
+    for (kind, count) in counts {
+        limit += count;
+        if n < limit {
+            return kind;
+        }
+    }
+    EnemyKind::Basic
 }
 
-//Enemies will always follow the position of the player
-pub fn chase_player(
-    player_query: Query<&GlobalTransform, With<Player>>,
-    mut enemy_query: Query<
-        (
-            &mut LinearVelocity,
-            &GlobalTransform,
-            &EnemyKind,
-            &mut Sprite,
-        ),
-        (With<Enemy>, Without<Player>),
-    >,
+fn run_wave_events(
+    mut commands: Commands,
+    mut wave: ResMut<Wave>,
+    spawn_area: SpawnArea,
+    mut rng: ResMut<GameRng>,
+    player_query: Query<&Transform, With<Player>>,
 ) {
-    if player_query.is_empty() || enemy_query.is_empty() {
+    let Ok(player) = player_query.get_single() else {
         return;
-    }
-
-    let player_pos = player_query.single().translation();
-    for (mut lvelocity, gtransform, enemy_type, mut enemy_sprite) in enemy_query.iter_mut() {
-        let player_proximity = (player_pos - gtransform.translation()).length();
-        let dir = (player_pos - gtransform.translation()).normalize();
-
-        //check if dir is pointing left:
-        enemy_sprite.flip_x = dir.x < 0.0;
-
-        let target_velocity = dir * ENEMY_SPEED;
-        match enemy_type {
-            EnemyKind::Ranged { proximity } if (*proximity as f32) > player_proximity => {
-                //test to print rages:
-                // info!("e prox: {}, p prox: {}", proximity, player_proximity);
-                lvelocity.0 = lvelocity.0.lerp(target_velocity.neg().xy(), 0.1);
+    };
+    let player_pos = player.translation.truncate();
+    let elapsed = wave.timer.elapsed_secs();
+    let bounds = spawn_area.bounds();
+
+    while let Some(event) = wave.definition.events.get(wave.next_event).cloned() {
+        if event.at() > elapsed {
+            break;
+        }
+        wave.next_event += 1;
+        let rng = rng.stream(RngStream::Spawning);
+        match event {
+            WaveEvent::Swarm {
+                count, formation, ..
+            } => {
+                info!("Swarm of {} enemies in wave {}", count, wave.number);
+                for position in bounds.formation(formation, count, player_pos, rng) {
+                    telegraph_spawn(&mut commands, EnemyKind::Basic, position);
+                }
             }
-            _ => {
-                //lerp velocity towards target velocity
-                lvelocity.0 = lvelocity.0.lerp(target_velocity.xy(), 0.1);
+            WaveEvent::Boss { .. } => {
+                info!("Boss in wave {}", wave.number);
+                telegraph_spawn(&mut commands, EnemyKind::Boss, bounds.random_position(rng));
             }
-        };
+        }
     }
 }
 
 fn clear_dead_enemies(
     mut commands: Commands,
     enemy_query: Query<
-        (&Damageable, &Transform, &Experience, Entity),
+        (&Damageable, &Transform, &Experience, &EnemyKind, Entity),
         (With<Enemy>, Without<Player>),
     >,
-    images: Res<ImageAssets>,
 ) {
     if enemy_query.is_empty() {
         return;
     }
 
     // let rng = rand::thread_rng();
-    for (health, pos, xp, enemy) in enemy_query.iter() {
+    for (health, pos, xp, kind, enemy) in enemy_query.iter() {
         let mut exp_pos = *pos;
         exp_pos.translation.z += 30.;
 
         if health.health <= 0.0 {
+            commands.trigger(EnemyDeath {
+                entity: enemy,
+                kind: *kind,
+                position: pos.translation,
+            });
             commands.entity(enemy).despawn_recursive();
-            commands.spawn((
-                Name::new("Xp drop"),
-                *xp,
-                ItemDrop,
-                SpriteBundle {
-                    texture: images[&ImageAsset::Exp].clone_weak(),
-                    transform: exp_pos,
-                    ..default()
-                },
-                ExpireTimer {
-                    timer: Timer::new(Duration::from_secs(35), TimerMode::Once),
-                },
-                Collider::circle(1.),
-                CollisionLayers::new(GameLayer::Pickups, GameLayer::Player),
-                StateScoped(Screen::Playing),
-            ));
+            commands.trigger(SpawnExperience {
+                amount: xp.0,
+                position: exp_pos.translation,
+            });
             // todo xp drops should only live for a short while
         }
     }
 }
 
+/// Removes every enemy, and every enemy that's about to spawn, without any drops.
 #[derive(Event, Debug)]
 pub struct ClearWave;
 
 fn clear_wave(
     _trigger: Trigger<ClearWave>,
     mut commands: Commands,
-    all_enemies: Query<Entity, With<Enemy>>,
+    all_enemies: Query<Entity, Or<(With<Enemy>, With<SpawnTelegraph>)>>,
 ) {
-    if all_enemies.is_empty() {
-        return;
+    for entity in &all_enemies {
+        commands.entity(entity).despawn_recursive();
     }
+}
+
+/// Ends a wave by turning every enemy left into its experience.
+#[derive(Event, Debug)]
+struct DissolveWave;
 
-    let _ = all_enemies
-        .iter()
-        .map(|e| commands.entity(e).despawn_recursive());
+fn dissolve_wave(
+    _trigger: Trigger<DissolveWave>,
+    mut commands: Commands,
+    enemy_query: Query<(Entity, &Transform, &Experience), With<Enemy>>,
+    telegraph_query: Query<Entity, With<SpawnTelegraph>>,
+) {
+    for (enemy, transform, xp) in &enemy_query {
+        commands.entity(enemy).despawn_recursive();
+        commands.trigger(SpawnExperience {
+            amount: xp.0,
+            position: transform.translation + Vec3::new(0., 0., 30.),
+        });
+    }
+    for telegraph in &telegraph_query {
+        commands.entity(telegraph).despawn_recursive();
+    }
 }
@@ -0,0 +1,248 @@
+//! What the special enemy kinds do besides moving: exploders blow up next to the
+//! player, splitters split when they die, healers heal the enemies around them and
+//! summoners call in more enemies.
+
+use std::sync::Arc;
+
+use avian2d::prelude::LinearVelocity;
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    config::{EXPLODER_RADIUS, SPLITTER_COPIES},
+    game::{
+        assets::ImageAssets,
+        enemy::{Enemy, EnemyBundle, EnemyCount, EnemyDeath, EnemyKind, Wave},
+        enemy_behavior::Chase,
+        projectiles::ProjectileTeam,
+        rng::{GameRng, RngStream},
+        spawn::player::Player,
+        spell_system::{
+            casting::{InstantCaster, SpellCastValues, SpellCaster},
+            spells::enemy::ExplosionSpell,
+        },
+        Damageable,
+    },
+    screen::{GameState, Screen},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Exploder>();
+    app.register_type::<Splitter>();
+    app.register_type::<Healer>();
+    app.register_type::<Summoner>();
+    app.observe(split_on_death);
+    app.add_systems(
+        Update,
+        (
+            light_exploders,
+            burn_exploder_fuses,
+            heal_enemies,
+            summon_enemies,
+        )
+            .run_if(in_state(GameState::Running)),
+    );
+}
+
+/// Blows up next to the player once its fuse burns out, it stops moving while the fuse burns.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct Exploder {
+    pub range: f32,
+    pub fuse: Timer,
+    pub lit: bool,
+    /// Damage dealt by the explosion.
+    pub damage: f32,
+}
+
+impl Exploder {
+    pub fn new(range: f32, fuse: f32, damage: f32) -> Self {
+        Exploder {
+            range,
+            fuse: Timer::from_seconds(fuse, TimerMode::Once),
+            lit: false,
+            damage,
+        }
+    }
+}
+
+/// Splits into smaller copies when it dies, as long as it has splits left.
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub struct Splitter {
+    pub splits_left: u32,
+}
+
+/// Heals every other enemy within `radius`.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct Healer {
+    pub radius: f32,
+    pub amount: f32,
+    pub timer: Timer,
+}
+
+/// Spawns `count` basic enemies around itself every time its timer finishes.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct Summoner {
+    pub count: u32,
+    pub timer: Timer,
+}
+
+fn light_exploders(
+    mut commands: Commands,
+    player_query: Query<&GlobalTransform, With<Player>>,
+    mut exploder_query: Query<(Entity, &GlobalTransform, &mut Exploder), Without<Player>>,
+) {
+    let Ok(player) = player_query.get_single() else {
+        return;
+    };
+    for (entity, transform, mut exploder) in &mut exploder_query {
+        if !exploder.lit && transform.translation().distance(player.translation()) < exploder.range
+        {
+            exploder.lit = true;
+            commands.entity(entity).remove::<Chase>();
+        }
+    }
+}
+
+fn burn_exploder_fuses(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut exploder_query: Query<(
+        Entity,
+        &Transform,
+        &mut Exploder,
+        &mut LinearVelocity,
+        &mut Sprite,
+    )>,
+) {
+    for (entity, transform, mut exploder, mut velocity, mut sprite) in &mut exploder_query {
+        if !exploder.lit {
+            continue;
+        }
+        velocity.0 = Vec2::ZERO;
+        // flashes faster as the fuse burns down
+        let flashes = 2. + 6. * exploder.fuse.fraction();
+        sprite.color = if (exploder.fuse.elapsed_secs() * flashes).fract() < 0.5 {
+            Color::WHITE
+        } else {
+            Color::srgb(1.0, 0.3, 0.2)
+        };
+        if !exploder.fuse.tick(time.delta()).finished() {
+            continue;
+        }
+
+        spawn_explosion(&mut commands, transform.translation, exploder.damage);
+        // blowing up isn't a kill, so nothing drops
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Blows up at `position`, damaging the player if they're close.
+pub fn spawn_explosion(commands: &mut Commands, position: Vec3, damage: f32) {
+    commands.spawn((
+        Name::new("Explosion"),
+        SpellCaster::Instant(InstantCaster::new(
+            SpellCastValues::for_team(ProjectileTeam::Enemy),
+            Arc::new(vec![Arc::new(ExplosionSpell {
+                radius: EXPLODER_RADIUS,
+                damage,
+            })]),
+        )),
+        SpatialBundle::from_transform(Transform::from_translation(position)),
+        StateScoped(Screen::Playing),
+    ));
+}
+
+fn split_on_death(
+    trigger: Trigger<EnemyDeath>,
+    mut commands: Commands,
+    wave: Res<Wave>,
+    images: Res<ImageAssets>,
+    splitter_query: Query<&Splitter>,
+) {
+    let death = trigger.event();
+    if death.kind != EnemyKind::Splitter {
+        return;
+    }
+    let Ok(splitter) = splitter_query.get(death.entity) else {
+        return;
+    };
+    if splitter.splits_left == 0 {
+        return;
+    }
+
+    let stats = &wave.definition().stats;
+    for i in 0..SPLITTER_COPIES {
+        let offset =
+            Vec2::from_angle(std::f32::consts::TAU * i as f32 / SPLITTER_COPIES as f32) * 10.;
+        let position = death.position.truncate() + offset;
+        commands.spawn(EnemyBundle::splitter(
+            position.x,
+            position.y,
+            stats,
+            &images,
+            splitter.splits_left - 1,
+        ));
+    }
+}
+
+fn heal_enemies(
+    time: Res<Time>,
+    mut healer_query: Query<(Entity, &GlobalTransform, &mut Healer)>,
+    mut enemy_query: Query<(Entity, &GlobalTransform, &mut Damageable), With<Enemy>>,
+) {
+    for (healer_entity, healer_transform, mut healer) in &mut healer_query {
+        if !healer.timer.tick(time.delta()).just_finished() {
+            continue;
+        }
+        let position = healer_transform.translation().truncate();
+        for (entity, transform, mut health) in &mut enemy_query {
+            if entity == healer_entity
+                || health.health >= health.max_health
+                || transform.translation().truncate().distance(position) > healer.radius
+            {
+                continue;
+            }
+            health.health = (health.health + healer.amount).min(health.max_health);
+        }
+    }
+}
+
+fn summon_enemies(
+    time: Res<Time>,
+    mut commands: Commands,
+    wave: Res<Wave>,
+    images: Res<ImageAssets>,
+    mut rng: ResMut<GameRng>,
+    mut summoner_query: Query<(&GlobalTransform, &mut Summoner)>,
+    enemy_count: Res<EnemyCount>,
+) {
+    let definition = wave.definition();
+    let mut alive = enemy_count.0;
+    for (transform, mut summoner) in &mut summoner_query {
+        if !summoner.timer.tick(time.delta()).just_finished() {
+            continue;
+        }
+        let position = transform.translation().truncate();
+        // summoning counts towards the wave's enemy limit
+        let count = summoner
+            .count
+            .min(definition.max_enemies.saturating_sub(alive));
+        for _ in 0..count {
+            let rng = rng.stream(RngStream::Spawning);
+            let offset = Vec2::from_angle(rng.gen_range(0.0..std::f32::consts::TAU))
+                * rng.gen_range(20.0..40.0);
+            let spawn = position + offset;
+            commands.spawn(EnemyBundle::basic(
+                spawn.x,
+                spawn.y,
+                &definition.stats,
+                &images,
+            ));
+        }
+        alive += count;
+    }
+}
@@ -0,0 +1,298 @@
+//! Enemy movement, built from behavior components.
+//!
+//! Every behavior is a component with its own system steering the enemy's
+//! [`LinearVelocity`], so enemy kinds pick their movement by picking behaviors.
+//! Behaviors run in the order they're listed in [`plugin`], and each one steers the
+//! velocity part of the way towards what it wants, so an enemy with more than one
+//! behavior moves in a blend of them. Behaviors heading for the player
+//! follow the [`FlowField`] around obstacles. The behaviors most enemies have run
+//! in parallel, since there can be thousands of enemies.
+
+use avian2d::prelude::LinearVelocity;
+use bevy::prelude::*;
+
+use crate::{
+    config::{ENEMY_SPEED, ENEMY_STEERING},
+    game::{enemy::Enemy, flow_field::FlowField, spawn::player::Player, Damageable},
+    screen::GameState,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<EnemySpeed>();
+    app.register_type::<Chase>();
+    app.register_type::<KeepDistance>();
+    app.register_type::<Circle>();
+    app.register_type::<Charge>();
+    app.register_type::<FleeWhenLow>();
+    app.add_systems(
+        Update,
+        (
+            chase,
+            keep_distance,
+            circle,
+            charge,
+            flee_when_low,
+            face_player,
+        )
+            .chain()
+            .run_if(in_state(GameState::Running)),
+    );
+}
+
+/// How fast an enemy moves, before any behavior multiplies it.
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub struct EnemySpeed(pub f32);
+
+impl Default for EnemySpeed {
+    fn default() -> Self {
+        EnemySpeed(ENEMY_SPEED)
+    }
+}
+
+/// Moves straight at the player.
+#[derive(Component, Debug, Clone, Copy, Default, Reflect)]
+#[reflect(Component)]
+pub struct Chase;
+
+/// Moves towards the player until it's `distance` away, and backs off when it's closer.
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub struct KeepDistance {
+    pub distance: f32,
+}
+
+/// Circles the player at `radius`.
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub struct Circle {
+    pub radius: f32,
+    pub clockwise: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Reflect)]
+pub enum ChargeState {
+    #[default]
+    Approaching,
+    WindingUp,
+    Charging(Vec2),
+}
+
+/// Approaches the player, stops to wind up once in `range`, then charges in a straight line.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct Charge {
+    pub range: f32,
+    /// Multiplies the enemy's speed while charging.
+    pub speed: f32,
+    pub windup: Timer,
+    pub duration: Timer,
+    pub state: ChargeState,
+}
+
+impl Charge {
+    pub fn new(range: f32, windup: f32, duration: f32, speed: f32) -> Self {
+        Charge {
+            range,
+            speed,
+            windup: Timer::from_seconds(windup, TimerMode::Once),
+            duration: Timer::from_seconds(duration, TimerMode::Once),
+            state: ChargeState::Approaching,
+        }
+    }
+}
+
+/// Runs from the player once its health drops below `threshold` of its max health.
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub struct FleeWhenLow {
+    pub threshold: f32,
+}
+
+/// How far to steer towards the wanted velocity this frame, so enemies turn as fast at any frame rate.
+fn steering(time: &Time) -> f32 {
+    1.0 - (-ENEMY_STEERING * time.delta_seconds()).exp()
+}
+
+fn steer(velocity: &mut LinearVelocity, target: Vec2, amount: f32) {
+    velocity.0 = velocity.0.lerp(target, amount);
+}
+
+fn chase(
+    time: Res<Time>,
+    flow_field: Res<FlowField>,
+    player_query: Query<&GlobalTransform, With<Player>>,
+    mut enemy_query: Query<
+        (&mut LinearVelocity, &GlobalTransform, &EnemySpeed),
+        (With<Chase>, Without<Player>),
+    >,
+) {
+    let Ok(player) = player_query.get_single() else {
+        return;
+    };
+    let player_pos = player.translation().truncate();
+    let amount = steering(&time);
+    enemy_query
+        .par_iter_mut()
+        .for_each(|(mut velocity, transform, speed)| {
+            let dir = flow_field.direction(transform.translation().truncate(), player_pos);
+            steer(&mut velocity, dir * speed.0, amount);
+        });
+}
+
+fn keep_distance(
+    time: Res<Time>,
+    flow_field: Res<FlowField>,
+    player_query: Query<&GlobalTransform, With<Player>>,
+    mut enemy_query: Query<
+        (
+            &mut LinearVelocity,
+            &GlobalTransform,
+            &EnemySpeed,
+            &KeepDistance,
+        ),
+        Without<Player>,
+    >,
+) {
+    let Ok(player) = player_query.get_single() else {
+        return;
+    };
+    let player_pos = player.translation().truncate();
+    let amount = steering(&time);
+    enemy_query
+        .par_iter_mut()
+        .for_each(|(mut velocity, transform, speed, keep_distance)| {
+            let position = transform.translation().truncate();
+            if position.distance(player_pos) < keep_distance.distance {
+                let away = (position - player_pos).normalize_or_zero();
+                steer(&mut velocity, away * speed.0, amount);
+            } else {
+                let dir = flow_field.direction(position, player_pos);
+                steer(&mut velocity, dir * speed.0, amount);
+            }
+        });
+}
+
+fn circle(
+    time: Res<Time>,
+    player_query: Query<&GlobalTransform, With<Player>>,
+    mut enemy_query: Query<
+        (&mut LinearVelocity, &GlobalTransform, &EnemySpeed, &Circle),
+        Without<Player>,
+    >,
+) {
+    let Ok(player) = player_query.get_single() else {
+        return;
+    };
+    let player_pos = player.translation().truncate();
+    let amount = steering(&time);
+    for (mut velocity, transform, speed, circle) in &mut enemy_query {
+        let offset = player_pos - transform.translation().truncate();
+        let dir = offset.normalize_or_zero();
+        let tangent = if circle.clockwise {
+            dir.perp()
+        } else {
+            -dir.perp()
+        };
+        // pull in or push out until the enemy is on the circle
+        let correction = ((offset.length() - circle.radius) / circle.radius).clamp(-1., 1.);
+        let target = (tangent + dir * correction).normalize_or_zero() * speed.0;
+        steer(&mut velocity, target, amount);
+    }
+}
+
+fn charge(
+    time: Res<Time>,
+    flow_field: Res<FlowField>,
+    player_query: Query<&GlobalTransform, With<Player>>,
+    mut enemy_query: Query<
+        (
+            &mut LinearVelocity,
+            &GlobalTransform,
+            &EnemySpeed,
+            &mut Charge,
+        ),
+        Without<Player>,
+    >,
+) {
+    let Ok(player) = player_query.get_single() else {
+        return;
+    };
+    let player_pos = player.translation().truncate();
+    let amount = steering(&time);
+    for (mut velocity, transform, speed, mut charge) in &mut enemy_query {
+        let position = transform.translation().truncate();
+        let offset = player_pos - position;
+        match charge.state {
+            ChargeState::Approaching => {
+                let dir = flow_field.direction(position, player_pos);
+                steer(&mut velocity, dir * speed.0, amount);
+                if offset.length() < charge.range {
+                    charge.windup.reset();
+                    charge.state = ChargeState::WindingUp;
+                }
+            }
+            ChargeState::WindingUp => {
+                steer(&mut velocity, Vec2::ZERO, amount);
+                if charge.windup.tick(time.delta()).finished() {
+                    // the direction is locked in, so the player can sidestep the charge
+                    charge.duration.reset();
+                    charge.state = ChargeState::Charging(offset.normalize_or_zero());
+                }
+            }
+            ChargeState::Charging(dir) => {
+                velocity.0 = dir * speed.0 * charge.speed;
+                if charge.duration.tick(time.delta()).finished() {
+                    charge.state = ChargeState::Approaching;
+                }
+            }
+        }
+    }
+}
+
+fn flee_when_low(
+    time: Res<Time>,
+    player_query: Query<&GlobalTransform, With<Player>>,
+    mut enemy_query: Query<
+        (
+            &mut LinearVelocity,
+            &GlobalTransform,
+            &EnemySpeed,
+            &Damageable,
+            &FleeWhenLow,
+        ),
+        Without<Player>,
+    >,
+) {
+    let Ok(player) = player_query.get_single() else {
+        return;
+    };
+    let player_pos = player.translation().truncate();
+    let amount = steering(&time);
+    for (mut velocity, transform, speed, health, flee) in &mut enemy_query {
+        if health.health > health.max_health * flee.threshold {
+            continue;
+        }
+        let dir = (transform.translation().truncate() - player_pos).normalize_or_zero();
+        steer(&mut velocity, dir * speed.0, amount);
+    }
+}
+
+/// Flips enemy sprites to look at the player, whichever way they're moving.
+fn face_player(
+    player_query: Query<&GlobalTransform, With<Player>>,
+    mut enemy_query: Query<(&GlobalTransform, &mut Sprite), (With<Enemy>, Without<Player>)>,
+) {
+    let Ok(player) = player_query.get_single() else {
+        return;
+    };
+    enemy_query
+        .par_iter_mut()
+        .for_each(|(transform, mut sprite)| {
+            let flip_x = player.translation().x < transform.translation().x;
+            // only touch sprites that turned around, so the rest aren't marked as changed
+            if sprite.flip_x != flip_x {
+                sprite.flip_x = flip_x;
+            }
+        });
+}
@@ -1,10 +1,16 @@
+use crate::config::{ENEMY_SPELL_DAMAGE_SCALE, ENEMY_WAND_COOLDOWN};
+use crate::game::projectiles::ProjectileTeam;
 use crate::game::spawn::player::Player;
-use crate::game::spell_system::spells::enemy::EnemySpell;
+use crate::game::spell_system::casting::SpellCastValues;
+use crate::game::spell_system::spells::find_spell;
 use crate::game::spell_system::triggers::TimerSpellTrigger;
+use crate::game::spell_system::{build_effects, SpellEffect};
+use crate::game::waves::WaveDefinition;
 use crate::screen::{GameState, Screen};
 use crate::AppSet;
 use bevy::app::{App, Update};
 use bevy::hierarchy::BuildChildren;
+use bevy::log::warn;
 use bevy::math::{Quat, Vec2, Vec3, Vec3Swizzles};
 use bevy::prelude::{
     in_state, Commands, Component, Entity, GlobalTransform, IntoSystemConfigs, Query,
@@ -28,7 +34,44 @@ pub struct EnemyWand;
 #[derive(Component, Default)]
 pub struct EnemyAim(pub Vec2);
 
-pub fn add_enemy_aim(entity: Entity, commands: &mut Commands) {
+/// The spells an enemy wand casts, built from a wave's gem names.
+#[derive(Debug, Clone, Default)]
+pub struct EnemyLoadout {
+    pub values: SpellCastValues,
+    pub spells: Arc<Vec<Arc<dyn SpellEffect>>>,
+    /// A single enemy bolt, for elites that shoot back when they're hit.
+    pub bolt: Arc<Vec<Arc<dyn SpellEffect>>>,
+}
+
+impl EnemyLoadout {
+    pub fn for_wave(definition: &WaveDefinition) -> Self {
+        let gems = definition
+            .enemy_wand
+            .iter()
+            .filter_map(|name| {
+                let spell = find_spell(name);
+                if spell.is_none() {
+                    warn!("Unknown gem {:?} in an enemy wand", name);
+                }
+                spell
+            })
+            .collect::<Vec<_>>();
+
+        let mut values = SpellCastValues::for_team(ProjectileTeam::Enemy);
+        // grows with the wave like contact damage does
+        values.damage_multiplier = ENEMY_SPELL_DAMAGE_SCALE * definition.stats.damage;
+
+        let bolt = find_spell("Enemy Bolt").into_iter().collect::<Vec<_>>();
+
+        EnemyLoadout {
+            values,
+            spells: Arc::new(build_effects(&gems)),
+            bolt: Arc::new(build_effects(&bolt)),
+        }
+    }
+}
+
+pub fn add_enemy_aim(entity: Entity, loadout: &EnemyLoadout, commands: &mut Commands) {
     commands.entity(entity).with_children(|parent| {
         parent.spawn((
             SpatialBundle::from_transform(Transform::from_translation(Vec3::ZERO)),
@@ -36,9 +79,9 @@ pub fn add_enemy_aim(entity: Entity, commands: &mut Commands) {
             EnemyAim::default(),
             StateScoped(Screen::Playing),
             TimerSpellTrigger {
-                spells: Arc::new(vec![Arc::new(EnemySpell)]),
-                timer: Timer::from_seconds(3.0, TimerMode::Repeating),
-                values: Default::default(),
+                spells: loadout.spells.clone(),
+                timer: Timer::from_seconds(ENEMY_WAND_COOLDOWN, TimerMode::Repeating),
+                values: loadout.values.clone(),
             },
         ));
     });
@@ -0,0 +1,183 @@
+//! A flow field leading enemies to the player around obstacles.
+//!
+//! The field covers the tilemap chunks around the camera (see [`ChunkManager`]),
+//! and every tile points to its neighbour that's closest to the player by walking.
+//! Colliders marked as an [`Obstacle`] block the tiles they cover.
+
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use avian2d::{
+    collision::AnyCollider,
+    prelude::{Collider, Rotation},
+};
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+
+use crate::{
+    config::FLOW_FIELD_DIRECT_RANGE,
+    game::spawn::{
+        map::{chunk_tiles, tile_to_world, world_to_tile, ChunkManager},
+        player::Player,
+    },
+    screen::{GameState, Screen},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Obstacle>();
+    app.init_resource::<FlowField>();
+    app.add_systems(OnEnter(Screen::Playing), reset_flow_field);
+    app.add_systems(
+        Update,
+        update_flow_field.run_if(in_state(GameState::Running)),
+    );
+}
+
+const NEIGHBOURS: [IVec2; 8] = [
+    IVec2::new(1, 0),
+    IVec2::new(-1, 0),
+    IVec2::new(0, 1),
+    IVec2::new(0, -1),
+    IVec2::new(1, 1),
+    IVec2::new(1, -1),
+    IVec2::new(-1, 1),
+    IVec2::new(-1, -1),
+];
+
+/// Marks an environment collider enemies have to walk around.
+#[derive(Component, Debug, Clone, Copy, Default, Reflect)]
+#[reflect(Component)]
+pub struct Obstacle;
+
+#[derive(Resource, Debug, Default)]
+pub struct FlowField {
+    /// The player's tile when the field was last built.
+    target: Option<IVec2>,
+    blocked: HashSet<IVec2>,
+    /// The direction to walk in from every reachable tile.
+    directions: HashMap<IVec2, Vec2>,
+}
+
+impl FlowField {
+    /// Which way to walk from `from` to reach `target`, going straight for it when
+    /// the field doesn't help: close to the target, outside the field, or with nothing in the way.
+    pub fn direction(&self, from: Vec2, target: Vec2) -> Vec2 {
+        let straight = (target - from).normalize_or_zero();
+        if self.blocked.is_empty() || from.distance(target) < FLOW_FIELD_DIRECT_RANGE {
+            return straight;
+        }
+        self.directions
+            .get(&world_to_tile(from))
+            .copied()
+            .unwrap_or(straight)
+    }
+
+    /// Dijkstra outwards from the target over the given tiles, diagonals can't cut
+    /// past the corner of a blocked tile.
+    fn build(&mut self, target: IVec2, tiles: HashSet<IVec2>, blocked: HashSet<IVec2>) {
+        const STRAIGHT: u32 = 10;
+        const DIAGONAL: u32 = 14;
+
+        self.target = Some(target);
+        // with nothing in the way every tile points straight at the target anyway
+        if blocked.is_empty() {
+            self.blocked.clear();
+            self.directions.clear();
+            return;
+        }
+
+        let walkable = |tile: &IVec2| tiles.contains(tile) && !blocked.contains(tile);
+        let mut costs = HashMap::new();
+        let mut queue = BinaryHeap::new();
+        costs.insert(target, 0);
+        queue.push(Reverse((0, target.x, target.y)));
+        while let Some(Reverse((cost, x, y))) = queue.pop() {
+            let tile = IVec2::new(x, y);
+            if costs.get(&tile).is_some_and(|best| *best < cost) {
+                continue;
+            }
+            for offset in NEIGHBOURS {
+                let next = tile + offset;
+                if !walkable(&next) {
+                    continue;
+                }
+                let diagonal = offset.x != 0 && offset.y != 0;
+                if diagonal
+                    && (!walkable(&(tile + IVec2::new(offset.x, 0)))
+                        || !walkable(&(tile + IVec2::new(0, offset.y))))
+                {
+                    continue;
+                }
+                let next_cost = cost + if diagonal { DIAGONAL } else { STRAIGHT };
+                if costs.get(&next).is_none_or(|best| next_cost < *best) {
+                    costs.insert(next, next_cost);
+                    queue.push(Reverse((next_cost, next.x, next.y)));
+                }
+            }
+        }
+
+        self.directions = costs
+            .iter()
+            .filter_map(|(tile, cost)| {
+                // point at the cheapest neighbour that was reached from this tile
+                let next = NEIGHBOURS
+                    .iter()
+                    .map(|offset| *tile + *offset)
+                    .filter_map(|next| costs.get(&next).map(|cost| (next, *cost)))
+                    .min_by_key(|(_, cost)| *cost)
+                    .filter(|(_, next_cost)| next_cost < cost)?;
+                let dir = (tile_to_world(next.0) - tile_to_world(*tile)).normalize();
+                Some((*tile, dir))
+            })
+            .collect();
+        self.blocked = blocked;
+    }
+}
+
+fn reset_flow_field(mut flow_field: ResMut<FlowField>) {
+    *flow_field = FlowField::default();
+}
+
+/// Rebuilds the field when the player moves to another tile, or the map or its obstacles change.
+fn update_flow_field(
+    mut flow_field: ResMut<FlowField>,
+    chunks: Res<ChunkManager>,
+    player_query: Query<&GlobalTransform, With<Player>>,
+    obstacle_query: Query<(&Collider, &Transform), With<Obstacle>>,
+    changed_obstacles: Query<(), (With<Obstacle>, Or<(Changed<Obstacle>, Changed<Transform>)>)>,
+    mut removed_obstacles: RemovedComponents<Obstacle>,
+) {
+    let Ok(player) = player_query.get_single() else {
+        return;
+    };
+    let target = world_to_tile(player.translation().truncate());
+    // only obstacles are looked at, so this stays cheap with thousands of enemies around
+    let removed = removed_obstacles.read().count() > 0;
+    if flow_field.target == Some(target)
+        && !chunks.is_changed()
+        && !removed
+        && changed_obstacles.is_empty()
+    {
+        return;
+    }
+
+    // the transform rather than the global transform or aabb, those lag a frame behind a spawn
+    let blocked = obstacle_query
+        .iter()
+        .flat_map(|(collider, transform)| {
+            let rotation = Rotation::radians(transform.rotation.to_euler(EulerRot::XYZ).2);
+            let aabb = collider.aabb(transform.translation.truncate(), rotation);
+            let min = world_to_tile(aabb.min);
+            let max = world_to_tile(aabb.max);
+            (min.y..=max.y).flat_map(move |y| (min.x..=max.x).map(move |x| IVec2::new(x, y)))
+        })
+        .collect::<HashSet<_>>();
+
+    let tiles = chunks
+        .spawned_chunks
+        .iter()
+        .flat_map(|chunk| chunk_tiles(*chunk))
+        .collect::<HashSet<_>>();
+    flow_field.build(target, tiles, blocked);
+}
@@ -0,0 +1,49 @@
+//! Gold dropped by enemies and spent in the shop between waves.
+//!
+//! Gold drops are a [`Pickup`](super::pickups::Pickup), every enemy drops some.
+
+use bevy::prelude::*;
+
+use crate::{
+    config::BOSS_GOLD,
+    game::{enemy::EnemyKind, spawn::player::Player},
+    screen::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Gold>();
+    app.add_systems(Update, update_gold_text.run_if(in_state(Screen::Playing)));
+}
+
+/// The gold the player is carrying.
+#[derive(Component, Debug, Default, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub struct Gold(pub u32);
+
+#[derive(Component)]
+pub struct GoldText;
+
+/// Gold dropped by an enemy of this kind.
+pub fn gold_for_kind(kind: &EnemyKind) -> u32 {
+    match kind {
+        EnemyKind::Basic => 1,
+        EnemyKind::Ranged => 2,
+        EnemyKind::Tank => 3,
+        EnemyKind::Boss => BOSS_GOLD,
+        EnemyKind::Exploder | EnemyKind::Splitter => 1,
+        EnemyKind::Healer => 2,
+        EnemyKind::Summoner => 3,
+    }
+}
+
+fn update_gold_text(
+    gold_query: Query<&Gold, (With<Player>, Changed<Gold>)>,
+    mut text_query: Query<&mut Text, With<GoldText>>,
+) {
+    let Ok(gold) = gold_query.get_single() else {
+        return;
+    };
+    for mut text in &mut text_query {
+        text.sections[0].value = format!("Gold {}", gold.0);
+    }
+}
@@ -17,6 +17,7 @@ pub enum PlayerAction {
     Move,
     Look,
     Shoot,
+    Dash,
 }
 
 impl PlayerAction {
@@ -28,12 +29,15 @@ impl PlayerAction {
         input_map.insert(Self::Move, DualAxis::left_stick());
         // input_map.insert(Self::Look, DualAxis::right_stick());
         input_map.insert(Self::Shoot, GamepadButtonType::RightTrigger);
+        input_map.insert(Self::Dash, GamepadButtonType::South);
 
         // Default kbm input bindings
         input_map.insert(Self::Move, VirtualDPad::wasd());
         input_map.insert(Self::Move, VirtualDPad::arrow_keys());
         input_map.insert(Self::Shoot, MouseButton::Left);
         input_map.insert(Self::Shoot, KeyCode::Space);
+        input_map.insert(Self::Dash, KeyCode::ShiftLeft);
+        input_map.insert(Self::Dash, KeyCode::ShiftRight);
 
         input_map
     }
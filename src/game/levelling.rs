@@ -1,30 +1,48 @@
-use avian2d::collision::CollidingEntities;
-use bevy::prelude::*;
+use bevy::{ecs::entity::EntityHashSet, prelude::*, time::common_conditions::on_timer};
+
+use std::time::Duration;
 
 use crate::{
-    config::{EXPERIENCE_RADIUS, EXPERIENCE_SPEED},
+    config::{
+        BANISH_LEVEL_INTERVAL, EXPERIENCE_MERGE_INTERVAL, EXPERIENCE_MERGE_RADIUS,
+        EXPERIENCE_SIZE_TIERS, EXPERIENCE_SPEED, MAX_EXPERIENCE_ORBS, REROLL_LEVEL_INTERVAL,
+        STARTING_BANISHES, STARTING_REROLLS,
+    },
     game::audio::sfx::Sfx,
-    screen::GameState,
+    screen::{GameState, Screen},
 };
 
 use super::{
+    assets::{ImageAsset, ImageAssets},
     enemy::{Enemy, ExpireTimer},
+    pickups::Pickup,
+    player_mods::{death::player_not_dying, stats::PlayerStats},
+    pooling::{release, spawn_pooled},
+    spatial_grid::SpatialGrid,
     spawn::player::Player,
+    ItemDrop,
 };
 
 pub(super) fn plugin(app: &mut App) {
     app.observe(level_up);
+    app.observe(spawn_experience);
+    app.init_resource::<OfferCharges>();
+    app.add_systems(OnEnter(Screen::Playing), reset_offer_charges);
     app.add_systems(
         Update,
         (
-            detect_player_experience_collision,
+            collect_experience.run_if(player_not_dying),
             move_experience_towards_player,
             despawn_experience_timer,
+            merge_experience.run_if(on_timer(Duration::from_secs_f32(EXPERIENCE_MERGE_INTERVAL))),
+            scale_experience,
         )
             .run_if(in_state(GameState::Running)),
     );
     app.register_type::<Experience>();
+    app.register_type::<Magnetized>();
     app.register_type::<PlayerLevel>();
+    app.register_type::<OfferCharges>();
 }
 
 #[derive(Component, Debug, Reflect)]
@@ -48,18 +66,98 @@ impl Default for PlayerLevel {
     }
 }
 
+/// How much an orb is worth. Orbs that were collected, merged or expired are emptied
+/// straight away, since they're only released once commands are applied.
 #[derive(Debug, Component, Copy, Clone, Reflect)]
 #[reflect(Component)]
 pub struct Experience(pub u32);
 
-fn detect_player_experience_collision(
+/// Pulls a drop towards the player from anywhere on the map.
+#[derive(Debug, Component, Copy, Clone, Default, Reflect)]
+#[reflect(Component)]
+pub struct Magnetized;
+
+/// Rerolls and banishes the player can spend on level up offers.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Resource)]
+pub struct OfferCharges {
+    pub rerolls: u32,
+    pub banishes: u32,
+}
+
+impl Default for OfferCharges {
+    fn default() -> Self {
+        OfferCharges {
+            rerolls: STARTING_REROLLS,
+            banishes: STARTING_BANISHES,
+        }
+    }
+}
+
+fn reset_offer_charges(mut charges: ResMut<OfferCharges>) {
+    *charges = OfferCharges::default();
+}
+
+/// Spawns an experience drop worth `amount` at `position`.
+#[derive(Event, Debug)]
+pub struct SpawnExperience {
+    pub amount: u32,
+    pub position: Vec3,
+}
+
+fn spawn_experience(
+    trigger: Trigger<SpawnExperience>,
+    mut commands: Commands,
+    images: Res<ImageAssets>,
+    mut orb_query: Query<(&mut Experience, &Transform), (With<ItemDrop>, Without<Enemy>)>,
+) {
+    let event = trigger.event();
+    // past the cap the experience joins the nearest orb instead of spawning another
+    if orb_query.iter().len() >= MAX_EXPERIENCE_ORBS {
+        let position = event.position.truncate();
+        let nearest = orb_query
+            .iter_mut()
+            .filter(|(experience, _)| experience.0 > 0)
+            .min_by(|(_, a), (_, b)| {
+                let a = a.translation.truncate().distance_squared(position);
+                let b = b.translation.truncate().distance_squared(position);
+                a.total_cmp(&b)
+            });
+        if let Some((mut experience, _)) = nearest {
+            experience.0 += event.amount;
+            return;
+        }
+    }
+    // there can be thousands of drops, so they're picked up by distance instead of colliders
+    let drop = (
+        Name::new("Xp drop"),
+        Experience(event.amount),
+        ItemDrop,
+        SpriteBundle {
+            texture: images[&ImageAsset::Exp].clone_weak(),
+            transform: Transform::from_translation(event.position),
+            ..default()
+        },
+        ExpireTimer {
+            timer: Timer::new(Duration::from_secs(35), TimerMode::Once),
+        },
+        StateScoped(Screen::Playing),
+    );
+    commands.add(move |world: &mut World| {
+        spawn_pooled::<Experience>(world, drop);
+    });
+}
+
+fn collect_experience(
     mut commands: Commands,
-    mut player_collision_query: Query<(&mut PlayerLevel, &CollidingEntities), With<Player>>,
-    exp_query: Query<(Entity, &Experience), (With<Experience>, Without<Enemy>)>,
+    mut player_query: Query<(&mut PlayerLevel, &Transform, &PlayerStats), With<Player>>,
+    mut exp_query: Query<(Entity, &mut Experience, &Transform), (With<ItemDrop>, Without<Enemy>)>,
 ) {
-    for (mut player_level, colliding_entities) in player_collision_query.iter_mut() {
-        for &colliding_entity in colliding_entities.0.iter() {
-            if let Ok((exp_entity, experience)) = exp_query.get(colliding_entity) {
+    for (mut player_level, player_transform, stats) in player_query.iter_mut() {
+        let player_pos = player_transform.translation.truncate();
+        let reach = stats.pickup_reach();
+        for (exp_entity, mut experience, exp_transform) in &mut exp_query {
+            if experience.0 > 0 && in_pickup_reach(player_pos, reach, exp_transform) {
                 info!(
                     "Exp collected: {:?}, Exp until next level: {:?}",
                     experience.0, player_level.exp_to_level_up
@@ -72,24 +170,35 @@ fn detect_player_experience_collision(
                 } else {
                     player_level.exp_to_level_up -= experience.0;
                 }
-                commands.entity(exp_entity).despawn_recursive();
+                experience.0 = 0;
+                release::<Experience>(&mut commands, exp_entity);
             }
         }
     }
 }
 
+/// Whether a drop is within the player's pickup `reach` (see [`PlayerStats::pickup_reach`]).
+pub fn in_pickup_reach(player_pos: Vec2, reach: f32, drop_transform: &Transform) -> bool {
+    drop_transform.translation.truncate().distance(player_pos) < reach
+}
+
+// pickups are pulled in and expire the same way as experience
 fn move_experience_towards_player(
     time: Res<Time>,
-    mut experience_query: Query<&mut Transform, (With<Experience>, Without<Enemy>)>,
-    player_query: Query<&Transform, (With<Player>, Without<Experience>)>,
+    mut experience_query: Query<
+        (&mut Transform, Has<Magnetized>),
+        (With<ItemDrop>, Without<Enemy>),
+    >,
+    player_query: Query<(&Transform, &PlayerStats), (With<Player>, Without<ItemDrop>)>,
 ) {
-    if let Ok(player_transform) = player_query.get_single() {
-        for mut experience_transform in experience_query.iter_mut() {
+    if let Ok((player_transform, stats)) = player_query.get_single() {
+        let pickup_radius = stats.pickup_radius.value();
+        for (mut experience_transform, magnetized) in experience_query.iter_mut() {
             let distance = experience_transform
                 .translation
                 .distance(player_transform.translation);
 
-            if distance < EXPERIENCE_RADIUS {
+            if magnetized || distance < pickup_radius {
                 let direction =
                     (player_transform.translation - experience_transform.translation).normalize();
                 experience_transform.translation +=
@@ -99,16 +208,85 @@ fn move_experience_towards_player(
     }
 }
 
+/// Merges orbs close to each other into one worth as much, keeping the entity count down.
+fn merge_experience(
+    mut commands: Commands,
+    mut orb_query: Query<
+        (Entity, &mut Experience, &Transform, &mut ExpireTimer),
+        (With<ItemDrop>, Without<Enemy>, Without<Magnetized>),
+    >,
+) {
+    let orbs: Vec<(Entity, Vec2)> = orb_query
+        .iter()
+        .filter(|(_, experience, _, _)| experience.0 > 0)
+        .map(|(entity, _, transform, _)| (entity, transform.translation.truncate()))
+        .collect();
+    // bucketed by the merge radius, every orb only looks at the cells next to its own
+    let mut grid = SpatialGrid::new(EXPERIENCE_MERGE_RADIUS);
+    for &(entity, position) in &orbs {
+        grid.insert(entity, position);
+    }
+    let mut merged = EntityHashSet::default();
+    for &(entity, position) in &orbs {
+        if merged.contains(&entity) {
+            continue;
+        }
+        let mut absorbed = 0;
+        for (other, _) in grid.within(position, EXPERIENCE_MERGE_RADIUS) {
+            if other == entity || !merged.insert(other) {
+                continue;
+            }
+            if let Ok((_, mut experience, _, _)) = orb_query.get_mut(other) {
+                absorbed += experience.0;
+                experience.0 = 0;
+            }
+            release::<Experience>(&mut commands, other);
+        }
+        if absorbed > 0 {
+            if let Ok((_, mut experience, _, mut expire_timer)) = orb_query.get_mut(entity) {
+                experience.0 += absorbed;
+                // the merged orb gets the full lifetime of a fresh drop
+                expire_timer.timer.reset();
+            }
+        }
+    }
+}
+
+/// Draws orbs bigger the more they're worth.
+fn scale_experience(
+    mut orb_query: Query<
+        (&Experience, &mut Transform),
+        (With<ItemDrop>, Without<Enemy>, Changed<Experience>),
+    >,
+) {
+    for (experience, mut transform) in &mut orb_query {
+        let scale = EXPERIENCE_SIZE_TIERS
+            .iter()
+            .rev()
+            .find(|(value, _)| experience.0 >= *value)
+            .map_or(1., |(_, scale)| *scale);
+        transform.scale = Vec3::splat(scale);
+    }
+}
+
 fn despawn_experience_timer(
     mut commands: Commands,
-    mut experience_query: Query<(Entity, &mut ExpireTimer), (With<Experience>, Without<Enemy>)>,
+    mut experience_query: Query<
+        (Entity, &mut ExpireTimer, Option<&mut Experience>),
+        (With<ItemDrop>, Without<Enemy>),
+    >,
     time: Res<Time>,
 ) {
-    for (experience, mut expire_timer) in experience_query.iter_mut() {
+    for (drop, mut expire_timer, experience) in experience_query.iter_mut() {
         expire_timer.timer.tick(time.delta());
 
         if expire_timer.timer.finished() {
-            commands.entity(experience).despawn_recursive();
+            if let Some(mut experience) = experience {
+                experience.0 = 0;
+                release::<Experience>(&mut commands, drop);
+            } else {
+                release::<Pickup>(&mut commands, drop);
+            }
         }
     }
 }
@@ -119,6 +297,7 @@ struct LevelUp;
 fn level_up(
     _trigger: Trigger<LevelUp>,
     mut commands: Commands,
+    mut charges: ResMut<OfferCharges>,
     mut player_query: Query<&mut PlayerLevel, With<Player>>,
     mut level_text_query: Query<&mut Text, With<LevelText>>,
     mut next_game_state: ResMut<NextState<GameState>>,
@@ -130,10 +309,14 @@ fn level_up(
     let mut player = player_query.single_mut();
 
     player.level += 1;
-    // damageable.max_health *= player.level as f32 * 1.05;
-    // damageable.health *= 1.001;
     info!("Player levels up to level {}", player.level);
     // todo do level up specifics here
+    if player.level.is_multiple_of(REROLL_LEVEL_INTERVAL) {
+        charges.rerolls += 1;
+    }
+    if player.level.is_multiple_of(BANISH_LEVEL_INTERVAL) {
+        charges.banishes += 1;
+    }
 
     if player.overflow >= player.exp_to_level_up {
         player.exp_to_level_up = compute_next_level(player.level);
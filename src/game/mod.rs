@@ -7,17 +7,36 @@ use std::time::Duration;
 mod animation;
 pub mod assets;
 pub mod audio;
+#[cfg(feature = "dev")]
+mod benchmark;
+pub mod boss;
 mod camera;
+pub mod characters;
+pub mod damage_feedback;
+pub mod elites;
 pub mod enemy;
+pub mod enemy_abilities;
+pub mod enemy_behavior;
 pub mod enemy_casting;
+pub mod flow_field;
+pub mod gold;
 pub mod input;
 pub mod levelling;
 pub mod lighting;
 pub mod physics;
+pub mod pickups;
 pub mod player_mods;
+pub mod pooling;
 pub mod projectiles;
+pub mod replay;
+pub mod rng;
+pub mod run_stats;
+pub mod settings;
+pub mod spatial_grid;
 pub mod spawn;
+pub mod spawn_placement;
 pub mod spell_system;
+pub mod waves;
 
 pub(super) fn plugin(app: &mut App) {
     app.add_plugins((
@@ -33,7 +52,28 @@ pub(super) fn plugin(app: &mut App) {
         projectiles::plugin,
         physics::plugin,
         player_mods::plugin,
+        rng::plugin,
+        replay::plugin,
+        gold::plugin,
     ));
+    app.add_plugins((
+        waves::plugin,
+        boss::plugin,
+        enemy_behavior::plugin,
+        enemy_abilities::plugin,
+        flow_field::plugin,
+        elites::plugin,
+        spawn_placement::plugin,
+        settings::plugin,
+        damage_feedback::plugin,
+        spatial_grid::plugin,
+        pooling::plugin,
+        characters::plugin,
+        run_stats::plugin,
+        pickups::plugin,
+    ));
+    #[cfg(feature = "dev")]
+    app.add_plugins(benchmark::plugin);
 
     app.register_type::<Damageable>();
 }
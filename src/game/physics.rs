@@ -16,7 +16,6 @@ pub enum GameLayer {
     Enemy,
     PlayerProjectile,
     EnemyProjectile,
-    Pickups,
 }
 
 fn pause(mut time: ResMut<Time<Physics>>) {
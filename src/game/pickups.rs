@@ -0,0 +1,236 @@
+//! Pickups enemies drop on death, each with its own effect when picked up.
+//!
+//! Pickups are drops like experience, pulled in, picked up by distance and pooled the same way.
+//! Every enemy drops gold, how likely it is to drop each other pickup is set by [`drop_chances`].
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    config::{BOMB_DAMAGE, PICKUP_LIFETIME, POTION_HEAL},
+    game::{
+        assets::{ImageAsset, ImageAssets},
+        audio::sfx::Sfx,
+        characters::Passive,
+        enemy::{Enemy, EnemyDeath, EnemyKind, ExpireTimer},
+        gold::{gold_for_kind, Gold},
+        levelling::{in_pickup_reach, Experience, Magnetized},
+        player_mods::{death::player_not_dying, health::HealEvent, stats::PlayerStats},
+        pooling::{release, spawn_pooled},
+        projectiles::{DamageTaken, ProjectileTeam},
+        rng::{GameRng, RngStream},
+        spawn::player::Player,
+        spawn_placement::SpawnArea,
+        spell_system::storage::SpellPool,
+        Damageable, ItemDrop,
+    },
+    screen::{GameState, Screen},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Pickup>();
+    app.observe(drop_pickup);
+    app.observe(spawn_pickup);
+    app.observe(apply_pickup);
+    app.add_systems(
+        Update,
+        collect_pickups
+            .run_if(player_not_dying)
+            .run_if(in_state(GameState::Running)),
+    );
+}
+
+/// What happens when the player picks this up.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+pub enum Pickup {
+    /// This much gold, more with the greed passive.
+    Gold(u32),
+    /// Heals the player by [`POTION_HEAL`].
+    HealthPotion,
+    /// Pulls in all experience on the map.
+    Magnet,
+    /// Deals [`BOMB_DAMAGE`] to every enemy on screen.
+    Bomb,
+    /// Opens a bonus gem selection with a guaranteed rare.
+    Chest,
+}
+
+impl Pickup {
+    fn color(&self) -> Color {
+        match self {
+            Pickup::Gold(_) => Color::srgb(1.0, 0.8, 0.2),
+            Pickup::HealthPotion => Color::srgb(1.0, 0.3, 0.3),
+            Pickup::Magnet => Color::srgb(0.4, 0.6, 1.0),
+            Pickup::Bomb => Color::srgb(0.3, 0.3, 0.3),
+            Pickup::Chest => Color::srgb(0.7, 0.45, 0.2),
+        }
+    }
+
+    /// Gold drops are common, so they're kept as small as experience.
+    fn scale(&self) -> f32 {
+        match self {
+            Pickup::Gold(_) => 1.,
+            _ => 2.,
+        }
+    }
+
+    fn sfx(&self) -> Sfx {
+        match self {
+            Pickup::Gold(_) => Sfx::PickUpGold,
+            _ => Sfx::PickUpExperience,
+        }
+    }
+}
+
+/// Chance of an enemy of this kind dropping each pickup, each one is rolled on its own.
+pub fn drop_chances(kind: &EnemyKind) -> [(Pickup, f64); 4] {
+    let [potion, magnet, bomb, chest] = match kind {
+        EnemyKind::Basic | EnemyKind::Exploder | EnemyKind::Splitter => [0.01, 0.002, 0.002, 0.0],
+        EnemyKind::Ranged => [0.02, 0.004, 0.004, 0.001],
+        EnemyKind::Tank | EnemyKind::Summoner => [0.03, 0.005, 0.005, 0.002],
+        EnemyKind::Healer => [0.05, 0.004, 0.004, 0.001],
+        // the boss already rewards a rare gem, so no chest
+        EnemyKind::Boss => [1.0, 1.0, 0.0, 0.0],
+    };
+    [
+        (Pickup::HealthPotion, potion),
+        (Pickup::Magnet, magnet),
+        (Pickup::Bomb, bomb),
+        (Pickup::Chest, chest),
+    ]
+}
+
+/// Spawns `pickup` at `position`.
+#[derive(Event, Debug)]
+pub struct SpawnPickup {
+    pub pickup: Pickup,
+    pub position: Vec3,
+}
+
+/// Triggered when the player picks up a pickup.
+#[derive(Event, Debug)]
+pub struct CollectPickup(pub Pickup);
+
+fn drop_pickup(trigger: Trigger<EnemyDeath>, mut commands: Commands, mut rng: ResMut<GameRng>) {
+    let event = trigger.event();
+    commands.trigger(SpawnPickup {
+        pickup: Pickup::Gold(gold_for_kind(&event.kind)),
+        position: event.position + Vec3::new(6., 0., 30.),
+    });
+    let rng = rng.stream(RngStream::Drops);
+    let mut offset = Vec3::new(-6., 0., 30.);
+    for (pickup, chance) in drop_chances(&event.kind) {
+        if rng.gen_bool(chance) {
+            commands.trigger(SpawnPickup {
+                pickup,
+                position: event.position + offset,
+            });
+            // spread out drops from the same death so they don't stack
+            offset.x -= 6.;
+        }
+    }
+}
+
+fn spawn_pickup(trigger: Trigger<SpawnPickup>, mut commands: Commands, images: Res<ImageAssets>) {
+    let event = trigger.event();
+    let drop = (
+        Name::new(format!("{:?} drop", event.pickup)),
+        event.pickup,
+        ItemDrop,
+        SpriteBundle {
+            // reuse the experience sprite, tinted and scaled
+            texture: images[&ImageAsset::Exp].clone_weak(),
+            sprite: Sprite {
+                color: event.pickup.color(),
+                ..default()
+            },
+            transform: Transform::from_translation(event.position)
+                .with_scale(Vec3::splat(event.pickup.scale())),
+            ..default()
+        },
+        ExpireTimer {
+            timer: Timer::new(Duration::from_secs_f32(PICKUP_LIFETIME), TimerMode::Once),
+        },
+        StateScoped(Screen::Playing),
+    );
+    commands.add(move |world: &mut World| {
+        spawn_pooled::<Pickup>(world, drop);
+    });
+}
+
+fn collect_pickups(
+    mut commands: Commands,
+    player_query: Query<(&Transform, &PlayerStats), With<Player>>,
+    pickup_query: Query<(Entity, &Pickup, &Transform), (With<ItemDrop>, Without<Player>)>,
+) {
+    let Ok((player_transform, stats)) = player_query.get_single() else {
+        return;
+    };
+    let player_pos = player_transform.translation.truncate();
+    let reach = stats.pickup_reach();
+    for (entity, pickup, transform) in &pickup_query {
+        if in_pickup_reach(player_pos, reach, transform) {
+            info!("Picked up {:?}", pickup);
+            commands.trigger(pickup.sfx());
+            commands.trigger(CollectPickup(*pickup));
+            release::<Pickup>(&mut commands, entity);
+        }
+    }
+}
+
+fn apply_pickup(
+    trigger: Trigger<CollectPickup>,
+    mut commands: Commands,
+    mut pool: ResMut<SpellPool>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+    spawn_area: SpawnArea,
+    mut player_query: Query<(&mut Gold, Option<&Passive>), With<Player>>,
+    experience_query: Query<Entity, (With<Experience>, With<ItemDrop>, Without<Enemy>)>,
+    mut enemy_query: Query<(Entity, &Transform, &mut Damageable), With<Enemy>>,
+) {
+    match trigger.event().0 {
+        Pickup::Gold(amount) => {
+            let Ok((mut gold, passive)) = player_query.get_single_mut() else {
+                return;
+            };
+            let multiplier = match passive {
+                Some(Passive::Greed(multiplier)) => *multiplier,
+                _ => 1.,
+            };
+            gold.0 += (amount as f32 * multiplier).round() as u32;
+        }
+        Pickup::HealthPotion => commands.trigger(HealEvent(POTION_HEAL)),
+        Pickup::Magnet => {
+            for experience in &experience_query {
+                commands.entity(experience).insert(Magnetized);
+            }
+        }
+        Pickup::Bomb => {
+            let Some(view) = spawn_area.view() else {
+                return;
+            };
+            for (enemy, transform, mut health) in &mut enemy_query {
+                if !view.contains(transform.translation.truncate()) {
+                    continue;
+                }
+                health.health -= BOMB_DAMAGE;
+                commands.trigger_targets(
+                    DamageTaken {
+                        amount: BOMB_DAMAGE,
+                        source: ProjectileTeam::Player,
+                        gem: None,
+                        position: transform.translation,
+                    },
+                    enemy,
+                );
+            }
+        }
+        Pickup::Chest => {
+            pool.guarantee_rare();
+            next_game_state.set(GameState::GemSelection);
+        }
+    }
+}
@@ -1,14 +1,13 @@
 //! Logic for dealing damage to the player
 
-use crate::game::physics::GameLayer;
 use crate::game::projectiles::HitByProjectileEvent;
+use crate::game::spatial_grid::EnemyGrid;
 use crate::{
-    game::{audio::sfx::Sfx, enemy::Enemy, spawn::player::Player, Damageable},
+    game::{audio::sfx::Sfx, enemy::Enemy, spawn::player::Player},
     screen::GameState,
     AppSet,
 };
-use avian2d::collision::Collider;
-use avian2d::prelude::{LinearVelocity, SpatialQuery, SpatialQueryFilter};
+use avian2d::prelude::LinearVelocity;
 use bevy::prelude::*;
 
 pub(super) fn plugin(app: &mut App) {
@@ -31,34 +30,22 @@ pub struct Invincibility {
 pub fn player_hit_by_projectile(
     trigger: Trigger<HitByProjectileEvent>,
     mut commands: Commands,
-    mut death_state: ResMut<NextState<GameState>>,
-    mut player_collision_query: Query<(&mut Damageable, &GlobalTransform), With<Player>>,
-    spatial_query: SpatialQuery,
+    player_query: Query<&GlobalTransform, With<Player>>,
+    enemy_grid: Res<EnemyGrid>,
     mut enemy_velocities: Query<(&mut LinearVelocity, &GlobalTransform), With<Enemy>>,
 ) {
     info!("player hit by projectile");
     let player_entity = trigger.entity();
-    let Ok((player_health, player_transform)) = player_collision_query.get_mut(player_entity)
-    else {
+    let Ok(player_transform) = player_query.get(player_entity) else {
         warn!("Player hit by projectiles called on non-player entity");
         return;
     };
 
-    if player_health.health <= 0. {
-        death_state.set(GameState::Death);
-    }
     commands.trigger(Sfx::WizardGetsHit);
 
     // apply knockback to enemies when the player is hit
-    let near_enemies = spatial_query.shape_intersections(
-        &Collider::circle(100.),
-        player_transform.translation().xy(),
-        0.,
-        SpatialQueryFilter::from_mask(GameLayer::Enemy),
-    );
-
-    for enemy in near_enemies.iter() {
-        let Ok((mut velocity, transform)) = enemy_velocities.get_mut(*enemy) else {
+    for (enemy, _) in enemy_grid.within(player_transform.translation().xy(), 100.) {
+        let Ok((mut velocity, transform)) = enemy_velocities.get_mut(enemy) else {
             continue;
         };
         let direction = (transform.translation() - player_transform.translation())
@@ -0,0 +1,112 @@
+//! A short dash in the move direction, the player can't be hurt while dashing.
+
+use bevy::prelude::*;
+use leafwing_input_manager::action_state::ActionState;
+
+use crate::{
+    config::{DASH_COOLDOWN, DASH_DURATION, DASH_INVINCIBILITY, DASH_SPEED},
+    game::input::PlayerAction,
+    screen::GameState,
+    AppSet,
+};
+
+use super::{damage::Invincibility, death::player_not_dying, movement::PlayerMovement};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Dash>();
+    app.add_systems(
+        Update,
+        (
+            tick_dash.in_set(AppSet::TickTimers),
+            start_dash.in_set(AppSet::Update).run_if(player_not_dying),
+        )
+            .run_if(in_state(GameState::Running)),
+    );
+}
+
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct Dash {
+    pub cooldown: Timer,
+    duration: Timer,
+    direction: Vec2,
+}
+
+impl Default for Dash {
+    fn default() -> Self {
+        let mut cooldown = Timer::from_seconds(DASH_COOLDOWN, TimerMode::Once);
+        cooldown.tick(cooldown.duration());
+        let mut duration = Timer::from_seconds(DASH_DURATION, TimerMode::Once);
+        duration.tick(duration.duration());
+        Dash {
+            cooldown,
+            duration,
+            direction: Vec2::ZERO,
+        }
+    }
+}
+
+impl Dash {
+    pub fn is_dashing(&self) -> bool {
+        !self.duration.finished()
+    }
+
+    /// The velocity to move at while dashing.
+    pub fn velocity(&self) -> Vec2 {
+        self.direction * DASH_SPEED
+    }
+}
+
+fn tick_dash(time: Res<Time>, mut dash_query: Query<&mut Dash>) {
+    for mut dash in &mut dash_query {
+        dash.cooldown.tick(time.delta());
+        dash.duration.tick(time.delta());
+    }
+}
+
+fn start_dash(
+    mut commands: Commands,
+    action_state: Res<ActionState<PlayerAction>>,
+    mut dash_query: Query<(
+        Entity,
+        &mut Dash,
+        &PlayerMovement,
+        &Sprite,
+        Option<&mut Invincibility>,
+    )>,
+) {
+    if !action_state.just_pressed(&PlayerAction::Dash) {
+        return;
+    }
+    for (entity, mut dash, movement, sprite, invincibility) in &mut dash_query {
+        if !dash.cooldown.finished() {
+            continue;
+        }
+        // standing still dashes the way the player is facing
+        dash.direction = if movement.0 == Vec2::ZERO {
+            if sprite.flip_x {
+                Vec2::NEG_X
+            } else {
+                Vec2::X
+            }
+        } else {
+            movement.0.normalize()
+        };
+        dash.duration.reset();
+        dash.cooldown.reset();
+
+        // don't cut short a longer invincibility, e.g. from just being hit
+        match invincibility {
+            Some(mut invincibility) => {
+                if invincibility.timer.remaining_secs() < DASH_INVINCIBILITY {
+                    invincibility.timer = Timer::from_seconds(DASH_INVINCIBILITY, TimerMode::Once);
+                }
+            }
+            None => {
+                commands.entity(entity).insert(Invincibility {
+                    timer: Timer::from_seconds(DASH_INVINCIBILITY, TimerMode::Once),
+                });
+            }
+        }
+    }
+}
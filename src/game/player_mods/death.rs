@@ -0,0 +1,69 @@
+//! The player's death, played out in slow motion before the death screen.
+
+use bevy::prelude::*;
+
+use crate::{
+    config::{DEATH_SEQUENCE_DURATION, DEATH_TIME_SCALE},
+    game::{audio::sfx::Sfx, spawn::player::Player, Damageable},
+    screen::{GameState, Screen},
+    AppSet,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        (start_dying, finish_dying)
+            .chain()
+            .in_set(AppSet::Update)
+            .run_if(in_state(GameState::Running)),
+    );
+    app.add_systems(OnExit(Screen::Playing), reset_time_scale);
+}
+
+/// Added to the player once their health runs out, the timer runs in real time.
+#[derive(Component, Debug)]
+pub struct Dying(Timer);
+
+/// Run condition for things that shouldn't happen once the player is dying,
+/// like opening the level up or shop screens.
+pub fn player_not_dying(dying_query: Query<(), (With<Player>, With<Dying>)>) -> bool {
+    dying_query.is_empty()
+}
+
+fn start_dying(
+    mut commands: Commands,
+    mut time: ResMut<Time<Virtual>>,
+    player_query: Query<(Entity, &Damageable), (With<Player>, Without<Dying>)>,
+) {
+    for (player, health) in &player_query {
+        if health.health > 0. {
+            continue;
+        }
+        info!("Player died");
+        commands.entity(player).insert(Dying(Timer::from_seconds(
+            DEATH_SEQUENCE_DURATION,
+            TimerMode::Once,
+        )));
+        commands.trigger(Sfx::WizardDies);
+        time.set_relative_speed(DEATH_TIME_SCALE);
+    }
+}
+
+fn finish_dying(
+    real_time: Res<Time<Real>>,
+    mut time: ResMut<Time<Virtual>>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+    mut dying_query: Query<&mut Dying>,
+) {
+    for mut dying in &mut dying_query {
+        if dying.0.tick(real_time.delta()).just_finished() {
+            time.set_relative_speed(1.);
+            next_game_state.set(GameState::Death);
+        }
+    }
+}
+
+/// Leaving mid death, e.g. from the pause menu, mustn't leave time slowed down.
+fn reset_time_scale(mut time: ResMut<Time<Virtual>>) {
+    time.set_relative_speed(1.);
+}
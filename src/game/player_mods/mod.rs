@@ -2,14 +2,20 @@ use bevy::prelude::*;
 
 pub mod aiming;
 pub mod damage;
+pub mod dash;
+pub mod death;
 pub mod health;
 pub mod movement;
+pub mod stats;
 
 pub(super) fn plugin(app: &mut App) {
     app.add_plugins((
         movement::plugin,
         aiming::plugin,
         damage::plugin,
+        dash::plugin,
+        death::plugin,
         health::plugin,
+        stats::plugin,
     ));
 }
@@ -10,7 +10,7 @@ use avian2d::{math::*, prelude::*};
 use bevy::prelude::*;
 use leafwing_input_manager::action_state::ActionState;
 
-use super::aiming::PlayerAim;
+use super::{aiming::PlayerAim, dash::Dash, death::Dying, stats::PlayerStats};
 
 pub(super) fn plugin(app: &mut App) {
     // Record directional input as movement controls.
@@ -23,7 +23,6 @@ pub(super) fn plugin(app: &mut App) {
     );
 
     // Apply movement based on controls.
-    app.register_type::<Movement>();
     app.add_systems(
         Update,
         apply_movement
@@ -68,24 +67,29 @@ fn record_movement_controller(
     }
 }
 
-#[derive(Component, Reflect)]
-#[reflect(Component)]
-pub struct Movement {
-    /// Since Bevy's default 2D camera setup is scaled such that
-    /// one unit is one pixel, you can think of this as
-    /// "How many pixels per second should the player move?"
-    /// Note that physics engines may use different unit/pixel ratios.
-    pub speed: f32,
-}
-
 fn apply_movement(
     time: Res<Time>,
-    mut movement_query: Query<(&mut LinearVelocity, &PlayerMovement, &Movement)>,
+    mut movement_query: Query<(
+        &mut LinearVelocity,
+        &PlayerMovement,
+        &PlayerStats,
+        Option<&Dash>,
+        Has<Dying>,
+    )>,
 ) {
-    for (mut velocity, controller, movement) in &mut movement_query {
+    for (mut velocity, controller, stats, dash, dying) in &mut movement_query {
+        if dying {
+            velocity.0 = Vec2::ZERO;
+            continue;
+        }
+        if let Some(dash) = dash.filter(|dash| dash.is_dashing()) {
+            velocity.0 = dash.velocity();
+            continue;
+        }
         let delta_time = time.delta_seconds_f64().adjust_precision();
+        let speed = stats.move_speed.value();
 
-        velocity.x = controller.0.x * movement.speed * delta_time;
-        velocity.y = controller.0.y * movement.speed * delta_time;
+        velocity.x = controller.0.x * speed * delta_time;
+        velocity.y = controller.0.y * speed * delta_time;
     }
 }
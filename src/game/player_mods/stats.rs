@@ -0,0 +1,230 @@
+//! The player's stats, grown by passive stat cards offered on level up.
+//!
+//! Every stat is a base value with additive and multiplicative bonuses on top.
+//! Systems that use a stat read it from [`PlayerStats`], stats that live on other
+//! components (max health, wand damage, armor) are copied over whenever they change.
+
+use bevy::prelude::*;
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{
+    config::*,
+    game::{
+        elites::Armor, spawn::player::Player, spell_system::triggers::PlayerSpellTrigger,
+        Damageable,
+    },
+    screen::GameState,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<PlayerStats>();
+    app.add_systems(
+        Update,
+        (apply_player_stats, regenerate_health).run_if(in_state(GameState::Running)),
+    );
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub enum Stat {
+    MaxHealth,
+    MoveSpeed,
+    PickupRadius,
+    /// Fraction taken off the delays between casts.
+    CastDelayReduction,
+    /// Multiplies the damage of the player's projectiles.
+    Damage,
+    /// Health regained every second.
+    Regen,
+    /// Fraction of damage ignored.
+    Armor,
+}
+
+impl Stat {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Stat::MaxHealth => "Max Health",
+            Stat::MoveSpeed => "Move Speed",
+            Stat::PickupRadius => "Pickup Radius",
+            Stat::CastDelayReduction => "Cast Speed",
+            Stat::Damage => "Damage",
+            Stat::Regen => "Regeneration",
+            Stat::Armor => "Armor",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Reflect)]
+pub struct StatValue {
+    pub base: f32,
+    pub additive: f32,
+    pub multiplier: f32,
+}
+
+impl StatValue {
+    fn new(base: f32) -> Self {
+        StatValue {
+            base,
+            additive: 0.,
+            multiplier: 1.,
+        }
+    }
+
+    pub fn value(&self) -> f32 {
+        (self.base + self.additive) * self.multiplier
+    }
+}
+
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct PlayerStats {
+    pub max_health: StatValue,
+    pub move_speed: StatValue,
+    pub pickup_radius: StatValue,
+    pub cast_delay_reduction: StatValue,
+    pub damage: StatValue,
+    pub regen: StatValue,
+    pub armor: StatValue,
+}
+
+impl Default for PlayerStats {
+    fn default() -> Self {
+        PlayerStats {
+            max_health: StatValue::new(PLAYER_HEALTH),
+            move_speed: StatValue::new(PLAYER_SPEED),
+            pickup_radius: StatValue::new(EXPERIENCE_RADIUS),
+            cast_delay_reduction: StatValue::new(0.),
+            damage: StatValue::new(1.),
+            regen: StatValue::new(0.),
+            armor: StatValue::new(0.),
+        }
+    }
+}
+
+impl PlayerStats {
+    fn stat_mut(&mut self, stat: Stat) -> &mut StatValue {
+        match stat {
+            Stat::MaxHealth => &mut self.max_health,
+            Stat::MoveSpeed => &mut self.move_speed,
+            Stat::PickupRadius => &mut self.pickup_radius,
+            Stat::CastDelayReduction => &mut self.cast_delay_reduction,
+            Stat::Damage => &mut self.damage,
+            Stat::Regen => &mut self.regen,
+            Stat::Armor => &mut self.armor,
+        }
+    }
+
+    pub fn apply(&mut self, card: StatCard) {
+        let value = self.stat_mut(card.stat);
+        match card.bonus {
+            StatBonus::Add(amount) => value.additive += amount,
+            StatBonus::Multiply(amount) => value.multiplier *= amount,
+        }
+    }
+
+    /// Multiplies the delays between the player's casts.
+    pub fn cast_delay_multiplier(&self) -> f32 {
+        1. - self
+            .cast_delay_reduction
+            .value()
+            .clamp(0., PLAYER_MAX_CAST_DELAY_REDUCTION)
+    }
+
+    /// How close drops have to be to be picked up, growing with the pickup radius drops are pulled in from.
+    pub fn pickup_reach(&self) -> f32 {
+        EXPERIENCE_PICKUP_RADIUS * self.pickup_radius.value() / EXPERIENCE_RADIUS
+    }
+
+    pub fn armor(&self) -> f32 {
+        self.armor.value().clamp(0., PLAYER_MAX_ARMOR)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+pub enum StatBonus {
+    Add(f32),
+    Multiply(f32),
+}
+
+/// A passive upgrade offered on level up.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+pub struct StatCard {
+    pub stat: Stat,
+    pub bonus: StatBonus,
+}
+
+impl StatCard {
+    /// Every card that can be offered.
+    const ALL: [StatCard; 7] = [
+        StatCard::new(Stat::MaxHealth, StatBonus::Add(2.)),
+        StatCard::new(Stat::MoveSpeed, StatBonus::Multiply(1.1)),
+        StatCard::new(Stat::PickupRadius, StatBonus::Multiply(1.25)),
+        StatCard::new(Stat::CastDelayReduction, StatBonus::Add(0.08)),
+        StatCard::new(Stat::Damage, StatBonus::Add(0.1)),
+        StatCard::new(Stat::Regen, StatBonus::Add(0.05)),
+        StatCard::new(Stat::Armor, StatBonus::Add(0.05)),
+    ];
+
+    const fn new(stat: Stat, bonus: StatBonus) -> Self {
+        StatCard { stat, bonus }
+    }
+
+    /// Sometimes a card to offer next to the gems on level up.
+    pub fn roll(rng: &mut impl Rng) -> Option<StatCard> {
+        if !rng.gen_bool(STAT_CARD_CHANCE) {
+            return None;
+        }
+        StatCard::ALL.choose(rng).copied()
+    }
+
+    pub fn description(&self) -> String {
+        match self.bonus {
+            StatBonus::Add(amount) => match self.stat {
+                Stat::CastDelayReduction | Stat::Damage | Stat::Armor => {
+                    format!("+{:.0}% {}", amount * 100., self.stat.name())
+                }
+                Stat::Regen => format!("+{} health per second", amount),
+                _ => format!("+{} {}", amount, self.stat.name()),
+            },
+            StatBonus::Multiply(amount) => {
+                format!("+{:.0}% {}", (amount - 1.) * 100., self.stat.name())
+            }
+        }
+    }
+}
+
+/// Copies the stats that live on other components over to them.
+fn apply_player_stats(
+    mut commands: Commands,
+    mut player_query: Query<(Entity, &PlayerStats, &mut Damageable), Changed<PlayerStats>>,
+    mut wand_query: Query<&mut PlayerSpellTrigger>,
+) {
+    let Ok((player, stats, mut health)) = player_query.get_single_mut() else {
+        return;
+    };
+
+    let max_health = stats.max_health.value();
+    // growing max health heals by as much, so the new health isn't empty
+    health.health += (max_health - health.max_health).max(0.);
+    health.max_health = max_health;
+    health.health = health.health.min(max_health);
+
+    commands.entity(player).insert(Armor(stats.armor()));
+
+    for mut wand in &mut wand_query {
+        wand.values.damage_multiplier = stats.damage.value();
+        wand.values.delay_multiplier = stats.cast_delay_multiplier();
+    }
+}
+
+fn regenerate_health(
+    time: Res<Time>,
+    mut player_query: Query<(&PlayerStats, &mut Damageable), With<Player>>,
+) {
+    for (stats, mut health) in &mut player_query {
+        let regen = stats.regen.value();
+        if regen <= 0. || health.health <= 0. || health.health >= health.max_health {
+            continue;
+        }
+        health.health = (health.health + regen * time.delta_seconds()).min(health.max_health);
+    }
+}
@@ -0,0 +1,98 @@
+//! Pools of entities that are reused instead of despawned.
+//!
+//! Released entities are stripped down to their transform and hidden, and spawning
+//! through [`spawn_pooled`] fills one back in before spawning anything new. Each pool
+//! is keyed by a component its entities have while they're in use.
+
+use std::marker::PhantomData;
+
+use bevy::prelude::*;
+
+use crate::{
+    game::{levelling::Experience, pickups::Pickup, projectiles::ProjectileLifetime},
+    screen::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Pooled>();
+    app.init_resource::<Pool<ProjectileLifetime>>();
+    app.init_resource::<Pool<Experience>>();
+    app.init_resource::<Pool<Pickup>>();
+    app.add_systems(
+        OnEnter(Screen::Playing),
+        (
+            reset_pool::<ProjectileLifetime>,
+            reset_pool::<Experience>,
+            reset_pool::<Pickup>,
+        ),
+    );
+}
+
+/// Marks an entity waiting in a pool.
+#[derive(Component, Debug, Clone, Copy, Default, Reflect)]
+#[reflect(Component)]
+pub struct Pooled;
+
+#[derive(Resource, Debug)]
+pub struct Pool<T: Component> {
+    free: Vec<Entity>,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Component> Default for Pool<T> {
+    fn default() -> Self {
+        Pool {
+            free: Vec::new(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// The components a pooled entity keeps.
+type PooledBundle = (
+    Transform,
+    GlobalTransform,
+    Visibility,
+    InheritedVisibility,
+    ViewVisibility,
+    StateScoped<Screen>,
+    Pooled,
+);
+
+// pooled entities are state scoped, so the last run's pool is already gone
+fn reset_pool<T: Component>(mut pool: ResMut<Pool<T>>) {
+    pool.free.clear();
+}
+
+/// Spawns `bundle` on an entity from `T`'s pool, or on a new entity if the pool is empty.
+pub fn spawn_pooled<T: Component>(world: &mut World, bundle: impl Bundle) -> Entity {
+    while let Some(entity) = world.resource_mut::<Pool<T>>().free.pop() {
+        let Some(mut pooled) = world.get_entity_mut(entity) else {
+            continue;
+        };
+        if !pooled.contains::<Pooled>() {
+            continue;
+        }
+        pooled.remove::<Pooled>().insert(bundle);
+        return entity;
+    }
+    world.spawn(bundle).id()
+}
+
+/// Puts `entity` back into `T`'s pool once commands are applied.
+pub fn release<T: Component>(commands: &mut Commands, entity: Entity) {
+    commands.add(move |world: &mut World| {
+        let Some(mut pooled) = world.get_entity_mut(entity) else {
+            return;
+        };
+        // more than one system can be done with the same entity in a frame
+        if pooled.contains::<Pooled>() {
+            return;
+        }
+        pooled.despawn_descendants();
+        pooled
+            .retain::<PooledBundle>()
+            .insert((Pooled, Visibility::Hidden));
+        world.resource_mut::<Pool<T>>().free.push(entity);
+    });
+}
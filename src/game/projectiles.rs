@@ -4,12 +4,15 @@ use avian2d::prelude::{Collision, CollisionLayers, LinearVelocity};
 use bevy::math::Vec3Swizzles;
 use bevy::prelude::{
     in_state, App, Commands, Component, DespawnRecursiveExt, Entity, Event, EventReader,
-    GlobalTransform, IntoSystemConfigs, Query, Reflect, Res, Time, Timer, TimerMode, Update,
+    GlobalTransform, Has, IntoSystemConfigs, Query, Reflect, Res, Time, Timer, TimerMode, Update,
+    Vec3,
 };
 
 use super::audio::sfx::Sfx;
+use crate::game::elites::Armor;
 use crate::game::physics::GameLayer;
 use crate::game::player_mods::damage::Invincibility;
+use crate::game::pooling::release;
 use crate::game::Damageable;
 use crate::screen::GameState;
 use crate::AppSet;
@@ -22,8 +25,8 @@ pub(super) fn plugin(app: &mut App) {
                 .in_set(AppSet::TickTimers)
                 .run_if(in_state(GameState::Running)),
             (
-                detect_projectile_collisions,
-                despawn_projectiles_no_hits,
+                // hit observers (e.g. chain) run in between and can give a projectile its hit back
+                (detect_projectile_collisions, despawn_projectiles_no_hits).chain(),
                 despawn_projectiles_lifetime,
             )
                 .in_set(AppSet::Update),
@@ -44,10 +47,21 @@ pub struct HitByProjectileEvent {
     pub projectile: Entity,
 }
 
-#[derive(Reflect, Clone, Debug, PartialEq, Eq)]
+/// Triggered on an entity whenever a projectile takes health from it.
+#[derive(Event, Debug, Clone)]
+pub struct DamageTaken {
+    pub amount: f32,
+    /// The team of the projectile that did the damage.
+    pub source: ProjectileTeam,
+    /// The gem that cast the projectile, if a spell did.
+    pub gem: Option<&'static str>,
+    pub position: Vec3,
+}
+
+#[derive(Reflect, Clone, Debug, Default, PartialEq, Eq)]
 pub enum ProjectileTeam {
+    #[default]
     Player,
-    #[allow(dead_code)]
     Enemy,
 }
 impl ProjectileTeam {
@@ -71,6 +85,8 @@ pub struct ProjectileDamage {
     pub damage: f32,
     pub hits_remaining: i32, //counter for how many enemies it can hit
     pub knockback_force: f32,
+    /// The gem that cast this, contact damage has none.
+    pub gem: Option<&'static str>,
 }
 
 #[derive(Component)]
@@ -91,19 +107,24 @@ fn despawn_projectiles_lifetime(
     //despawn if pierce = 0 or lifetime is up
     for (entity, lifetime) in projectile_query.iter_mut() {
         if lifetime.lifetime.finished() {
-            commands.entity(entity).despawn_recursive();
+            release::<ProjectileLifetime>(&mut commands, entity);
         }
     }
 }
 
 fn despawn_projectiles_no_hits(
     mut commands: Commands,
-    mut projectile_query: Query<(Entity, &ProjectileDamage)>,
+    mut projectile_query: Query<(Entity, &ProjectileDamage, Has<ProjectileLifetime>)>,
 ) {
     //despawn if pierce = 0 or lifetime is up
-    for (entity, dmg) in projectile_query.iter_mut() {
+    for (entity, dmg, is_projectile) in projectile_query.iter_mut() {
         if dmg.hits_remaining <= 0 {
-            commands.entity(entity).despawn_recursive();
+            // enemies deal contact damage with projectile damage too, but aren't pooled
+            if is_projectile {
+                release::<ProjectileLifetime>(&mut commands, entity);
+            } else {
+                commands.entity(entity).despawn_recursive();
+            }
         }
     }
 }
@@ -118,6 +139,7 @@ fn detect_projectile_collisions(
         Option<&mut LinearVelocity>,
         &mut Damageable,
         Option<&Invincibility>,
+        Option<&Armor>,
     )>,
 ) {
     //datastructure to keep track of hit entities, as they cant be hit more than once per frame
@@ -138,7 +160,8 @@ fn detect_projectile_collisions(
                 continue;
             };
 
-        let Ok((h_transform, lv, mut health, invincibility)) = health_havers.get_mut(health_entity)
+        let Ok((h_transform, lv, mut health, invincibility, armor)) =
+            health_havers.get_mut(health_entity)
         else {
             return;
         };
@@ -166,7 +189,9 @@ fn detect_projectile_collisions(
         hit_entities.push(health_entity);
 
         //do damage + health.invincibility_timer)
-        health.health -= projectile_dmg.damage;
+        let armor = armor.map_or(0., |armor| armor.0);
+        let damage = projectile_dmg.damage * (1. - armor);
+        health.health -= damage;
         if let Some(timer) = health.invincibility_timer {
             commands.entity(health_entity).insert(Invincibility {
                 timer: Timer::new(timer, TimerMode::Once),
@@ -198,5 +223,14 @@ fn detect_projectile_collisions(
             },
             health_entity,
         );
+        commands.trigger_targets(
+            DamageTaken {
+                amount: damage,
+                source: projectile_dmg.team.clone(),
+                gem: projectile_dmg.gem,
+                position: h_transform.translation(),
+            },
+            health_entity,
+        );
     }
 }
@@ -0,0 +1,371 @@
+//! Recording and playback of a run's input.
+//!
+//! Together with the run's [`RunSeed`] this is enough to replay a run: every running
+//! frame records its delta time and the player's movement, aim and shoot input, and
+//! every level up, shop visit and wand edit records the choices that were made.
+//! The character played is recorded next to the seed, as it decides the starting wand.
+//! Native builds record each run to [`REPLAY_PATH`], one entry per line, written out every
+//! few seconds, so a run that crashes still leaves most of a replay behind that can be
+//! played back from the title screen.
+
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+#[cfg(not(target_family = "wasm"))]
+use std::path::Path;
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy::time::{common_conditions::on_timer, TimeUpdateStrategy};
+use leafwing_input_manager::action_state::ActionState;
+use serde::{Deserialize, Serialize};
+
+use crate::config::REPLAY_FLUSH_INTERVAL;
+use crate::game::characters::SelectedCharacter;
+use crate::game::input::PlayerAction;
+use crate::game::player_mods::aiming::PlayerAim;
+use crate::game::player_mods::movement::PlayerMovement;
+use crate::game::rng::RunSeed;
+use crate::game::spell_system::fusion::FusionRecipes;
+use crate::game::spell_system::storage::{RebuildWand, SpellInventory, WandEdit, WandStash};
+use crate::screen::{GameState, Screen};
+use crate::AppSet;
+
+pub const REPLAY_PATH: &str = "replays/latest.ron";
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<ReplayMode>();
+    app.add_systems(OnEnter(Screen::Playing), start_replay);
+    app.add_systems(OnExit(Screen::Playing), stop_replay);
+    app.add_systems(
+        Update,
+        (
+            record_input.run_if(is_recording),
+            play_back_input.run_if(is_playing_back),
+        )
+            .in_set(AppSet::ReplayInput)
+            .run_if(in_state(GameState::Running)),
+    );
+    app.add_systems(
+        Update,
+        flush_replay.run_if(
+            is_recording.and_then(on_timer(Duration::from_secs_f32(REPLAY_FLUSH_INTERVAL))),
+        ),
+    );
+}
+
+/// The input of a single frame spent in [`GameState::Running`].
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct ReplayFrame {
+    pub delta: f32,
+    pub movement: [f32; 2],
+    pub aim: [f32; 2],
+    pub shoot: bool,
+    #[serde(default)]
+    pub dash: bool,
+}
+
+/// What the player did with the gems offered on level up.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GemChoice {
+    /// The offered gem with this index was placed at the start of the wand.
+    Start(usize),
+    /// The offered gem with this index was placed at the end of the wand.
+    End(usize),
+    Reroll,
+    /// The offered gem with this index was banished from the pool.
+    Banish(usize),
+    /// The offered stat card was taken.
+    StatCard,
+    /// Nothing was taken.
+    Discard,
+    /// Nothing was taken, for a heal or experience instead.
+    Skip,
+}
+
+/// What the player did in the shop between waves.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShopChoice {
+    /// The stocked gem with this index was bought.
+    BuyGem(usize),
+    BuySlot,
+    BuyHeal,
+    /// The wand gem with this index was removed.
+    RemoveGem(usize),
+    Leave,
+}
+
+/// Recorded input, kept in the order it happened, so e.g. wand edits made while
+/// paused are played back between the same two frames.
+#[derive(Debug, Clone)]
+pub enum ReplayInput {
+    Frame(ReplayFrame),
+    GemChoice(GemChoice),
+    ShopChoice(ShopChoice),
+    WandEdit(WandEdit),
+}
+
+/// A single line of a replay file.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+enum ReplayEntry {
+    Seed(u64),
+    Character(String),
+    Frame(ReplayFrame),
+    GemChoice(GemChoice),
+    ShopChoice(ShopChoice),
+    WandEdit(WandEdit),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Replay {
+    pub seed: u64,
+    /// Missing from replays recorded before characters existed.
+    pub character: Option<String>,
+    pub inputs: Vec<ReplayInput>,
+}
+
+impl Replay {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let contents = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let mut replay = Replay::default();
+        for (n, line) in contents.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let input = match ron::from_str(line).map_err(|e| format!("line {}: {}", n + 1, e))? {
+                ReplayEntry::Seed(seed) => {
+                    replay.seed = seed;
+                    continue;
+                }
+                ReplayEntry::Character(name) => {
+                    replay.character = Some(name);
+                    continue;
+                }
+                ReplayEntry::Frame(frame) => ReplayInput::Frame(frame),
+                ReplayEntry::GemChoice(choice) => ReplayInput::GemChoice(choice),
+                ReplayEntry::ShopChoice(choice) => ReplayInput::ShopChoice(choice),
+                ReplayEntry::WandEdit(edit) => ReplayInput::WandEdit(edit),
+            };
+            replay.inputs.push(input);
+        }
+        Ok(replay)
+    }
+}
+
+#[derive(Debug)]
+pub struct Playback {
+    replay: Replay,
+    next_input: usize,
+}
+
+impl Playback {
+    /// Delta time of the next recorded frame.
+    fn next_delta(&self) -> Option<f32> {
+        self.replay.inputs[self.next_input.min(self.replay.inputs.len())..]
+            .iter()
+            .find_map(|input| match input {
+                ReplayInput::Frame(frame) => Some(frame.delta),
+                _ => None,
+            })
+    }
+}
+
+#[derive(Resource, Debug, Default)]
+pub enum ReplayMode {
+    #[default]
+    Off,
+    Recording(BufWriter<File>),
+    Playback(Playback),
+}
+
+impl ReplayMode {
+    pub fn playback(replay: Replay) -> Self {
+        ReplayMode::Playback(Playback {
+            replay,
+            next_input: 0,
+        })
+    }
+
+    pub fn record_gem_choice(&mut self, choice: GemChoice) {
+        self.write(ReplayEntry::GemChoice(choice));
+    }
+
+    pub fn record_shop_choice(&mut self, choice: ShopChoice) {
+        self.write(ReplayEntry::ShopChoice(choice));
+    }
+
+    pub fn record_wand_edit(&mut self, edit: WandEdit) {
+        self.write(ReplayEntry::WandEdit(edit));
+    }
+
+    /// Takes the next recorded input, if a replay is being played back.
+    pub fn next_input(&mut self) -> Option<ReplayInput> {
+        let ReplayMode::Playback(playback) = self else {
+            return None;
+        };
+        let input = playback.replay.inputs.get(playback.next_input).cloned();
+        playback.next_input += 1;
+        input
+    }
+
+    fn write(&mut self, entry: ReplayEntry) {
+        let ReplayMode::Recording(file) = self else {
+            return;
+        };
+        let result = ron::to_string(&entry)
+            .map_err(io::Error::other)
+            .and_then(|line| writeln!(file, "{}", line));
+        if let Err(e) = result {
+            warn!("Failed to write replay, stopping recording: {}", e);
+            *self = ReplayMode::Off;
+        }
+    }
+}
+
+pub fn is_recording(mode: Res<ReplayMode>) -> bool {
+    matches!(*mode, ReplayMode::Recording(_))
+}
+
+pub fn is_playing_back(mode: Res<ReplayMode>) -> bool {
+    matches!(*mode, ReplayMode::Playback(_))
+}
+
+/// Hands control back to the player, once the replay runs out of input.
+pub fn finish_playback(mode: &mut ReplayMode, time_strategy: &mut TimeUpdateStrategy) {
+    info!("Replay finished, handing control back to the player");
+    *mode = ReplayMode::Off;
+    *time_strategy = TimeUpdateStrategy::Automatic;
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn create_replay_file(seed: u64, character: &str) -> io::Result<BufWriter<File>> {
+    if let Some(dir) = Path::new(REPLAY_PATH).parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut file = BufWriter::new(File::create(REPLAY_PATH)?);
+    let header = ron::to_string(&ReplayEntry::Seed(seed)).map_err(io::Error::other)?;
+    writeln!(file, "{}", header)?;
+    let character =
+        ron::to_string(&ReplayEntry::Character(character.to_string())).map_err(io::Error::other)?;
+    writeln!(file, "{}", character)?;
+    Ok(file)
+}
+
+fn start_replay(
+    seed: Res<RunSeed>,
+    #[cfg_attr(target_family = "wasm", allow(unused_variables))] character: Res<SelectedCharacter>,
+    mut mode: ResMut<ReplayMode>,
+    mut time_strategy: ResMut<TimeUpdateStrategy>,
+) {
+    if let ReplayMode::Playback(playback) = mode.as_mut() {
+        info!("Playing back replay of seed {}", *seed);
+        playback.next_input = 0;
+        if let Some(delta) = playback.next_delta() {
+            *time_strategy = TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(delta));
+        }
+        return;
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    match create_replay_file(seed.0, &character.0) {
+        Ok(file) => *mode = ReplayMode::Recording(file),
+        Err(e) => warn!("Failed to start recording replay: {}", e),
+    }
+}
+
+fn stop_replay(mut mode: ResMut<ReplayMode>, mut time_strategy: ResMut<TimeUpdateStrategy>) {
+    if let ReplayMode::Recording(file) = mode.as_mut() {
+        match file.flush() {
+            Ok(()) => info!("Saved replay to {}", REPLAY_PATH),
+            Err(e) => warn!("Failed to save replay: {}", e),
+        }
+    }
+    *mode = ReplayMode::Off;
+    *time_strategy = TimeUpdateStrategy::Automatic;
+}
+
+fn flush_replay(mut mode: ResMut<ReplayMode>) {
+    let ReplayMode::Recording(file) = mode.as_mut() else {
+        return;
+    };
+    if let Err(e) = file.flush() {
+        warn!("Failed to write replay, stopping recording: {}", e);
+        *mode = ReplayMode::Off;
+    }
+}
+
+fn record_input(
+    // playback feeds this back in as the real delta, virtual time would be scaled twice
+    time: Res<Time<Real>>,
+    action_state: Res<ActionState<PlayerAction>>,
+    movement_query: Query<&PlayerMovement>,
+    aim_query: Query<&PlayerAim>,
+    mut mode: ResMut<ReplayMode>,
+) {
+    let movement = movement_query.get_single().map_or(Vec2::ZERO, |m| m.0);
+    let aim = aim_query.get_single().map_or(Vec2::ZERO, |a| a.0);
+    mode.write(ReplayEntry::Frame(ReplayFrame {
+        delta: time.delta_seconds(),
+        movement: movement.to_array(),
+        aim: aim.to_array(),
+        shoot: action_state.pressed(&PlayerAction::Shoot),
+        dash: action_state.just_pressed(&PlayerAction::Dash),
+    }));
+}
+
+fn play_back_input(
+    mut commands: Commands,
+    mut mode: ResMut<ReplayMode>,
+    mut time_strategy: ResMut<TimeUpdateStrategy>,
+    mut action_state: ResMut<ActionState<PlayerAction>>,
+    mut spell_inventory: ResMut<SpellInventory>,
+    mut stash: ResMut<WandStash>,
+    recipes: Res<FusionRecipes>,
+    mut movement_query: Query<&mut PlayerMovement>,
+    mut aim_query: Query<(&mut PlayerAim, &mut Transform)>,
+) {
+    // apply everything that happened since the last frame, e.g. wand edits made while paused
+    let frame = loop {
+        match mode.next_input() {
+            Some(ReplayInput::Frame(frame)) => break frame,
+            Some(ReplayInput::WandEdit(edit)) => {
+                spell_inventory.apply_edit(&mut stash, &recipes, edit);
+                commands.trigger(RebuildWand);
+            }
+            Some(input) => warn!("Replay is out of sync, skipping {:?}", input),
+            None => {
+                finish_playback(&mut mode, &mut time_strategy);
+                return;
+            }
+        }
+    };
+
+    // the next frame has to advance time by the same amount it did when it was recorded
+    if let ReplayMode::Playback(playback) = mode.as_ref() {
+        if let Some(delta) = playback.next_delta() {
+            *time_strategy = TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(delta));
+        }
+    }
+
+    if frame.shoot {
+        action_state.press(&PlayerAction::Shoot);
+    } else {
+        action_state.release(&PlayerAction::Shoot);
+    }
+    if frame.dash {
+        action_state.press(&PlayerAction::Dash);
+    } else {
+        action_state.release(&PlayerAction::Dash);
+    }
+
+    if let Ok(mut movement) = movement_query.get_single_mut() {
+        movement.0 = Vec2::from_array(frame.movement);
+    }
+
+    let aim = Vec2::from_array(frame.aim);
+    for (mut player_aim, mut transform) in &mut aim_query {
+        player_aim.0 = aim;
+        if aim.length_squared() > 0.01 {
+            transform.rotation = Quat::from_rotation_z(-aim.x.atan2(aim.y));
+        }
+    }
+}
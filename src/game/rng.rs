@@ -0,0 +1,189 @@
+//! Seeded randomness for a run.
+//!
+//! Every roll that affects gameplay goes through [`GameRng`], which is reseeded
+//! from the [`RunSeed`] whenever a run starts, so a run can be replayed from its seed.
+//! Each subsystem draws from its own [`RngStream`], so e.g. footstep sounds can't
+//! shift the enemy spawn positions of a replayed run.
+//! A seed can be passed on the command line, e.g. `--seed 00000000DEADBEEF`, in the
+//! same form the title screen shows it.
+
+use std::{fmt, num::ParseIntError, str::FromStr};
+
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::screen::Screen;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<RunSeed>();
+    app.init_resource::<RunSeed>();
+    app.init_resource::<GameRng>();
+    app.add_systems(OnEnter(Screen::Playing), reseed_rng);
+}
+
+/// The seed the current (or next) run is played with.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Resource)]
+pub struct RunSeed(pub u64);
+
+impl RunSeed {
+    pub fn reroll(&mut self) {
+        self.0 = rand::random();
+    }
+}
+
+impl Default for RunSeed {
+    fn default() -> Self {
+        seed_from_args().unwrap_or_else(|| RunSeed(rand::random()))
+    }
+}
+
+/// The seed given with `--seed`, if there is one and it parses.
+fn seed_from_args() -> Option<RunSeed> {
+    let arg = std::env::args().skip_while(|arg| arg != "--seed").nth(1)?;
+    match arg.parse() {
+        Ok(seed) => Some(seed),
+        Err(error) => {
+            warn!("Ignoring --seed {:?}: {}", arg, error);
+            None
+        }
+    }
+}
+
+impl fmt::Display for RunSeed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016X}", self.0)
+    }
+}
+
+/// Parses a seed the way it's displayed, in hex.
+impl FromStr for RunSeed {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = s.trim();
+        let hex = hex.strip_prefix("0x").unwrap_or(hex);
+        u64::from_str_radix(hex, 16).map(RunSeed)
+    }
+}
+
+/// The subsystems that draw random numbers, each from their own stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RngStream {
+    /// Projectile spread and other spell effects.
+    Spells,
+    /// Enemy spawn positions and composition.
+    Spawning,
+    /// Gems offered on level up.
+    GemOffers,
+    /// Gems stocked in the shop between waves.
+    Shop,
+    /// Pickups dropped by enemies.
+    Drops,
+    /// Things that don't affect gameplay, like footstep sounds.
+    Cosmetic,
+}
+
+impl RngStream {
+    const COUNT: usize = 6;
+}
+
+#[derive(Resource)]
+pub struct GameRng {
+    streams: [StdRng; RngStream::COUNT],
+}
+
+impl GameRng {
+    pub fn from_seed(seed: u64) -> Self {
+        // spread the stream index across the seed so neighbouring streams don't correlate
+        let stream = |stream: RngStream| {
+            StdRng::seed_from_u64(seed ^ (stream as u64 + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15))
+        };
+        GameRng {
+            streams: [
+                stream(RngStream::Spells),
+                stream(RngStream::Spawning),
+                stream(RngStream::GemOffers),
+                stream(RngStream::Shop),
+                stream(RngStream::Drops),
+                stream(RngStream::Cosmetic),
+            ],
+        }
+    }
+
+    pub fn stream(&mut self, stream: RngStream) -> &mut StdRng {
+        &mut self.streams[stream as usize]
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        GameRng::from_seed(rand::random())
+    }
+}
+
+fn reseed_rng(seed: Res<RunSeed>, mut rng: ResMut<GameRng>) {
+    info!("Starting run with seed {}", *seed);
+    *rng = GameRng::from_seed(seed.0);
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+
+    const STREAMS: [RngStream; RngStream::COUNT] = [
+        RngStream::Spells,
+        RngStream::Spawning,
+        RngStream::GemOffers,
+        RngStream::Shop,
+        RngStream::Drops,
+        RngStream::Cosmetic,
+    ];
+
+    fn draws(rng: &mut GameRng, stream: RngStream) -> Vec<u64> {
+        (0..8).map(|_| rng.stream(stream).gen()).collect()
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_streams() {
+        let mut a = GameRng::from_seed(42);
+        let mut b = GameRng::from_seed(42);
+        for stream in STREAMS {
+            assert_eq!(draws(&mut a, stream), draws(&mut b, stream));
+        }
+        assert_ne!(
+            draws(&mut GameRng::from_seed(42), RngStream::Spells),
+            draws(&mut GameRng::from_seed(43), RngStream::Spells)
+        );
+    }
+
+    #[test]
+    fn streams_are_independent() {
+        let mut rng = GameRng::from_seed(42);
+        let spells = draws(&mut rng, RngStream::Spells);
+        for stream in &STREAMS[1..] {
+            assert_ne!(draws(&mut rng, *stream), spells);
+        }
+
+        // drawing a lot from one stream doesn't shift another
+        let mut busy = GameRng::from_seed(42);
+        let mut quiet = GameRng::from_seed(42);
+        for _ in 0..1000 {
+            busy.stream(RngStream::Cosmetic).gen::<u64>();
+        }
+        for stream in &STREAMS[..RngStream::COUNT - 1] {
+            assert_eq!(draws(&mut busy, *stream), draws(&mut quiet, *stream));
+        }
+    }
+
+    #[test]
+    fn seeds_parse_the_way_they_are_shown() {
+        let seed = RunSeed(0xDEAD_BEEF);
+        assert_eq!(seed.to_string().parse(), Ok(seed));
+        assert_eq!("0xdeadbeef".parse(), Ok(seed));
+        assert!("not a seed".parse::<RunSeed>().is_err());
+    }
+}
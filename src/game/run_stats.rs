@@ -0,0 +1,76 @@
+//! Statistics of the current run, shown on the death screen.
+
+use std::cmp::Reverse;
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{
+    game::{
+        enemy::{EnemyDeath, EnemyKind},
+        projectiles::{DamageTaken, ProjectileTeam},
+    },
+    screen::{GameState, Screen},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<RunStats>();
+    app.observe(count_kill);
+    app.observe(count_damage);
+    app.add_systems(OnEnter(Screen::Playing), reset_run_stats);
+    app.add_systems(Update, track_time.run_if(in_state(GameState::Running)));
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct RunStats {
+    /// Seconds spent in the running state, menus don't count.
+    pub time: f32,
+    pub kills: HashMap<EnemyKind, u32>,
+    /// Damage dealt to enemies by each gem, by name.
+    pub damage: HashMap<&'static str, f32>,
+}
+
+impl RunStats {
+    /// Kills per enemy kind, most killed first.
+    pub fn kills_by_kind(&self) -> Vec<(EnemyKind, u32)> {
+        let mut kills: Vec<_> = self
+            .kills
+            .iter()
+            .map(|(&kind, &count)| (kind, count))
+            .collect();
+        kills.sort_by_key(|&(_, count)| Reverse(count));
+        kills
+    }
+
+    /// Damage per gem, most damage first.
+    pub fn damage_by_gem(&self) -> Vec<(&'static str, f32)> {
+        let mut damage: Vec<_> = self
+            .damage
+            .iter()
+            .map(|(&gem, &amount)| (gem, amount))
+            .collect();
+        damage.sort_by(|a, b| b.1.total_cmp(&a.1));
+        damage
+    }
+}
+
+fn reset_run_stats(mut stats: ResMut<RunStats>) {
+    *stats = RunStats::default();
+}
+
+fn track_time(time: Res<Time>, mut stats: ResMut<RunStats>) {
+    stats.time += time.delta_seconds();
+}
+
+fn count_kill(trigger: Trigger<EnemyDeath>, mut stats: ResMut<RunStats>) {
+    *stats.kills.entry(trigger.event().kind).or_default() += 1;
+}
+
+fn count_damage(trigger: Trigger<DamageTaken>, mut stats: ResMut<RunStats>) {
+    let event = trigger.event();
+    if event.source != ProjectileTeam::Player {
+        return;
+    }
+    if let Some(gem) = event.gem {
+        *stats.damage.entry(gem).or_default() += event.amount;
+    }
+}
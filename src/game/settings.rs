@@ -0,0 +1,26 @@
+//! Player preferences, changed from the settings screen or the pause menu.
+
+use bevy::prelude::*;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Settings>();
+    app.init_resource::<Settings>();
+}
+
+#[derive(Resource, Debug, Clone, Reflect)]
+#[reflect(Resource)]
+pub struct Settings {
+    /// Show how much damage every hit did.
+    pub damage_numbers: bool,
+    /// Show health bars over hurt enemies.
+    pub health_bars: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            damage_numbers: true,
+            health_bars: true,
+        }
+    }
+}
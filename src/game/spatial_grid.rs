@@ -0,0 +1,167 @@
+//! Grids of entity positions for finding entities near a point.
+//!
+//! The enemy grid is rebuilt once a frame, and everything that looks for nearby enemies
+//! (homing, auto-aim and chain spells, and knockback) shares it instead of running its own physics query.
+//! Other crowds, like experience orbs looking for orbs to merge with, build a [`SpatialGrid`] of their own.
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{config::ENEMY_GRID_CELL_SIZE, game::enemy::Enemy, screen::GameState};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<EnemyGrid>();
+    app.add_systems(
+        PreUpdate,
+        rebuild_enemy_grid.run_if(in_state(GameState::Running)),
+    );
+}
+
+#[derive(Debug)]
+pub struct SpatialGrid {
+    cell_size: f32,
+    /// Every entity and its position, by the cell it's in.
+    cells: HashMap<IVec2, Vec<(Entity, Vec2)>>,
+}
+
+impl SpatialGrid {
+    pub fn new(cell_size: f32) -> Self {
+        SpatialGrid {
+            cell_size,
+            cells: HashMap::default(),
+        }
+    }
+
+    fn cell(&self, position: Vec2) -> IVec2 {
+        (position / self.cell_size).floor().as_ivec2()
+    }
+
+    pub fn insert(&mut self, entity: Entity, position: Vec2) {
+        let cell = self.cell(position);
+        self.cells.entry(cell).or_default().push((entity, position));
+    }
+
+    /// Empties the grid for a rebuild.
+    pub fn clear(&mut self) {
+        // cells are kept around so their allocations can be reused, unless they stayed
+        // empty since the last rebuild, which keeps the map to where entities are now
+        self.cells.retain(|_, cell| !cell.is_empty());
+        for cell in self.cells.values_mut() {
+            cell.clear();
+        }
+    }
+
+    /// Every entity within `radius` of `position`.
+    pub fn within(&self, position: Vec2, radius: f32) -> impl Iterator<Item = (Entity, Vec2)> + '_ {
+        let min = self.cell(position - Vec2::splat(radius));
+        let max = self.cell(position + Vec2::splat(radius));
+        (min.y..=max.y)
+            .flat_map(move |y| (min.x..=max.x).map(move |x| IVec2::new(x, y)))
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .copied()
+            .filter(move |(_, other)| other.distance_squared(position) <= radius * radius)
+    }
+
+    /// The closest entity within `range` of `position`.
+    pub fn nearest(&self, position: Vec2, range: f32) -> Option<(Entity, Vec2)> {
+        self.within(position, range).min_by(|(_, a), (_, b)| {
+            a.distance_squared(position)
+                .total_cmp(&b.distance_squared(position))
+        })
+    }
+}
+
+/// Every enemy, as of the start of the frame.
+#[derive(Resource, Debug, Deref, DerefMut)]
+pub struct EnemyGrid(SpatialGrid);
+
+impl Default for EnemyGrid {
+    fn default() -> Self {
+        EnemyGrid(SpatialGrid::new(ENEMY_GRID_CELL_SIZE))
+    }
+}
+
+fn rebuild_enemy_grid(
+    mut grid: ResMut<EnemyGrid>,
+    enemy_query: Query<(Entity, &GlobalTransform), With<Enemy>>,
+) {
+    grid.clear();
+    for (entity, transform) in &enemy_query {
+        grid.insert(entity, transform.translation().truncate());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+
+    /// A grid of random points, and the points themselves to check it against.
+    fn random_grid(rng: &mut StdRng) -> (SpatialGrid, Vec<(Entity, Vec2)>) {
+        let mut grid = SpatialGrid::new(32.);
+        let points = (0..500)
+            .map(|i| {
+                let position = Vec2::new(rng.gen_range(-400.0..400.), rng.gen_range(-400.0..400.));
+                (Entity::from_raw(i), position)
+            })
+            .collect::<Vec<_>>();
+        for &(entity, position) in &points {
+            grid.insert(entity, position);
+        }
+        (grid, points)
+    }
+
+    #[test]
+    fn within_finds_exactly_the_entities_in_range() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let (grid, points) = random_grid(&mut rng);
+        for _ in 0..100 {
+            let position = Vec2::new(rng.gen_range(-450.0..450.), rng.gen_range(-450.0..450.));
+            let radius = rng.gen_range(0.0..100.);
+            let mut found = grid
+                .within(position, radius)
+                .map(|(entity, _)| entity)
+                .collect::<Vec<_>>();
+            let mut expected = points
+                .iter()
+                .filter(|(_, other)| other.distance_squared(position) <= radius * radius)
+                .map(|(entity, _)| *entity)
+                .collect::<Vec<_>>();
+            found.sort();
+            expected.sort();
+            assert_eq!(found, expected);
+        }
+    }
+
+    #[test]
+    fn nearest_finds_the_closest_entity_in_range() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let (grid, points) = random_grid(&mut rng);
+        for _ in 0..100 {
+            let position = Vec2::new(rng.gen_range(-450.0..450.), rng.gen_range(-450.0..450.));
+            let range = rng.gen_range(0.0..100.);
+            let expected = points
+                .iter()
+                .filter(|(_, other)| other.distance(position) <= range)
+                .min_by(|(_, a), (_, b)| {
+                    a.distance_squared(position)
+                        .total_cmp(&b.distance_squared(position))
+                })
+                .map(|(entity, _)| *entity);
+            assert_eq!(
+                grid.nearest(position, range).map(|(entity, _)| entity),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn cleared_grids_find_nothing() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let (mut grid, _) = random_grid(&mut rng);
+        grid.clear();
+        assert_eq!(grid.within(Vec2::ZERO, 1000.).count(), 0);
+        assert!(grid.nearest(Vec2::ZERO, 1000.).is_none());
+    }
+}
@@ -9,6 +9,7 @@ use avian2d::{
 use bevy::prelude::*;
 
 use crate::config::{BORDER_THICKNESS, MAP_HEIGHT, MAP_WIDTH};
+use crate::game::flow_field::Obstacle;
 use crate::game::physics::GameLayer;
 use crate::screen::Screen;
 
@@ -51,6 +52,7 @@ fn spawn_box_borders(_trigger: Trigger<SpawnBorders>, mut commands: Commands) {
         commands.spawn((
             Name::new("Border"),
             Border,
+            Obstacle,
             SpriteBundle {
                 sprite: Sprite {
                     color: Color::srgb(0.7, 0.7, 0.8),
@@ -5,6 +5,7 @@ use bevy_ecs_tilemap::prelude::*;
 
 use super::{borders::SpawnBorders, player::SpawnPlayer, prompt::SpawnPrompt, wand::SpawnWand};
 use crate::{
+    config::CHUNK_DESPAWN_DISTANCE,
     game::assets::{ImageAsset, ImageAssets},
     screen::Screen,
 };
@@ -26,8 +27,9 @@ pub struct SpawnLevel;
 #[reflect(Component)]
 pub struct WorldBox;
 
+/// The chunks of the tilemap around the camera, enemy pathfinding covers the same area.
 #[derive(Default, Debug, Resource)]
-struct ChunkManager {
+pub struct ChunkManager {
     pub spawned_chunks: HashSet<IVec2>,
 }
 
@@ -52,9 +54,9 @@ fn spawn_level(_trigger: Trigger<SpawnLevel>, mut commands: Commands, images: Re
     // commands.trigger(StartWave);
 }
 
-const TILE_SIZE: TilemapTileSize = TilemapTileSize { x: 32.0, y: 32.0 };
+pub const TILE_SIZE: TilemapTileSize = TilemapTileSize { x: 32.0, y: 32.0 };
 // For this example, don't choose too large a chunk size.
-const CHUNK_SIZE: UVec2 = UVec2 { x: 16, y: 16 };
+pub const CHUNK_SIZE: UVec2 = UVec2 { x: 16, y: 16 };
 // Render chunk sizes are set to 4 render chunks per user specified chunk.
 const RENDER_CHUNK_SIZE: UVec2 = UVec2 {
     x: CHUNK_SIZE.x * 2,
@@ -107,6 +109,25 @@ fn spawn_chunk(commands: &mut Commands, images: &Res<ImageAssets>, chunk_pos: IV
         .insert(Forest);
 }
 
+/// The tile a world position is on, counting tiles from the one centred on the origin.
+pub fn world_to_tile(pos: Vec2) -> IVec2 {
+    (pos / Vec2::new(TILE_SIZE.x, TILE_SIZE.y))
+        .round()
+        .as_ivec2()
+}
+
+/// The centre of a tile in world space.
+pub fn tile_to_world(tile: IVec2) -> Vec2 {
+    tile.as_vec2() * Vec2::new(TILE_SIZE.x, TILE_SIZE.y)
+}
+
+/// Every tile of a chunk.
+pub fn chunk_tiles(chunk_pos: IVec2) -> impl Iterator<Item = IVec2> {
+    let size = CHUNK_SIZE.as_ivec2();
+    let origin = chunk_pos * size;
+    (0..size.y).flat_map(move |y| (0..size.x).map(move |x| origin + IVec2::new(x, y)))
+}
+
 fn camera_pos_to_chunk_pos(camera_pos: &Vec2) -> IVec2 {
     let camera_pos = camera_pos.as_ivec2();
     let chunk_size: IVec2 = IVec2::new(CHUNK_SIZE.x as i32, CHUNK_SIZE.y as i32);
@@ -143,7 +164,7 @@ fn despawn_outofrange_chunks(
         for (entity, chunk_transform) in chunks_query.iter() {
             let chunk_pos = chunk_transform.translation.xy();
             let distance = camera_transform.translation.xy().distance(chunk_pos);
-            if distance > 1080.0 {
+            if distance > CHUNK_DESPAWN_DISTANCE {
                 let x = (chunk_pos.x / (CHUNK_SIZE.x as f32 * TILE_SIZE.x)).floor() as i32;
                 let y = (chunk_pos.y / (CHUNK_SIZE.y as f32 * TILE_SIZE.y)).floor() as i32;
                 chunk_manager.spawned_chunks.remove(&IVec2::new(x, y));
@@ -5,13 +5,15 @@ use std::time::Duration;
 // use crate::game::lighting::{GameLight, LightMaterial};
 use crate::game::physics::GameLayer;
 use crate::game::player_mods::damage::player_hit_by_projectile;
-use crate::game::player_mods::movement::{Movement, PlayerMovement};
+use crate::game::player_mods::dash::Dash;
+use crate::game::player_mods::movement::PlayerMovement;
 use crate::game::projectiles::ProjectileTeam;
 use crate::{
-    config::{PLAYER_HEALTH, PLAYER_SPEED},
     game::{
         animation::PlayerAnimation,
         assets::{ImageAsset, ImageAssets},
+        characters::{Characters, Passive},
+        gold::Gold,
         levelling::PlayerLevel,
         Damageable,
     },
@@ -36,6 +38,7 @@ fn spawn_player(
     _trigger: Trigger<SpawnPlayer>,
     mut commands: Commands,
     images: Res<ImageAssets>,
+    characters: Characters,
     mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
     // #[allow(dead_code)] _light_materials: ResMut<Assets<LightMaterial>>,
     #[allow(dead_code)] _meshes: ResMut<Assets<Mesh>>,
@@ -48,16 +51,27 @@ fn spawn_player(
     let texture_atlas_layout = texture_atlas_layouts.add(layout);
     let player_animation = PlayerAnimation::new();
 
+    let character = characters.selected();
+    info!("Playing as {}", character.name);
+    let stats = character.stats.player_stats();
+    let mut dash = Dash::default();
+    if let Passive::Nimble(multiplier) = character.passive {
+        let cooldown = dash.cooldown.duration().mul_f32(multiplier);
+        dash.cooldown.set_duration(cooldown);
+        dash.cooldown.tick(cooldown);
+    }
+
     let mut p = commands.spawn((
         Name::new("Wizard"),
         Player,
         Damageable {
-            max_health: PLAYER_HEALTH,
-            health: PLAYER_HEALTH,
+            max_health: stats.max_health.value(),
+            health: stats.max_health.value(),
             team: ProjectileTeam::Player,
             invincibility_timer: Some(Duration::from_secs_f32(0.5)),
         },
         PlayerLevel::default(),
+        Gold::default(),
         SpriteBundle {
             texture: images[&ImageAsset::Wizard].clone_weak(),
             transform: Transform::from_translation(Vec3::new(0., 0., 3.)),
@@ -68,24 +82,25 @@ fn spawn_player(
             index: player_animation.get_atlas_index(),
         },
         PlayerMovement::default(),
-        Movement {
-            speed: PLAYER_SPEED,
-        },
+        stats,
+        dash,
+        character.passive,
         player_animation,
-        LockedAxes::ROTATION_LOCKED,
-        RigidBody::Dynamic,
-        Collider::ellipse(8., 10.),
-        CollisionLayers::new(
-            GameLayer::Player,
-            [
-                GameLayer::Border,
-                GameLayer::Environment,
-                GameLayer::Enemy,
-                GameLayer::EnemyProjectile,
-                GameLayer::Pickups,
-            ],
+        (
+            LockedAxes::ROTATION_LOCKED,
+            RigidBody::Dynamic,
+            Collider::ellipse(8., 10.),
+            CollisionLayers::new(
+                GameLayer::Player,
+                [
+                    GameLayer::Border,
+                    GameLayer::Environment,
+                    GameLayer::Enemy,
+                    GameLayer::EnemyProjectile,
+                ],
+            ),
+            LinearVelocity::default(),
         ),
-        LinearVelocity::default(),
         StateScoped(Screen::Playing),
     ));
 
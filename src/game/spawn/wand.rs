@@ -9,6 +9,7 @@ use crate::game::spell_system::SpellModifierNode;
 use crate::{
     game::{
         player_mods::aiming::{AttachToPlayer, PlayerAim},
+        projectiles::ProjectileTeam,
         spell_system::casting::SpellCastValues,
     },
     screen::Screen,
@@ -45,6 +46,9 @@ fn spawn_wand(_trigger: Trigger<SpawnWand>, images: Res<ImageAssets>, mut comman
         values: SpellCastValues {
             spread: 0.0,
             modifiers: Arc::new(SpellModifierNode::Root),
+            team: ProjectileTeam::Player,
+            damage_multiplier: 1.0,
+            delay_multiplier: 1.0,
         },
         spells: Arc::new(vec![]),
     },));
@@ -0,0 +1,341 @@
+//! Where enemies spawn: just outside the camera's view, inside the arena, and in
+//! the formation the wave asks for.
+//!
+//! Enemies don't appear straight away, a [`SpawnTelegraph`] marks the spot for a
+//! moment first and then triggers [`SpawnEnemy`].
+//!
+//! Enemies left too far behind the player are moved back to a fresh spawn point, so
+//! they keep counting towards the wave's enemies for a reason.
+
+use std::{
+    f32::consts::{SQRT_2, TAU},
+    time::Duration,
+};
+
+use avian2d::prelude::LinearVelocity;
+use bevy::{ecs::system::SystemParam, prelude::*, time::common_conditions::on_timer};
+use rand::Rng;
+
+use crate::{
+    config::*,
+    game::{
+        enemy::{Enemy, EnemyKind},
+        enemy_behavior::{Charge, ChargeState},
+        rng::{GameRng, RngStream},
+        spawn::player::Player,
+        waves::SpawnFormation,
+    },
+    screen::{GameState, Screen},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<SpawnTelegraph>();
+    app.add_systems(
+        Update,
+        (
+            tick_telegraphs,
+            leash_enemies.run_if(on_timer(Duration::from_secs_f32(ENEMY_LEASH_INTERVAL))),
+        )
+            .run_if(in_state(GameState::Running)),
+    );
+}
+
+/// Triggered when a telegraphed enemy should appear.
+#[derive(Event, Debug)]
+pub struct SpawnEnemy {
+    pub kind: EnemyKind,
+    pub position: Vec2,
+}
+
+/// Marks the spot an enemy is about to spawn at.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct SpawnTelegraph {
+    pub kind: EnemyKind,
+    pub timer: Timer,
+}
+
+/// Shows a telegraph at `position`, the enemy spawns once it runs out.
+pub fn telegraph_spawn(commands: &mut Commands, kind: EnemyKind, position: Vec2) {
+    commands.spawn((
+        Name::new("Spawn Telegraph"),
+        SpawnTelegraph {
+            kind,
+            timer: Timer::from_seconds(SPAWN_TELEGRAPH_DURATION, TimerMode::Once),
+        },
+        SpriteBundle {
+            sprite: Sprite {
+                color: SPAWN_TELEGRAPH_COLOR,
+                custom_size: Some(Vec2::splat(SPAWN_TELEGRAPH_SIZE)),
+                ..default()
+            },
+            transform: Transform::from_translation(position.extend(1.0)).with_scale(Vec3::ZERO),
+            ..default()
+        },
+        StateScoped(Screen::Playing),
+    ));
+}
+
+fn tick_telegraphs(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut telegraph_query: Query<(Entity, &mut SpawnTelegraph, &mut Transform)>,
+) {
+    for (entity, mut telegraph, mut transform) in &mut telegraph_query {
+        telegraph.timer.tick(time.delta());
+        // grows while spinning, so it reads as something arriving
+        let fraction = telegraph.timer.fraction();
+        transform.scale = Vec3::splat(fraction);
+        transform.rotation = Quat::from_rotation_z(fraction * TAU);
+        if !telegraph.timer.finished() {
+            continue;
+        }
+
+        commands.entity(entity).despawn_recursive();
+        commands.trigger(SpawnEnemy {
+            kind: telegraph.kind,
+            position: transform.translation.truncate(),
+        });
+    }
+}
+
+/// Moves enemies further from the player than the chunk despawn distance to a fresh
+/// spawn point, keeping everything else about them (health, elite affixes, ...) as it was.
+fn leash_enemies(
+    spawn_area: SpawnArea,
+    mut rng: ResMut<GameRng>,
+    player_query: Query<&GlobalTransform, With<Player>>,
+    mut enemy_query: Query<(&mut Transform, &mut LinearVelocity, Option<&mut Charge>), With<Enemy>>,
+) {
+    let Ok(player) = player_query.get_single() else {
+        return;
+    };
+    let player_pos = player.translation().truncate();
+    let bounds = spawn_area.bounds();
+    // enemies come back inside the largest square within the leash, or they'd be moved
+    // again on the next check
+    let leash =
+        Rect::from_center_half_size(player_pos, Vec2::splat(CHUNK_DESPAWN_DISTANCE / SQRT_2));
+    let respawn = SpawnBounds {
+        arena: bounds.arena.intersect(leash),
+        view: bounds.view,
+    };
+    let rng = rng.stream(RngStream::Spawning);
+    for (mut transform, mut velocity, charge) in &mut enemy_query {
+        if transform.translation.truncate().distance(player_pos) <= CHUNK_DESPAWN_DISTANCE {
+            continue;
+        }
+        let position = respawn.random_position(rng);
+        transform.translation = position.extend(transform.translation.z);
+        velocity.0 = Vec2::ZERO;
+        // a charge towards where the player used to be makes no sense from the new spot
+        if let Some(mut charge) = charge {
+            charge.windup.reset();
+            charge.duration.reset();
+            charge.state = ChargeState::Approaching;
+        }
+    }
+}
+
+/// The parts of the world enemies may spawn in.
+#[derive(SystemParam)]
+pub struct SpawnArea<'w, 's> {
+    camera_query:
+        Query<'w, 's, (&'static GlobalTransform, &'static OrthographicProjection), With<Camera2d>>,
+}
+
+impl SpawnArea<'_, '_> {
+    pub fn bounds(&self) -> SpawnBounds {
+        let arena = Rect::from_center_size(Vec2::ZERO, Vec2::new(MAP_WIDTH, MAP_HEIGHT))
+            .inflate(-SPAWN_ARENA_MARGIN);
+        let view = self
+            .view()
+            .map(|view| view.inflate(SPAWN_VIEW_MARGIN))
+            .unwrap_or_default();
+        SpawnBounds { arena, view }
+    }
+
+    /// The part of the world the camera shows.
+    pub fn view(&self) -> Option<Rect> {
+        self.camera_query
+            .get_single()
+            .ok()
+            .map(|(transform, projection)| {
+                let area = projection.area;
+                Rect::from_center_size(
+                    transform.translation().truncate() + area.center(),
+                    area.size(),
+                )
+            })
+    }
+}
+
+/// Enemies spawn inside `arena` but outside `view`, so they never pop in on screen.
+#[derive(Debug, Clone, Copy)]
+pub struct SpawnBounds {
+    pub arena: Rect,
+    pub view: Rect,
+}
+
+impl SpawnBounds {
+    pub fn is_valid(&self, position: Vec2) -> bool {
+        self.arena.contains(position) && !self.view.contains(position)
+    }
+
+    /// A random valid position, or the arena corner furthest from the view if none turns up.
+    pub fn random_position(&self, rng: &mut impl Rng) -> Vec2 {
+        for _ in 0..32 {
+            let position = Vec2::new(
+                rng.gen_range(self.arena.min.x..=self.arena.max.x),
+                rng.gen_range(self.arena.min.y..=self.arena.max.y),
+            );
+            if self.is_valid(position) {
+                return position;
+            }
+        }
+        let center = self.view.center();
+        [
+            self.arena.min,
+            self.arena.max,
+            Vec2::new(self.arena.min.x, self.arena.max.y),
+            Vec2::new(self.arena.max.x, self.arena.min.y),
+        ]
+        .into_iter()
+        .max_by(|a, b| a.distance(center).total_cmp(&b.distance(center)))
+        .unwrap()
+    }
+
+    /// Pulls `position` into the arena, and picks a random position instead if that puts it in view.
+    fn keep_valid(&self, position: Vec2, rng: &mut impl Rng) -> Vec2 {
+        let position = position.clamp(self.arena.min, self.arena.max);
+        if self.is_valid(position) {
+            position
+        } else {
+            self.random_position(rng)
+        }
+    }
+
+    /// Where to spawn `count` enemies in `formation` around the player at `target`.
+    pub fn formation(
+        &self,
+        formation: SpawnFormation,
+        count: u32,
+        target: Vec2,
+        rng: &mut impl Rng,
+    ) -> Vec<Vec2> {
+        match formation {
+            SpawnFormation::Scattered => (0..count).map(|_| self.random_position(rng)).collect(),
+            SpawnFormation::Ring => {
+                // just far enough out that the whole ring is off screen
+                let radius = self.view.half_size().length();
+                let start = rng.gen_range(0.0..TAU);
+                (0..count)
+                    .map(|i| {
+                        let angle = start + TAU * i as f32 / count as f32;
+                        self.keep_valid(target + Vec2::from_angle(angle) * radius, rng)
+                    })
+                    .collect()
+            }
+            SpawnFormation::Line => {
+                // the arena edge furthest from the player, so the line sweeps across the whole arena
+                let (from, to) = [
+                    (
+                        self.arena.min,
+                        Vec2::new(self.arena.max.x, self.arena.min.y),
+                    ),
+                    (
+                        Vec2::new(self.arena.min.x, self.arena.max.y),
+                        self.arena.max,
+                    ),
+                    (
+                        self.arena.min,
+                        Vec2::new(self.arena.min.x, self.arena.max.y),
+                    ),
+                    (
+                        Vec2::new(self.arena.max.x, self.arena.min.y),
+                        self.arena.max,
+                    ),
+                ]
+                .into_iter()
+                .max_by(|a, b| {
+                    let distance = |(from, to): (Vec2, Vec2)| target.distance((from + to) / 2.);
+                    distance(*a).total_cmp(&distance(*b))
+                })
+                .unwrap();
+                (0..count)
+                    .map(|i| {
+                        let along = (i as f32 + 0.5) / count as f32;
+                        self.keep_valid(from.lerp(to, along), rng)
+                    })
+                    .collect()
+            }
+            SpawnFormation::Pack => {
+                let center = self.random_position(rng);
+                (0..count)
+                    .map(|_| {
+                        let offset = Vec2::from_angle(rng.gen_range(0.0..TAU))
+                            * rng.gen_range(0.0..SPAWN_PACK_RADIUS);
+                        self.keep_valid(center + offset, rng)
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    /// A view in the middle of the arena, and one in its corner.
+    fn bounds() -> [SpawnBounds; 2] {
+        let arena = Rect::from_center_size(Vec2::ZERO, Vec2::splat(600.));
+        [Vec2::ZERO, Vec2::new(220., -220.)].map(|center| SpawnBounds {
+            arena,
+            view: Rect::from_center_size(center, Vec2::new(240., 160.)),
+        })
+    }
+
+    fn assert_valid(bounds: &SpawnBounds, positions: &[Vec2]) {
+        for position in positions {
+            assert!(
+                bounds.arena.contains(*position),
+                "{position} outside the arena"
+            );
+            assert!(!bounds.view.contains(*position), "{position} in view");
+        }
+    }
+
+    #[test]
+    fn random_positions_are_in_the_arena_and_off_screen() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for bounds in bounds() {
+            let positions = (0..200)
+                .map(|_| bounds.random_position(&mut rng))
+                .collect::<Vec<_>>();
+            assert_valid(&bounds, &positions);
+        }
+    }
+
+    #[test]
+    fn every_formation_is_in_the_arena_and_off_screen() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for bounds in bounds() {
+            let target = bounds.view.center();
+            for formation in [
+                SpawnFormation::Scattered,
+                SpawnFormation::Ring,
+                SpawnFormation::Line,
+                SpawnFormation::Pack,
+            ] {
+                for _ in 0..20 {
+                    let positions = bounds.formation(formation, 24, target, &mut rng);
+                    assert_eq!(positions.len(), 24);
+                    assert_valid(&bounds, &positions);
+                }
+            }
+        }
+    }
+}
@@ -9,6 +9,7 @@ use bevy::prelude::{
     Query, Reflect, Res, Time, Timer, TimerMode, Vec2, World,
 };
 
+use crate::game::projectiles::ProjectileTeam;
 use crate::game::spell_system::{SpellEffect, SpellModifier, SpellModifierNode};
 use crate::screen::GameState;
 use crate::AppSet;
@@ -33,11 +34,33 @@ pub(super) fn plugin(app: &mut App) {
 /////////////////////
 
 /// values passed down the spell chain, modified by modifiers, and used on spell cast
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 pub struct SpellCastValues {
     #[allow(dead_code)]
     pub spread: f32, //used for multicasting/multishot spell_system
     pub modifiers: Arc<SpellModifierNode>, //modifiers to apply to the spell
+    pub team: ProjectileTeam,              //team of the caster, projectiles hit the other team
+    pub damage_multiplier: f32,            //applied to the damage of every projectile cast
+    pub delay_multiplier: f32,             //applied to the delays between casts
+}
+impl Default for SpellCastValues {
+    fn default() -> Self {
+        SpellCastValues {
+            spread: 0.0,
+            modifiers: Default::default(),
+            team: ProjectileTeam::Player,
+            damage_multiplier: 1.0,
+            delay_multiplier: 1.0,
+        }
+    }
+}
+impl SpellCastValues {
+    pub fn for_team(team: ProjectileTeam) -> Self {
+        SpellCastValues {
+            team,
+            ..Default::default()
+        }
+    }
 }
 
 /// context used to cast a spell
@@ -129,12 +152,13 @@ impl SequentialCaster {
         for spell in spells.iter().rev() {
             spell_queue.push(spell.clone());
         }
+        let delay_multiplier = cast_values.delay_multiplier;
         Self {
             spell_queue,
             cast_values,
             base_spell_delay: Duration::from_secs_f32(0.1),
             spell_delay: Timer::from_seconds(0.0, TimerMode::Once),
-            base_caster_delay: Duration::from_secs_f32(0.5),
+            base_caster_delay: Duration::from_secs_f32(0.5 * delay_multiplier),
             caster_delay: Timer::from_seconds(0.0, TimerMode::Once),
         }
     }
@@ -155,7 +179,9 @@ impl SequentialCaster {
     }
 
     fn add_spell_delay(&mut self, delay: Duration) {
-        self.spell_delay.set_duration(delay);
+        // the base spell delay is part of `delay`, so it's scaled here
+        self.spell_delay
+            .set_duration(delay.mul_f32(self.cast_values.delay_multiplier));
         self.spell_delay.reset();
     }
 }
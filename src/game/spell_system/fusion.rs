@@ -0,0 +1,177 @@
+//! Fusing owned gems into a new gem, following a table of recipes.
+//!
+//! Recipes name their gems the way [`SpellData::get_name`](super::SpellData::get_name)
+//! does, so a recipe can use any gem, including the ones only made by fusing.
+//! Recipes are authored in [`FUSION_RECIPES_PATH`], there are none if it fails to load.
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::game::{assets::ron_loader::RonAssetLoader, spell_system::SpellComponent};
+
+pub const FUSION_RECIPES_PATH: &str = "spells/recipes.fusion.ron";
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<FusionRecipeBook>();
+    app.register_asset_loader(RonAssetLoader::<FusionRecipeBook>::new(&["fusion.ron"]));
+    app.register_type::<FusionRecipes>();
+    app.init_resource::<FusionRecipes>();
+    app.add_systems(
+        Update,
+        update_fusion_recipes.run_if(resource_exists::<FusionRecipeBookHandle>),
+    );
+}
+
+#[derive(Deserialize, Debug, Clone, Reflect)]
+pub struct FusionRecipe {
+    /// Names of the gems used up, the same gem can be listed more than once.
+    pub ingredients: Vec<String>,
+    /// Name of the gem made.
+    pub result: String,
+}
+
+/// Where an owned gem is kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GemLocation {
+    Wand(usize),
+    Stash(usize),
+}
+
+impl FusionRecipe {
+    /// The owned gems this recipe would use up, or None if some are missing.
+    /// Stashed gems are used before the ones in the wand.
+    pub fn find_ingredients(
+        &self,
+        wand: &[SpellComponent],
+        stash: &[SpellComponent],
+    ) -> Option<Vec<GemLocation>> {
+        let owned = stash
+            .iter()
+            .enumerate()
+            .map(|(i, spell)| (GemLocation::Stash(i), spell))
+            .chain(
+                wand.iter()
+                    .enumerate()
+                    .map(|(i, spell)| (GemLocation::Wand(i), spell)),
+            )
+            .collect::<Vec<_>>();
+
+        let mut used = Vec::new();
+        for ingredient in &self.ingredients {
+            let (location, _) = owned.iter().find(|(location, spell)| {
+                !used.contains(location) && spell.data.get_name() == *ingredient
+            })?;
+            used.push(*location);
+        }
+        Some(used)
+    }
+
+    pub fn describe(&self) -> String {
+        format!("{} = {}", self.ingredients.join(" + "), self.result)
+    }
+}
+
+/// Every known recipe, the wand editor offers the ones the player has the gems for.
+#[derive(Resource, Debug, Clone, Default, Reflect)]
+#[reflect(Resource)]
+pub struct FusionRecipes(pub Vec<FusionRecipe>);
+
+#[derive(Asset, TypePath, Deserialize, Debug, Clone, Default)]
+pub struct FusionRecipeBook {
+    pub recipes: Vec<FusionRecipe>,
+}
+
+/// The recipe book used by every run, loaded on the loading screen.
+#[derive(Resource, Debug)]
+pub struct FusionRecipeBookHandle(pub Handle<FusionRecipeBook>);
+
+/// Copies the recipe book into [`FusionRecipes`] once it loads, and again whenever it
+/// changes in dev builds.
+fn update_fusion_recipes(
+    mut events: EventReader<AssetEvent<FusionRecipeBook>>,
+    books: Res<Assets<FusionRecipeBook>>,
+    handle: Res<FusionRecipeBookHandle>,
+    mut recipes: ResMut<FusionRecipes>,
+) {
+    for event in events.read() {
+        let (AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id }) = event
+        else {
+            continue;
+        };
+        if handle.0.id() != *id {
+            continue;
+        }
+        if let Some(book) = books.get(*id) {
+            info!("Loaded {} fusion recipes", book.recipes.len());
+            recipes.0 = book.recipes.clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::spell_system::spells::find_spell;
+
+    fn recipe(ingredients: &[&str]) -> FusionRecipe {
+        FusionRecipe {
+            ingredients: ingredients.iter().map(|name| name.to_string()).collect(),
+            result: String::from("Shock Nova"),
+        }
+    }
+
+    fn gems(names: &[&str]) -> Vec<SpellComponent> {
+        names.iter().map(|name| find_spell(name).unwrap()).collect()
+    }
+
+    #[test]
+    fn finds_ingredients_in_the_wand() {
+        let found = recipe(&["Zap", "Bang"]).find_ingredients(&gems(&["Bang", "Zap"]), &[]);
+        assert_eq!(
+            found,
+            Some(vec![GemLocation::Wand(1), GemLocation::Wand(0)])
+        );
+    }
+
+    #[test]
+    fn uses_stashed_gems_first() {
+        let found =
+            recipe(&["Zap", "Bang"]).find_ingredients(&gems(&["Zap", "Bang"]), &gems(&["Zap"]));
+        assert_eq!(
+            found,
+            Some(vec![GemLocation::Stash(0), GemLocation::Wand(1)])
+        );
+    }
+
+    #[test]
+    fn a_gem_listed_twice_needs_two_copies() {
+        let zaps = recipe(&["Zap", "Zap", "Zap"]);
+        assert_eq!(zaps.find_ingredients(&gems(&["Zap", "Zap"]), &[]), None);
+        assert_eq!(
+            zaps.find_ingredients(&gems(&["Zap", "Zap"]), &gems(&["Zap"])),
+            Some(vec![
+                GemLocation::Stash(0),
+                GemLocation::Wand(0),
+                GemLocation::Wand(1)
+            ])
+        );
+    }
+
+    #[test]
+    fn missing_ingredients_find_nothing() {
+        let found = recipe(&["Zap", "Bang"]).find_ingredients(&gems(&["Zap", "Zap"]), &[]);
+        assert_eq!(found, None);
+    }
+
+    #[test]
+    fn recipe_book_parses_and_names_real_gems() {
+        let book: FusionRecipeBook =
+            ron::de::from_str(include_str!("../../../assets/spells/recipes.fusion.ron")).unwrap();
+        assert!(!book.recipes.is_empty());
+        for recipe in &book.recipes {
+            for name in recipe.ingredients.iter().chain([&recipe.result]) {
+                assert!(find_spell(name).is_some(), "no gem named {}", name);
+            }
+        }
+    }
+}
@@ -2,7 +2,9 @@ use std::time::Duration;
 
 use crate::game::assets::particles::{ParticleAsset, ParticleAssets};
 use crate::game::assets::spell_gfx::{SpellGFXAsset, SpellGFXAssets};
-use crate::game::projectiles::{ProjectileDamage, ProjectileLifetime, ProjectileTeam};
+use crate::game::pooling::spawn_pooled;
+use crate::game::projectiles::{ProjectileDamage, ProjectileLifetime};
+use crate::game::rng::{GameRng, RngStream};
 use crate::game::spell_system::casting::SpellCastContext;
 use crate::screen::Screen;
 use avian2d::prelude::{Collider, LinearVelocity, RigidBody, Sensor};
@@ -14,6 +16,7 @@ use bevy::prelude::{
 };
 use bevy::sprite::{ColorMaterial, Mesh2dHandle, Sprite};
 use bevy_particle_systems::{BurstIndex, ParticleCount, Playing, RunningState};
+use rand::Rng;
 
 pub enum SpellModel {
     None,
@@ -23,6 +26,8 @@ pub enum SpellModel {
 }
 
 pub struct ProjectileStats {
+    /// The gem the damage is credited to in the run summary.
+    pub gem: &'static str,
     pub radius: f32,
     pub speed: f32,
     pub damage: f32,
@@ -34,7 +39,6 @@ pub struct ProjectileStats {
 pub fn spawn_spell_projectile(
     context: &mut SpellCastContext,
     world: &mut World,
-    team: ProjectileTeam,
     spell_model: SpellModel,
     spell_particles: Option<ParticleAsset>,
     stats: ProjectileStats,
@@ -61,16 +65,24 @@ pub fn spawn_spell_projectile(
     let direction = -context.direction.x.atan2(context.direction.y);
     //spread is degrees of spread, so we need to convert to radians
     let spread = context.values.spread.to_radians();
-    let rotation = Quat::from_rotation_z(direction + spread * ((rand::random::<f32>() - 0.5) * 2.));
+    let roll = world
+        .resource_mut::<GameRng>()
+        .stream(RngStream::Spells)
+        .gen::<f32>();
+    let rotation = Quat::from_rotation_z(direction + spread * ((roll - 0.5) * 2.));
 
     let (z, _, _) = rotation.to_euler(EulerRot::ZXY);
 
     //convert rotation back into a direction vector for the velocity
     let vel_vec = Vec2::new(-z.sin(), z.cos());
 
-    //create new spell entity:
-    let spell = world
-        .spawn((
+    //projectiles belong to the team of whoever cast them
+    let team = context.values.team.clone();
+
+    //create new spell entity, reusing a pooled one if there is one:
+    let spell = spawn_pooled::<ProjectileLifetime>(
+        world,
+        (
             Collider::circle(stats.radius),
             RigidBody::Kinematic,
             Sensor,
@@ -86,17 +98,18 @@ pub fn spawn_spell_projectile(
             LinearVelocity(vel_vec * stats.speed),
             team.get_collision_layer(),
             ProjectileDamage {
-                damage: stats.damage,
+                damage: stats.damage * context.values.damage_multiplier,
                 hits_remaining: stats.num_hits,
                 team: team.clone(),
                 knockback_force: stats.knockback_force,
+                gem: Some(stats.gem),
             },
             ProjectileLifetime {
                 lifetime: Timer::new(stats.lifetime, TimerMode::Once),
             },
             StateScoped(Screen::Playing),
-        ))
-        .id();
+        ),
+    );
 
     // if team == ProjectileTeam::Player {
     //     world.entity_mut(spell).insert(GameLight {
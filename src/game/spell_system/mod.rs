@@ -11,7 +11,9 @@ use crate::game::spell_system::SpellModifierNode::Node;
 
 pub mod casting;
 pub mod examples;
+pub mod fusion;
 pub mod helpers;
+pub mod rarity;
 pub mod spells;
 pub mod storage;
 pub mod triggers;
@@ -21,6 +23,8 @@ pub(super) fn plugin(app: &mut App) {
         casting::plugin,
         triggers::plugin,
         storage::plugin,
+        fusion::plugin,
+        rarity::plugin,
         spells::plugin,
     ));
 }
@@ -43,6 +47,17 @@ pub trait SpellData: Send + Sync + CloneBoxSpellData {
     // fn get_upgrade_desc(&self) -> String; //todo
 }
 
+/// Builds a list of gems into the effects they cast, the way a wand does.
+/// Gems left over after the last complete effect are ignored.
+pub fn build_effects(spells: &[SpellComponent]) -> Vec<Arc<dyn SpellEffect>> {
+    let mut effects = Vec::new();
+    let mut iter = spells.iter();
+    while let Some(effect) = iter.next().and_then(|spell| spell.data.build(&mut iter)) {
+        effects.push(effect);
+    }
+    effects
+}
+
 pub trait CloneBoxSpellData {
     fn clone_box(&self) -> Box<dyn SpellData>;
 }
@@ -128,3 +143,33 @@ impl SpellModifierNode {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::spell_system::spells::find_spell;
+
+    fn gems(names: &[&str]) -> Vec<SpellComponent> {
+        names
+            .iter()
+            .map(|name| find_spell(name).unwrap_or_else(|| panic!("no gem named {}", name)))
+            .collect()
+    }
+
+    #[test]
+    fn every_core_is_its_own_effect() {
+        assert_eq!(build_effects(&gems(&["Zap", "Bang", "Zap"])).len(), 3);
+    }
+
+    #[test]
+    fn modifiers_and_multicasters_take_the_gems_after_them() {
+        let wand = gems(&["DMG UP", "Zap", "Burst 2", "Zap", "Bang", "Zap"]);
+        assert_eq!(build_effects(&wand).len(), 3);
+    }
+
+    #[test]
+    fn leftover_gems_are_ignored() {
+        assert_eq!(build_effects(&gems(&["Zap", "DMG UP"])).len(), 1);
+        assert!(build_effects(&gems(&["DMG UP"])).is_empty());
+    }
+}
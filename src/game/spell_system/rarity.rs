@@ -0,0 +1,116 @@
+//! Rarity tiers of the gems in the [`SpellPool`](super::storage::SpellPool).
+//!
+//! Offers first roll a tier, then pick a gem of that tier. The odds of the better
+//! tiers grow with the wave and the player's level, and a pity timer guarantees a
+//! rare (or better) gem after enough offers without one.
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::config::{
+    RARE_PITY_OFFERS, RARITY_BASE_WEIGHTS, RARITY_WEIGHT_PER_LEVEL, RARITY_WEIGHT_PER_WAVE,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<OfferRules>();
+    app.init_resource::<OfferRules>();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Reflect)]
+pub enum SpellRarity {
+    Common,
+    Uncommon,
+    Rare,
+    Legendary,
+}
+
+impl SpellRarity {
+    pub const ALL: [SpellRarity; 4] = [
+        SpellRarity::Common,
+        SpellRarity::Uncommon,
+        SpellRarity::Rare,
+        SpellRarity::Legendary,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SpellRarity::Common => "Common",
+            SpellRarity::Uncommon => "Uncommon",
+            SpellRarity::Rare => "Rare",
+            SpellRarity::Legendary => "Legendary",
+        }
+    }
+
+    /// Border color of the gem cards of this rarity.
+    pub fn color(&self) -> Color {
+        match self {
+            SpellRarity::Common => Color::srgb(0.50, 0.50, 0.50),
+            SpellRarity::Uncommon => Color::srgb(0.30, 0.75, 0.35),
+            SpellRarity::Rare => Color::srgb(0.25, 0.50, 0.95),
+            SpellRarity::Legendary => Color::srgb(0.95, 0.65, 0.15),
+        }
+    }
+}
+
+/// The rules used to roll the rarity of offered gems.
+#[derive(Resource, Debug, Clone, Reflect)]
+#[reflect(Resource)]
+pub struct OfferRules {
+    /// Relative odds of each tier, indexed by [`SpellRarity`], at wave 1 and level 1.
+    pub base_weights: [f32; 4],
+    /// Added to the odds of each tier for every wave after the first.
+    pub weight_per_wave: [f32; 4],
+    /// Added to the odds of each tier for every level after the first.
+    pub weight_per_level: [f32; 4],
+    /// After this many offers in a row without a rare gem, the next one has one.
+    pub rare_pity: u32,
+}
+
+impl Default for OfferRules {
+    fn default() -> Self {
+        OfferRules {
+            base_weights: RARITY_BASE_WEIGHTS,
+            weight_per_wave: RARITY_WEIGHT_PER_WAVE,
+            weight_per_level: RARITY_WEIGHT_PER_LEVEL,
+            rare_pity: RARE_PITY_OFFERS,
+        }
+    }
+}
+
+impl OfferRules {
+    pub fn weights(&self, wave: u32, level: u32) -> [f32; 4] {
+        let waves = wave.saturating_sub(1) as f32;
+        let levels = level.saturating_sub(1) as f32;
+        let mut weights = [0.; 4];
+        for (i, weight) in weights.iter_mut().enumerate() {
+            *weight = (self.base_weights[i]
+                + self.weight_per_wave[i] * waves
+                + self.weight_per_level[i] * levels)
+                .max(0.);
+        }
+        weights
+    }
+
+    /// Rolls a tier of at least `min`.
+    pub fn roll(&self, wave: u32, level: u32, min: SpellRarity, rng: &mut impl Rng) -> SpellRarity {
+        let weights = self.weights(wave, level);
+        let candidates = SpellRarity::ALL
+            .into_iter()
+            .filter(|rarity| *rarity >= min)
+            .collect::<Vec<_>>();
+        let total = candidates.iter().map(|r| weights[*r as usize]).sum::<f32>();
+        if total <= 0. {
+            return min;
+        }
+
+        let mut roll = rng.gen_range(0.0..total);
+        for rarity in &candidates {
+            if roll < weights[*rarity as usize] {
+                return *rarity;
+            }
+            roll -= weights[*rarity as usize];
+        }
+        // float rounding can leave a sliver of the roll over
+        candidates.last().copied().unwrap_or(min)
+    }
+}
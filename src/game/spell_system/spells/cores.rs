@@ -1,23 +1,25 @@
 use crate::game::assets::particles::ParticleAsset;
 use crate::game::assets::spell_gfx::SpellGFXAsset;
-use crate::game::projectiles::{ProjectileDamage, ProjectileTeam};
+use crate::game::projectiles::ProjectileDamage;
 use crate::game::spell_system::casting::SpellCastContext;
 use crate::game::spell_system::helpers::{spawn_spell_projectile, ProjectileStats, SpellModel};
+use crate::game::spell_system::rarity::SpellRarity;
 use crate::game::spell_system::{SpellComponent, SpellData, SpellEffect};
 use bevy::log::{info, warn};
+use bevy::math::Vec2;
 use bevy::prelude::World;
 use std::slice::Iter;
 use std::sync::Arc;
 use std::time::Duration;
 
-pub(super) fn get_spells() -> Vec<(SpellComponent, i32)> {
+pub(super) fn get_spells() -> Vec<(SpellComponent, SpellRarity)> {
     vec![
         (
             SpellComponent {
                 data: Box::new(ZapSpellData { base_damage: 40.0 }),
                 icon_id: 0,
             },
-            1,
+            SpellRarity::Common,
         ),
         (
             SpellComponent {
@@ -27,7 +29,7 @@ pub(super) fn get_spells() -> Vec<(SpellComponent, i32)> {
                 }),
                 icon_id: 3,
             },
-            20,
+            SpellRarity::Uncommon,
         ),
         (
             SpellComponent {
@@ -38,7 +40,7 @@ pub(super) fn get_spells() -> Vec<(SpellComponent, i32)> {
                 }),
                 icon_id: 1,
             },
-            5,
+            SpellRarity::Common,
         ),
         (
             SpellComponent {
@@ -48,11 +50,23 @@ pub(super) fn get_spells() -> Vec<(SpellComponent, i32)> {
                 }),
                 icon_id: 2,
             },
-            10,
+            SpellRarity::Uncommon,
         ),
     ]
 }
 
+/// Gems that are never offered and only come from fusing other gems.
+pub(super) fn get_fused_spells() -> Vec<SpellComponent> {
+    vec![SpellComponent {
+        data: Box::new(ShockNovaSpellData {
+            base_damage: 60.0,
+            radius: 45.0,
+            bolt_count: 8,
+        }),
+        icon_id: 4,
+    }]
+}
+
 /////////
 // ZAP //
 /////////
@@ -89,10 +103,10 @@ impl SpellEffect for ZapSpell {
         let Some(spell_entity) = spawn_spell_projectile(
             context,
             world,
-            ProjectileTeam::Player,
             SpellModel::StaticSprite(SpellGFXAsset::Zap),
             Some(ParticleAsset::Zap),
             ProjectileStats {
+                gem: "Zap",
                 radius: 5.,
                 speed: 200.0,
                 damage: self.base_damage,
@@ -150,10 +164,10 @@ impl SpellEffect for BangSpell {
         let Some(spell_entity) = spawn_spell_projectile(
             context,
             world,
-            ProjectileTeam::Player,
             SpellModel::None,
             Some(ParticleAsset::Bang),
             ProjectileStats {
+                gem: "Bang",
                 radius: self.radius,
                 speed: 0.0,
                 damage: self.base_damage,
@@ -218,10 +232,10 @@ impl SpellEffect for ArcaneArrowSpell {
         let Some(spell_entity) = spawn_spell_projectile(
             context,
             world,
-            ProjectileTeam::Player,
             SpellModel::StaticSprite(SpellGFXAsset::ArcaneArrow),
             Some(ParticleAsset::ArcaneArrow),
             ProjectileStats {
+                gem: "Arrow",
                 radius: 5.,
                 speed: self.speed,
                 damage: self.base_damage,
@@ -283,10 +297,10 @@ impl SpellEffect for SplitterBoltsSpell {
             let Some(spell_entity) = spawn_spell_projectile(
                 &mut cast_context,
                 world,
-                ProjectileTeam::Player,
                 SpellModel::StaticSprite(SpellGFXAsset::SplitterBolts),
                 Some(ParticleAsset::SplitterBolts),
                 ProjectileStats {
+                    gem: "split",
                     radius: 5.,
                     speed: 250.0,
                     damage: self.base_damage,
@@ -303,3 +317,98 @@ impl SpellEffect for SplitterBoltsSpell {
         }
     }
 }
+
+////////////////
+// SHOCK NOVA //
+////////////////
+// A fused spell that explodes around the caster and sends jolts of energy out in every direction.
+
+#[derive(Clone)]
+pub struct ShockNovaSpellData {
+    pub base_damage: f32,
+    pub radius: f32,
+    pub bolt_count: u32,
+}
+impl SpellData for ShockNovaSpellData {
+    fn build(&self, _iter: &mut Iter<SpellComponent>) -> Option<Arc<dyn SpellEffect>> {
+        Some(Arc::new(ShockNovaSpell {
+            base_damage: self.base_damage,
+            radius: self.radius,
+            bolt_count: self.bolt_count,
+        }))
+    }
+
+    fn get_name(&self) -> String {
+        String::from("Shock Nova")
+    }
+
+    fn get_desc(&self) -> String {
+        String::from("Explodes dealing: ")
+            + &self.base_damage.to_string()
+            + " damage in a "
+            + &self.radius.to_string()
+            + " radius, and fires "
+            + &self.bolt_count.to_string()
+            + " jolts in every direction."
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ShockNovaSpell {
+    pub base_damage: f32,
+    pub radius: f32,
+    pub bolt_count: u32,
+}
+impl SpellEffect for ShockNovaSpell {
+    fn cast(&self, context: &mut SpellCastContext, world: &mut World) {
+        let Some(spell_entity) = spawn_spell_projectile(
+            context,
+            world,
+            SpellModel::None,
+            Some(ParticleAsset::Bang),
+            ProjectileStats {
+                gem: "Shock Nova",
+                radius: self.radius,
+                speed: 0.0,
+                damage: self.base_damage,
+                num_hits: 1000,
+                lifetime: Duration::from_secs_f32(0.05),
+                knockback_force: 80.0,
+            },
+        ) else {
+            warn!("Failed to spawn shock nova spell entity");
+            return;
+        };
+        let spell_damage = world.get::<ProjectileDamage>(spell_entity).unwrap().damage;
+        info!("Cast Shock Nova - DMG: {}", spell_damage);
+
+        // the jolts are spread evenly around the cast direction
+        for i in 0..self.bolt_count {
+            let mut cast_context = context.clone();
+            let angle = std::f32::consts::TAU * i as f32 / self.bolt_count as f32;
+            cast_context.direction = Vec2::from_angle(angle).rotate(context.direction);
+            cast_context.values.spread = 0.0;
+
+            if spawn_spell_projectile(
+                &mut cast_context,
+                world,
+                SpellModel::StaticSprite(SpellGFXAsset::Zap),
+                Some(ParticleAsset::Zap),
+                ProjectileStats {
+                    gem: "Shock Nova",
+                    radius: 5.,
+                    speed: 200.0,
+                    damage: self.base_damage / 2.,
+                    num_hits: 1,
+                    lifetime: Duration::from_secs_f32(1.5),
+                    knockback_force: 50.0,
+                },
+            )
+            .is_none()
+            {
+                warn!("Failed to spawn shock nova jolt entity");
+                return;
+            }
+        }
+    }
+}
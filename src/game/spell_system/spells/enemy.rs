@@ -1,32 +1,68 @@
-/////////
-// ENEMY //
-/////////
-// A simple spell that fires a jolt of energy dealing damage.
-
 use crate::game::assets::particles::ParticleAsset;
 use crate::game::assets::spell_gfx::SpellGFXAsset;
-use crate::game::projectiles::{ProjectileDamage, ProjectileTeam};
+use crate::game::projectiles::ProjectileDamage;
 use crate::game::spell_system::casting::SpellCastContext;
 use crate::game::spell_system::helpers::{spawn_spell_projectile, ProjectileStats, SpellModel};
-use crate::game::spell_system::SpellEffect;
+use crate::game::spell_system::{SpellComponent, SpellData, SpellEffect};
 use bevy::log::{info, warn};
-use bevy::prelude::World;
+use bevy::math::Vec2;
+use bevy::prelude::{Entity, World};
+use std::f32::consts::TAU;
+use std::slice::Iter;
+use std::sync::Arc;
 use std::time::Duration;
 
+/// Gems only enemies use, never offered to the player.
+pub(super) fn get_enemy_spells() -> Vec<SpellComponent> {
+    vec![SpellComponent {
+        data: Box::new(EnemySpellData { base_damage: 40.0 }),
+        icon_id: 0,
+    }]
+}
+
+/////////
+// ENEMY //
+/////////
+// A simple spell that fires a slow jolt of energy dealing damage.
+
+#[derive(Clone)]
+pub struct EnemySpellData {
+    pub base_damage: f32,
+}
+impl SpellData for EnemySpellData {
+    fn build(&self, _iter: &mut Iter<SpellComponent>) -> Option<Arc<dyn SpellEffect>> {
+        Some(Arc::new(EnemySpell {
+            base_damage: self.base_damage,
+        }))
+    }
+
+    fn get_name(&self) -> String {
+        String::from("Enemy Bolt")
+    }
+
+    fn get_desc(&self) -> String {
+        String::from("Fires a slow jolt of energy dealing: ")
+            + &self.base_damage.to_string()
+            + " damage."
+    }
+}
+
 #[derive(Debug, Clone)]
-pub struct EnemySpell;
+pub struct EnemySpell {
+    pub base_damage: f32,
+}
 impl SpellEffect for EnemySpell {
     fn cast(&self, context: &mut SpellCastContext, world: &mut World) {
         let Some(spell_entity) = spawn_spell_projectile(
             context,
             world,
-            ProjectileTeam::Enemy,
             SpellModel::StaticSprite(SpellGFXAsset::Enemy),
             Some(ParticleAsset::Enemy),
             ProjectileStats {
+                gem: "Enemy Bolt",
                 radius: 5.,
                 speed: 100.0,
-                damage: 1.,
+                damage: self.base_damage,
                 num_hits: 1,
                 lifetime: Duration::from_secs_f32(5.0),
                 knockback_force: 200.0,
@@ -39,3 +75,125 @@ impl SpellEffect for EnemySpell {
         info!("Cast Enemy - DMG: {}", spell_damage);
     }
 }
+
+//////////////////
+// RADIAL BURST //
+//////////////////
+// A boss attack that fires a ring of projectiles in every direction.
+
+#[derive(Debug, Clone)]
+pub struct RadialBurstSpell {
+    pub count: u32,
+    pub speed: f32,
+}
+impl SpellEffect for RadialBurstSpell {
+    fn cast(&self, context: &mut SpellCastContext, world: &mut World) {
+        for i in 0..self.count {
+            let angle = TAU * i as f32 / self.count as f32;
+            let mut cast_context = context.clone();
+            cast_context.direction = Vec2::from_angle(angle).rotate(context.direction);
+            cast_context.values.spread = 0.0;
+            if spawn_boss_projectile(&mut cast_context, world, self.speed).is_none() {
+                warn!("Failed to spawn radial burst spell entity");
+                return;
+            }
+        }
+        info!("Cast Radial Burst - {} projectiles", self.count);
+    }
+}
+
+//////////////////
+// AIMED VOLLEY //
+//////////////////
+// A boss attack that fires a fan of projectiles at whatever the caster is aiming at.
+
+#[derive(Debug, Clone)]
+pub struct AimedVolleySpell {
+    pub count: u32,
+    /// Degrees between the outermost projectiles.
+    pub arc: f32,
+    pub speed: f32,
+}
+impl SpellEffect for AimedVolleySpell {
+    fn cast(&self, context: &mut SpellCastContext, world: &mut World) {
+        let step = if self.count > 1 {
+            self.arc.to_radians() / (self.count - 1) as f32
+        } else {
+            0.0
+        };
+        let start = -self.arc.to_radians() / 2.0;
+        for i in 0..self.count {
+            let angle = if self.count > 1 {
+                start + step * i as f32
+            } else {
+                0.0
+            };
+            let mut cast_context = context.clone();
+            cast_context.direction = Vec2::from_angle(angle).rotate(context.direction);
+            cast_context.values.spread = 0.0;
+            if spawn_boss_projectile(&mut cast_context, world, self.speed).is_none() {
+                warn!("Failed to spawn aimed volley spell entity");
+                return;
+            }
+        }
+        info!("Cast Aimed Volley - {} projectiles", self.count);
+    }
+}
+
+///////////////
+// EXPLOSION //
+///////////////
+// An exploding enemy's last act, damaging everything of the other team around the caster.
+
+#[derive(Debug, Clone)]
+pub struct ExplosionSpell {
+    pub radius: f32,
+    pub damage: f32,
+}
+impl SpellEffect for ExplosionSpell {
+    fn cast(&self, context: &mut SpellCastContext, world: &mut World) {
+        if spawn_spell_projectile(
+            context,
+            world,
+            SpellModel::None,
+            Some(ParticleAsset::Bang),
+            ProjectileStats {
+                gem: "Explosion",
+                radius: self.radius,
+                speed: 0.0,
+                damage: self.damage,
+                num_hits: 1000,
+                lifetime: Duration::from_secs_f32(0.05),
+                knockback_force: 300.0,
+            },
+        )
+        .is_none()
+        {
+            warn!("Failed to spawn explosion spell entity");
+            return;
+        }
+        info!("Cast Explosion - DMG: {}", self.damage);
+    }
+}
+
+fn spawn_boss_projectile(
+    context: &mut SpellCastContext,
+    world: &mut World,
+    speed: f32,
+) -> Option<Entity> {
+    spawn_spell_projectile(
+        context,
+        world,
+        SpellModel::StaticSprite(SpellGFXAsset::Enemy),
+        Some(ParticleAsset::Enemy),
+        ProjectileStats {
+            gem: "Boss Bolt",
+            radius: 6.,
+            speed,
+            damage: 1.,
+            num_hits: 1,
+            lifetime: Duration::from_secs_f32(5.0),
+            knockback_force: 150.0,
+        },
+    )
+}
@@ -1,6 +1,8 @@
 use crate::game::spell_system::storage::SpellPool;
-use bevy::app::{App, Startup};
-use bevy::prelude::ResMut;
+use crate::game::spell_system::SpellComponent;
+use crate::screen::Screen;
+use bevy::app::App;
+use bevy::prelude::{OnEnter, ResMut};
 
 pub mod cores;
 pub mod enemy;
@@ -9,7 +11,7 @@ pub mod multicasters;
 pub mod targeters;
 
 pub(super) fn plugin(app: &mut App) {
-    app.add_systems(Startup, load_spells);
+    app.add_systems(OnEnter(Screen::Playing), load_spells);
     app.add_plugins(
         // (
         // cores::plugin,
@@ -19,9 +21,31 @@ pub(super) fn plugin(app: &mut App) {
     );
 }
 
+/// Refills the pool at the start of every run, so gems banished in the last run come back.
 pub fn load_spells(mut pool: ResMut<SpellPool>) {
+    *pool = SpellPool::default();
     pool.insert_spells(cores::get_spells());
     pool.insert_spells(modifiers::get_spells());
     pool.insert_spells(multicasters::get_spells());
     pool.insert_spells(targeters::get_spells());
 }
+
+/// Looks up a gem by its name, including gems that are only made by fusing and the ones only enemies use.
+pub fn find_spell(name: &str) -> Option<SpellComponent> {
+    let offered = [
+        cores::get_spells(),
+        modifiers::get_spells(),
+        multicasters::get_spells(),
+        targeters::get_spells(),
+    ]
+    .into_iter()
+    .flatten()
+    .map(|(spell, _)| spell);
+    let fused = cores::get_fused_spells()
+        .into_iter()
+        .chain(multicasters::get_fused_spells())
+        .chain(enemy::get_enemy_spells());
+    offered
+        .chain(fused)
+        .find(|spell| spell.data.get_name() == name)
+}
@@ -1,5 +1,6 @@
 use crate::game::projectiles::{ProjectileDamage, ProjectileLifetime};
 use crate::game::spell_system::casting::SpellCastContext;
+use crate::game::spell_system::rarity::SpellRarity;
 use crate::game::spell_system::{SpellComponent, SpellData, SpellEffect, SpellModifier};
 use avian2d::prelude::LinearVelocity;
 use bevy::log::info;
@@ -8,7 +9,7 @@ use std::slice::Iter;
 use std::sync::Arc;
 use std::time::Duration;
 
-pub(super) fn get_spells() -> Vec<(SpellComponent, i32)> {
+pub(super) fn get_spells() -> Vec<(SpellComponent, SpellRarity)> {
     vec![
         (
             SpellComponent {
@@ -17,7 +18,7 @@ pub(super) fn get_spells() -> Vec<(SpellComponent, i32)> {
                 }),
                 icon_id: 12,
             },
-            5,
+            SpellRarity::Common,
         ),
         (
             SpellComponent {
@@ -27,7 +28,7 @@ pub(super) fn get_spells() -> Vec<(SpellComponent, i32)> {
                 }),
                 icon_id: 15,
             },
-            5,
+            SpellRarity::Common,
         ),
         (
             SpellComponent {
@@ -36,7 +37,7 @@ pub(super) fn get_spells() -> Vec<(SpellComponent, i32)> {
                 }),
                 icon_id: 16,
             },
-            5,
+            SpellRarity::Common,
         ),
         (
             SpellComponent {
@@ -47,7 +48,7 @@ pub(super) fn get_spells() -> Vec<(SpellComponent, i32)> {
                 }),
                 icon_id: 17,
             },
-            50,
+            SpellRarity::Rare,
         ),
         (
             SpellComponent {
@@ -58,7 +59,7 @@ pub(super) fn get_spells() -> Vec<(SpellComponent, i32)> {
                 }),
                 icon_id: 18,
             },
-            100,
+            SpellRarity::Legendary,
         ),
     ]
 }
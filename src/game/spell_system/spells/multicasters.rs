@@ -5,10 +5,11 @@ use bevy::prelude::{Entity, World};
 use log::warn;
 
 use crate::game::spell_system::casting::{SpellCastContext, SpellCastValues};
+use crate::game::spell_system::rarity::SpellRarity;
 use crate::game::spell_system::triggers::{do_collision_trigger, CollisionSpellTrigger};
 use crate::game::spell_system::{SpellComponent, SpellData, SpellEffect, SpellModifier};
 
-pub(super) fn get_spells() -> Vec<(SpellComponent, i32)> {
+pub(super) fn get_spells() -> Vec<(SpellComponent, SpellRarity)> {
     vec![
         (
             SpellComponent {
@@ -17,7 +18,7 @@ pub(super) fn get_spells() -> Vec<(SpellComponent, i32)> {
                 }),
                 icon_id: 24,
             },
-            10,
+            SpellRarity::Uncommon,
         ),
         (
             SpellComponent {
@@ -27,7 +28,7 @@ pub(super) fn get_spells() -> Vec<(SpellComponent, i32)> {
                 }),
                 icon_id: 26,
             },
-            10,
+            SpellRarity::Uncommon,
         ),
         (
             SpellComponent {
@@ -37,7 +38,7 @@ pub(super) fn get_spells() -> Vec<(SpellComponent, i32)> {
                 }),
                 icon_id: 27,
             },
-            25,
+            SpellRarity::Rare,
         ),
         (
             SpellComponent {
@@ -47,32 +48,40 @@ pub(super) fn get_spells() -> Vec<(SpellComponent, i32)> {
                 }),
                 icon_id: 28,
             },
-            80,
+            SpellRarity::Legendary,
         ),
         (
             SpellComponent {
                 data: Box::new(BurstCastData { spell_count: 2 }),
                 icon_id: 29,
             },
-            15,
+            SpellRarity::Uncommon,
         ),
         (
             SpellComponent {
                 data: Box::new(BurstCastData { spell_count: 3 }),
                 icon_id: 30,
             },
-            30,
+            SpellRarity::Rare,
         ),
         (
             SpellComponent {
                 data: Box::new(BurstCastData { spell_count: 4 }),
                 icon_id: 28,
             },
-            100,
+            SpellRarity::Legendary,
         ),
     ]
 }
 
+/// Gems that are never offered and only come from fusing other gems.
+pub(super) fn get_fused_spells() -> Vec<SpellComponent> {
+    vec![SpellComponent {
+        data: Box::new(BurstCastData { spell_count: 6 }),
+        icon_id: 30,
+    }]
+}
+
 #[derive(Clone)]
 pub struct ContactCasterData {
     pub spells_triggered: usize,
@@ -123,7 +132,11 @@ impl SpellEffect for ContactCaster {
             let mut spell_context = new_context.clone();
             spell_context.caster = e;
             mod_world.entity_mut(e).insert((CollisionSpellTrigger {
-                values: SpellCastValues::default(),
+                values: SpellCastValues {
+                    team: spell_context.values.team.clone(),
+                    damage_multiplier: spell_context.values.damage_multiplier,
+                    ..SpellCastValues::default()
+                },
                 spells: spells.clone(),
             },));
             mod_world.entity_mut(e).observe(do_collision_trigger);
@@ -2,29 +2,30 @@ use std::f32::consts::PI;
 use std::slice::Iter;
 use std::sync::Arc;
 
-use avian2d::collision::Collider;
-use avian2d::prelude::{LinearVelocity, SpatialQuery, SpatialQueryFilter};
+use avian2d::prelude::LinearVelocity;
 use bevy::app::{App, Update};
 use bevy::log::info;
 use bevy::math::{Quat, Vec3Swizzles};
 use bevy::prelude::{
-    Component, Entity, GlobalTransform, IntoSystemConfigs, Query, Transform, With, World,
+    Component, Entity, GlobalTransform, IntoSystemConfigs, Query, Res, Transform, Trigger, With,
+    World,
 };
-use log::warn;
 
-use crate::game::physics::GameLayer;
-use crate::game::projectiles::ProjectileDamage;
+use crate::game::projectiles::{ProjectileCollisionEvent, ProjectileDamage, ProjectileTeam};
+use crate::game::spatial_grid::EnemyGrid;
 use crate::game::spawn::player::Player;
 use crate::game::spell_system::casting::SpellCastContext;
+use crate::game::spell_system::rarity::SpellRarity;
 use crate::game::spell_system::{SpellComponent, SpellData, SpellEffect, SpellModifier};
 use crate::game::Damageable;
 use crate::AppSet;
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(Update, do_homing.in_set(AppSet::Update));
+    app.observe(do_chain);
 }
 
-pub(super) fn get_spells() -> Vec<(SpellComponent, i32)> {
+pub(super) fn get_spells() -> Vec<(SpellComponent, SpellRarity)> {
     vec![
         (
             SpellComponent {
@@ -35,7 +36,24 @@ pub(super) fn get_spells() -> Vec<(SpellComponent, i32)> {
                 }),
                 icon_id: 36,
             },
-            15,
+            SpellRarity::Uncommon,
+        ),
+        (
+            SpellComponent {
+                data: Box::new(AutoAimData { range: 200.0 }),
+                icon_id: 37,
+            },
+            SpellRarity::Common,
+        ),
+        (
+            SpellComponent {
+                data: Box::new(ChainData {
+                    jumps: 2,
+                    range: 80.0,
+                }),
+                icon_id: 39,
+            },
+            SpellRarity::Rare,
         ),
         // (
         //     SpellComponent {
@@ -46,7 +64,7 @@ pub(super) fn get_spells() -> Vec<(SpellComponent, i32)> {
         //         }),
         //         icon_id: 38,
         //     },
-        //     15,
+        //     SpellRarity::Uncommon,
         // ),
     ]
 }
@@ -55,11 +73,13 @@ pub(super) fn get_spells() -> Vec<(SpellComponent, i32)> {
 
 // 1. AutoAim
 // 2. Homing
+// 3. Chain
 // 4. Bounce
 // 5. Orbit
 
 #[derive(Clone, Debug, Copy)]
 pub enum HomingTarget {
+    /// The closest target on the other team from whoever cast the projectile.
     ClosestEnemy,
     #[allow(dead_code)]
     PlayerOrbit,
@@ -137,7 +157,7 @@ pub struct HomingComponent {
 //system to take an objects linear velocity, that has a child homing component with a collider
 // use that collider to find the nearest enemy within the homing range and lerp the velocity towards that enemy
 pub fn do_homing(
-    spatial_query: SpatialQuery,
+    enemy_grid: Res<EnemyGrid>,
     mut q_projectiles: Query<(
         &HomingComponent,
         &GlobalTransform,
@@ -154,37 +174,19 @@ pub fn do_homing(
 
         let (target, distance): (Option<Entity>, f32) = match homing.target {
             HomingTarget::ClosestEnemy => {
-                let near_enemies = spatial_query.shape_intersections(
-                    &Collider::circle(homing.range),
-                    p_translation.xy(),
-                    0.,
-                    SpatialQueryFilter::from_mask(GameLayer::Enemy),
-                );
-
-                let target = near_enemies
-                    .iter()
-                    .fold((f32::INFINITY, None), |(dist, prev), e| {
-                        // get target entity
-                        let Ok((tr, dm)) = q_targets.get(*e) else {
-                            warn!("Failed to get target from homing child");
-                            return (f32::MAX, prev);
-                        };
-
-                        //check target entity can be damaged
-                        if dm.team == *p_team {
-                            return (dist, prev);
-                        }
-
-                        let new_dist = p_translation.distance(tr.translation());
-
-                        if new_dist < dist {
-                            (new_dist, Some(*e))
-                        } else {
-                            (dist, prev)
-                        }
-                    });
-
-                (target.1, 0.)
+                let target = match p_team {
+                    ProjectileTeam::Player => enemy_grid
+                        .nearest(p_translation.xy(), homing.range)
+                        .map(|(enemy, _)| enemy),
+                    // the player is the only thing enemy projectiles can home in on
+                    ProjectileTeam::Enemy => q_player.get_single().ok().filter(|player| {
+                        q_targets.get(*player).is_ok_and(|(tr, _)| {
+                            tr.translation().distance(p_translation) <= homing.range
+                        })
+                    }),
+                };
+
+                (target, 0.)
             }
             HomingTarget::PlayerOrbit => match q_player.get_single() {
                 Ok(e) => (Some(e), homing.range),
@@ -194,8 +196,8 @@ pub fn do_homing(
 
         if let Some(target) = target {
             let Ok((tr, _)) = q_targets.get(target) else {
-                warn!("Failed to get target from homing child");
-                return;
+                // the grid is built at the start of the frame, so its enemies can be gone already
+                continue;
             };
 
             let target_translation = tr.translation();
@@ -217,3 +219,264 @@ pub fn do_homing(
         }
     }
 }
+
+/////////////
+// AUTOAIM //
+/////////////
+// A targeter that turns the spells it contains towards the closest enemy when they're cast.
+
+#[derive(Clone)]
+pub struct AutoAimData {
+    pub range: f32,
+}
+impl SpellData for AutoAimData {
+    fn build(&self, iter: &mut Iter<SpellComponent>) -> Option<Arc<dyn SpellEffect>> {
+        let spell = iter.next()?.data.build(iter)?;
+        Some(Arc::new(AutoAim {
+            range: self.range,
+            spell,
+        }))
+    }
+
+    fn get_name(&self) -> String {
+        "Auto Aim".to_string()
+    }
+
+    fn get_desc(&self) -> String {
+        "Aims the next spell at the closest enemy within: ".to_string()
+            + &self.range.to_string()
+            + " range."
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AutoAim {
+    pub range: f32,
+    pub spell: Arc<dyn SpellEffect>,
+}
+impl SpellEffect for AutoAim {
+    fn cast(&self, context: &mut SpellCastContext, world: &mut World) {
+        let Some(caster) = world.get::<GlobalTransform>(context.caster) else {
+            self.spell.cast(context, world);
+            return;
+        };
+        let position = caster.translation().xy();
+
+        let target = match context.values.team {
+            ProjectileTeam::Player => world
+                .resource::<EnemyGrid>()
+                .nearest(position, self.range)
+                .map(|(_, enemy)| enemy),
+            // the player is the only thing enemies aim at
+            ProjectileTeam::Enemy => world
+                .query_filtered::<&GlobalTransform, With<Player>>()
+                .get_single(world)
+                .ok()
+                .map(|player| player.translation().xy())
+                .filter(|player| player.distance(position) <= self.range),
+        };
+
+        // with nothing in range the spell keeps the direction it was cast in
+        if let Some(direction) = target.and_then(|target| (target - position).try_normalize()) {
+            context.direction = direction;
+        }
+
+        info!("Cast AutoAim");
+        self.spell.cast(context, world);
+    }
+}
+
+///////////
+// CHAIN //
+///////////
+// A targeter that sends the projectiles it contains on to the next closest enemy when they hit one.
+
+#[derive(Clone)]
+pub struct ChainData {
+    pub jumps: i32,
+    pub range: f32,
+}
+impl SpellData for ChainData {
+    fn build(&self, iter: &mut Iter<SpellComponent>) -> Option<Arc<dyn SpellEffect>> {
+        let spell = iter.next()?.data.build(iter)?;
+        Some(Arc::new(Chain {
+            jumps: self.jumps,
+            range: self.range,
+            spell,
+        }))
+    }
+
+    fn get_name(&self) -> String {
+        "Chain".to_string()
+    }
+
+    fn get_desc(&self) -> String {
+        "On hit, the next spell jumps to another enemy within: ".to_string()
+            + &self.range.to_string()
+            + " range, up to: "
+            + &self.jumps.to_string()
+            + " times."
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Chain {
+    pub jumps: i32,
+    pub range: f32,
+    pub spell: Arc<dyn SpellEffect>,
+}
+impl SpellEffect for Chain {
+    fn cast(&self, context: &mut SpellCastContext, world: &mut World) {
+        let jumps = self.jumps;
+        let range = self.range;
+        let modifier: SpellModifier = Box::new(move |e: Entity, mod_world: &mut World| {
+            mod_world
+                .entity_mut(e)
+                .insert(ChainComponent { jumps, range });
+        });
+
+        context.add_modifier("Chain Mod", modifier);
+        info!("Cast Chain Mod");
+        self.spell.cast(context, world);
+    }
+}
+
+// chain component added to projectiles by the chain spell effect
+#[derive(Component)]
+pub struct ChainComponent {
+    pub jumps: i32, // jumps left
+    pub range: f32, // how far the next enemy can be
+}
+
+// observer that turns a chaining projectile towards the closest enemy other than the one it hit,
+// and gives back the hit it used so it lives to reach it
+pub fn do_chain(
+    trigger: Trigger<ProjectileCollisionEvent>,
+    enemy_grid: Res<EnemyGrid>,
+    mut q_projectiles: Query<(
+        &mut ChainComponent,
+        &GlobalTransform,
+        &mut Transform,
+        &mut ProjectileDamage,
+        &mut LinearVelocity,
+    )>,
+) {
+    let Ok((mut chain, p_gtr, mut p_tr, mut p_dm, mut p_lv)) =
+        q_projectiles.get_mut(trigger.entity())
+    else {
+        return;
+    };
+    // enemy projectiles only ever hit the player, so there's nothing to chain to
+    if chain.jumps <= 0 || p_dm.team != ProjectileTeam::Player {
+        return;
+    }
+
+    let hit = trigger.event().target;
+    let p_translation = p_gtr.translation().xy();
+    let Some((_, next)) = enemy_grid
+        .within(p_translation, chain.range)
+        .filter(|(enemy, _)| *enemy != hit)
+        .min_by(|(_, a), (_, b)| {
+            a.distance_squared(p_translation)
+                .total_cmp(&b.distance_squared(p_translation))
+        })
+    else {
+        return;
+    };
+    let Some(direction) = (next - p_translation).try_normalize() else {
+        return;
+    };
+
+    chain.jumps -= 1;
+    p_dm.hits_remaining += 1;
+    p_lv.0 = direction * p_lv.0.length();
+    p_tr.rotation = Quat::from_rotation_z(direction.y.atan2(direction.x) - PI / 2.);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use avian2d::prelude::{Collision, Contacts};
+    use bevy::math::Vec2;
+    use bevy::prelude::{Timer, TimerMode};
+
+    use super::*;
+    use crate::game::pooling::{Pool, Pooled};
+    use crate::game::projectiles::{self, ProjectileLifetime};
+
+    #[test]
+    fn a_one_hit_projectile_chains_to_the_next_enemy() {
+        let mut app = App::new();
+        app.add_event::<Collision>();
+        app.init_resource::<EnemyGrid>();
+        app.init_resource::<Pool<ProjectileLifetime>>();
+        app.add_plugins(projectiles::plugin);
+        app.observe(do_chain);
+
+        let world = app.world_mut();
+        let enemy = |world: &mut World, x: f32| {
+            let position = Vec2::new(x, 0.);
+            let entity = world
+                .spawn((
+                    Damageable {
+                        max_health: 100.,
+                        health: 100.,
+                        team: ProjectileTeam::Enemy,
+                        invincibility_timer: None,
+                    },
+                    GlobalTransform::from_translation(position.extend(0.)),
+                ))
+                .id();
+            world.resource_mut::<EnemyGrid>().insert(entity, position);
+            entity
+        };
+        let hit = enemy(world, 0.);
+        enemy(world, 50.);
+        let projectile = world
+            .spawn((
+                ProjectileDamage {
+                    team: ProjectileTeam::Player,
+                    damage: 10.,
+                    hits_remaining: 1,
+                    knockback_force: 0.,
+                    gem: None,
+                },
+                ProjectileLifetime {
+                    lifetime: Timer::new(Duration::from_secs(10), TimerMode::Once),
+                },
+                ChainComponent {
+                    jumps: 1,
+                    range: 80.,
+                },
+                Transform::default(),
+                GlobalTransform::from_xyz(-5., 0., 0.),
+                LinearVelocity(Vec2::new(0., 100.)),
+            ))
+            .id();
+        world.send_event(Collision(Contacts {
+            entity1: projectile,
+            entity2: hit,
+            body_entity1: Some(projectile),
+            body_entity2: Some(hit),
+            manifolds: Vec::new(),
+            is_sensor: false,
+            during_current_frame: true,
+            during_previous_frame: false,
+            total_normal_impulse: 0.,
+            total_tangent_impulse: 0.,
+        }));
+
+        app.update();
+
+        let projectile = app.world().entity(projectile);
+        assert!(!projectile.contains::<Pooled>());
+        assert_eq!(
+            projectile.get::<ProjectileDamage>().unwrap().hits_remaining,
+            1
+        );
+        assert_eq!(projectile.get::<ChainComponent>().unwrap().jumps, 0);
+        let velocity = projectile.get::<LinearVelocity>().unwrap().0;
+        assert!(velocity.x > 99. && velocity.y.abs() < 1.);
+    }
+}
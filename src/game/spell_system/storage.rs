@@ -1,17 +1,25 @@
-use std::cmp::Ordering;
+use std::ops::Range;
 use std::sync::Arc;
 
+use crate::config::{STARTING_WAND_SLOTS, STASH_SLOTS};
+use crate::game::characters::Characters;
 use crate::game::spawn::wand::SpawnWand;
+use crate::game::spell_system::fusion::{FusionRecipes, GemLocation};
+use crate::game::spell_system::rarity::{OfferRules, SpellRarity};
+use crate::game::spell_system::spells::find_spell;
 use crate::game::spell_system::triggers::PlayerSpellTrigger;
-use crate::game::spell_system::{SpellComponent, SpellEffect};
+use crate::game::spell_system::{build_effects, SpellComponent, SpellEffect};
 use bevy::app::App;
 use bevy::prelude::{Commands, Event, Query, ResMut, Resource, Trigger};
-use log::{debug, info};
+use log::{debug, info, warn};
+use rand::seq::SliceRandom;
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 
 pub(super) fn plugin(app: &mut App) {
     app.init_resource::<SpellPool>()
         .init_resource::<SpellInventory>()
+        .init_resource::<WandStash>()
         .observe(insert_spell_at_pos)
         .observe(rebuild_wand)
         .observe(new_wand_spells);
@@ -19,69 +27,261 @@ pub(super) fn plugin(app: &mut App) {
 
 #[derive(Resource, Default)]
 pub struct SpellPool {
-    pub spells: Vec<(SpellComponent, i32)>,
+    pub spells: Vec<(SpellComponent, SpellRarity)>,
+    /// Offers made in a row without a rare gem, for the pity timer.
+    offers_since_rare: u32,
+    /// The next offer has a rare gem, e.g. after killing a boss.
+    rare_guaranteed: bool,
 }
 impl SpellPool {
-    // pub fn get_random_spell_component(&self) -> &SpellComponent {
-    //     //gets random spell based on the weights
-    //     let total_weight = self.spells.iter().map(|(_, w)| w).sum::<i32>();
-    //     let mut rng = rand::thread_rng();
-    //     let mut roll = rng.gen_range(0..total_weight);
-    //     for (spell, weight) in &self.spells {
-    //         if roll < *weight {
-    //             return spell;
-    //         }
-    //         roll -= *weight;
-    //     }
-    //     panic!("Failed to get random spell");
-    // }
-
-    pub fn get_x_random_unique_spell_components(&self, x: usize) -> Vec<&SpellComponent> {
-        // gets x random unique spells, based on the weights
-        let mut rng = rand::thread_rng();
-        let spell_indexes = (0..self.spells.len()).collect::<Vec<_>>();
-
-        let mut weighted_indexes = spell_indexes
-            .iter()
-            .map(|i| (*i, 1. - rng.gen::<f32>().powi(self.spells[*i].1)))
-            .collect::<Vec<_>>();
-        weighted_indexes.sort_by(|(_, w1), (_, w2)| w1.partial_cmp(w2).unwrap_or(Ordering::Equal));
+    /// Makes sure the next offer has a rare (or better) gem.
+    pub fn guarantee_rare(&mut self) {
+        self.rare_guaranteed = true;
+    }
 
-        weighted_indexes
-            .iter()
-            .take(x)
-            .map(|(i, _)| &self.spells[*i].0)
-            .collect()
+    /// Draws up to `x` unique gems to offer, rolling the rarity of each one first.
+    /// Only draws that `track_pity` use and update the pity timer, so e.g. the shop
+    /// can't use up a rare guaranteed for the next level up.
+    pub fn draw_offers(
+        &mut self,
+        x: usize,
+        rules: &OfferRules,
+        wave: u32,
+        level: u32,
+        track_pity: bool,
+        rng: &mut impl Rng,
+    ) -> Vec<(SpellComponent, SpellRarity)> {
+        let pity =
+            track_pity && (self.rare_guaranteed || self.offers_since_rare >= rules.rare_pity);
+        let mut remaining = (0..self.spells.len()).collect::<Vec<_>>();
+        let mut offers = Vec::new();
+
+        while offers.len() < x && !remaining.is_empty() {
+            // a pity offer always has at least one rare gem
+            let min = if pity && offers.is_empty() {
+                SpellRarity::Rare
+            } else {
+                SpellRarity::Common
+            };
+            let rolled = rules.roll(wave, level, min, rng);
+            // fall back to the closest tier that still has gems left
+            let Some(rarity) = remaining
+                .iter()
+                .map(|i| self.spells[*i].1)
+                .min_by_key(|r| (*r < min, (*r as i32 - rolled as i32).abs()))
+            else {
+                break;
+            };
+
+            let candidates = remaining
+                .iter()
+                .copied()
+                .filter(|i| self.spells[*i].1 == rarity)
+                .collect::<Vec<_>>();
+            let Some(&pick) = candidates.choose(rng) else {
+                break;
+            };
+            remaining.retain(|i| *i != pick);
+            offers.push(self.spells[pick].clone());
+        }
+
+        if track_pity {
+            if offers.iter().any(|(_, r)| *r >= SpellRarity::Rare) {
+                self.offers_since_rare = 0;
+                self.rare_guaranteed = false;
+            } else {
+                self.offers_since_rare += 1;
+            }
+        }
+        offers.shuffle(rng);
+        offers
+    }
+
+    /// Removes a gem from the pool for the rest of the run.
+    pub fn banish(&mut self, spell: &SpellComponent) {
+        self.spells.retain(|(s, _)| {
+            s.icon_id != spell.icon_id || s.data.get_name() != spell.data.get_name()
+        });
     }
 
-    pub(crate) fn insert_spells(&mut self, spells: Vec<(SpellComponent, i32)>) {
+    pub(crate) fn insert_spells(&mut self, spells: Vec<(SpellComponent, SpellRarity)>) {
         self.spells.extend(spells);
     }
 }
 
-#[derive(Resource, Default)]
+#[derive(Resource)]
 pub struct SpellInventory {
     pub spells: Vec<SpellComponent>,
     pub spell_effects: Vec<Arc<dyn SpellEffect>>,
+    /// How many gems fit in the wand.
+    pub slots: usize,
+}
+impl Default for SpellInventory {
+    fn default() -> Self {
+        SpellInventory {
+            spells: Vec::new(),
+            spell_effects: Vec::new(),
+            slots: STARTING_WAND_SLOTS,
+        }
+    }
 }
 impl SpellInventory {
+    pub fn is_full(&self) -> bool {
+        self.spells.len() >= self.slots
+    }
     pub(crate) fn push_spell(&mut self, spell: SpellComponent) {
         self.spells.push(spell);
         self.rebuild_effects();
     }
     pub(crate) fn rebuild_effects(&mut self) {
-        self.spell_effects.clear();
+        self.spell_effects = build_effects(&self.spells);
+    }
+    pub(crate) fn insert_spell(&mut self, spell: SpellComponent, pos: SpellAddPos) {
+        self.spells.insert(pos.get_index(&self.spells), spell);
+        self.rebuild_effects();
+    }
 
+    /// The gems each built effect is made of, in casting order.
+    /// Gems after the last group don't build into anything and are never cast.
+    pub fn cast_groups(&self) -> Vec<Range<usize>> {
+        let mut groups = Vec::new();
         let mut iter = self.spells.iter();
-        while let Some(effect) = iter.next().and_then(|spell| spell.data.build(&mut iter)) {
-            self.spell_effects.push(effect);
+        loop {
+            let start = self.spells.len() - iter.len();
+            let Some(spell) = iter.next() else {
+                break;
+            };
+            if spell.data.build(&mut iter).is_none() {
+                break;
+            }
+            groups.push(start..self.spells.len() - iter.len());
         }
+        groups
     }
-    pub(crate) fn insert_spell(&mut self, spell: SpellComponent, pos: SpellAddPos) {
-        self.spells.insert(pos.get_index(&self.spells), spell);
+
+    /// Applies an edit made in the wand editor, returns false if it isn't possible.
+    pub fn apply_edit(
+        &mut self,
+        stash: &mut WandStash,
+        recipes: &FusionRecipes,
+        edit: WandEdit,
+    ) -> bool {
+        let len = self.spells.len();
+        match edit {
+            WandEdit::Swap(a, b) => {
+                if a >= len || b >= len {
+                    return false;
+                }
+                self.spells.swap(a, b);
+            }
+            WandEdit::Move { from, to } => {
+                if from >= len || to >= len {
+                    return false;
+                }
+                let spell = self.spells.remove(from);
+                self.spells.insert(to, spell);
+            }
+            WandEdit::Stash(from) => {
+                // keep at least one gem, so the wand can still shoot
+                if from >= len || len <= 1 || stash.is_full() {
+                    return false;
+                }
+                stash.spells.push(self.spells.remove(from));
+            }
+            WandEdit::Unstash { from, to } => {
+                if from >= stash.spells.len() || to > len || self.is_full() {
+                    return false;
+                }
+                let spell = stash.spells.remove(from);
+                self.spells.insert(to, spell);
+            }
+            WandEdit::SwapStashed { stash: from, wand } => {
+                if from >= stash.spells.len() || wand >= len {
+                    return false;
+                }
+                std::mem::swap(&mut stash.spells[from], &mut self.spells[wand]);
+            }
+            WandEdit::Fuse(recipe) => {
+                let Some(recipe) = recipes.0.get(recipe) else {
+                    return false;
+                };
+                let Some(ingredients) = recipe.find_ingredients(&self.spells, &stash.spells) else {
+                    return false;
+                };
+                let Some(result) = find_spell(&recipe.result) else {
+                    warn!("Fusion recipe makes unknown gem {}", recipe.result);
+                    return false;
+                };
+                info!("Fused {}", recipe.describe());
+                self.fuse(stash, &ingredients, result);
+            }
+        }
         self.rebuild_effects();
+        true
+    }
+
+    /// Replaces the ingredients with the fused gem, which takes the place of the first
+    /// ingredient in the wand, or goes into the stash if they were all stashed.
+    fn fuse(&mut self, stash: &mut WandStash, ingredients: &[GemLocation], result: SpellComponent) {
+        let mut from_wand = Vec::new();
+        let mut from_stash = Vec::new();
+        for location in ingredients {
+            match *location {
+                GemLocation::Wand(i) => from_wand.push(i),
+                GemLocation::Stash(i) => from_stash.push(i),
+            }
+        }
+        // remove from the back, so the other indices stay valid
+        from_wand.sort_unstable_by(|a, b| b.cmp(a));
+        from_stash.sort_unstable_by(|a, b| b.cmp(a));
+        for i in &from_wand {
+            self.spells.remove(*i);
+        }
+        for i in &from_stash {
+            stash.spells.remove(*i);
+        }
+
+        match from_wand.last() {
+            Some(first) => self.spells.insert(*first, result),
+            None => stash.spells.push(result),
+        }
+    }
+}
+
+/// Gems taken out of the wand, which can be put back in at any time.
+#[derive(Resource, Default)]
+pub struct WandStash {
+    pub spells: Vec<SpellComponent>,
+}
+impl WandStash {
+    pub fn is_full(&self) -> bool {
+        self.spells.len() >= STASH_SLOTS
     }
 }
+
+/// A change made to the wand in the wand editor.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WandEdit {
+    Swap(usize, usize),
+    /// Moves a gem so it ends up at index `to`.
+    Move {
+        from: usize,
+        to: usize,
+    },
+    /// Moves a gem from the wand into the stash.
+    Stash(usize),
+    /// Puts a stashed gem back into the wand at index `to`.
+    Unstash {
+        from: usize,
+        to: usize,
+    },
+    SwapStashed {
+        stash: usize,
+        wand: usize,
+    },
+    /// Fuses the gems of the [`FusionRecipes`] entry with this index.
+    Fuse(usize),
+}
 #[derive(Event)]
 pub struct AddSpellTo(SpellComponent, SpellAddPos);
 #[derive(Event)]
@@ -145,50 +345,207 @@ fn rebuild_wand(
     }
 }
 
+/// Fills the wand with the selected character's starting gems.
 pub fn new_wand_spells(
     _trigger: Trigger<SpawnWand>,
     mut inventory: ResMut<SpellInventory>,
+    mut stash: ResMut<WandStash>,
+    characters: Characters,
     mut commands: Commands,
 ) {
+    let character = characters.selected();
     inventory.spells.clear();
-    // inventory.push_spell(SpellComponent {
-    //     data: Box::new(
-    //         crate::game::spell_system::spells::modifiers::DuplicateData {
-    //             spread_increase: 40.,
-    //             bullet_count: 3,
-    //             damage_decrease: 0.5,
-    //         },
-    //     ),
-    //     icon_id: 18,
-    // });
-    inventory.push_spell(SpellComponent {
-        data: Box::new(crate::game::spell_system::spells::cores::ZapSpellData {
-            base_damage: 40.0,
-        }),
-        icon_id: 0,
-    });
-    // inventory.push_spell(SpellComponent {
-    //     data: Box::new(crate::game::spell_system::spells::cores::BangSpellData {
-    //         base_damage: 40.0,
-    //         radius: 30.0,
-    //     }),
-    //     icon_id: 3,
-    // },);
-    // inventory.push_spell(SpellComponent {
-    //     data: Box::new(crate::game::spell_system::spells::cores::ArcaneArrowSpellData {
-    //         base_damage: 30.0,
-    //         speed: 400.0,
-    //         num_hits: 3,
-    //     }),
-    //     icon_id: 1,
-    // });
-    // inventory.push_spell(SpellComponent {
-    //     data: Box::new(crate::game::spell_system::spells::cores::SplitterBoltsSpellData {
-    //         base_damage: 20.0,
-    //         projectile_count: 3,
-    //     }),
-    //     icon_id: 2,
-    // });
+    stash.spells.clear();
+    inventory.slots = character.wand.slots;
+    for name in &character.wand.gems {
+        match find_spell(name) {
+            Some(spell) => inventory.push_spell(spell),
+            None => warn!("Unknown gem {:?} in {}'s wand", name, character.name),
+        }
+    }
 
     commands.trigger(RebuildWand);
 }
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::game::spell_system::fusion::FusionRecipe;
+    use crate::game::spell_system::spells::cores::ZapSpellData;
+
+    fn gem() -> SpellComponent {
+        SpellComponent {
+            data: Box::new(ZapSpellData { base_damage: 40.0 }),
+            icon_id: 0,
+        }
+    }
+
+    /// Four common gems and a rare one, with odds that never roll past common by themselves.
+    fn pool_and_rules() -> (SpellPool, OfferRules) {
+        let mut pool = SpellPool::default();
+        pool.insert_spells(
+            [
+                SpellRarity::Common,
+                SpellRarity::Common,
+                SpellRarity::Common,
+                SpellRarity::Common,
+                SpellRarity::Rare,
+            ]
+            .into_iter()
+            .map(|rarity| (gem(), rarity))
+            .collect(),
+        );
+        let rules = OfferRules {
+            base_weights: [1., 0., 0., 0.],
+            weight_per_wave: [0.; 4],
+            weight_per_level: [0.; 4],
+            rare_pity: 2,
+        };
+        (pool, rules)
+    }
+
+    fn has_rare(offers: &[(SpellComponent, SpellRarity)]) -> bool {
+        offers
+            .iter()
+            .any(|(_, rarity)| *rarity >= SpellRarity::Rare)
+    }
+
+    #[test]
+    fn pity_offers_a_rare_after_enough_offers_without_one() {
+        let (mut pool, rules) = pool_and_rules();
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..rules.rare_pity {
+            assert!(!has_rare(
+                &pool.draw_offers(3, &rules, 1, 1, true, &mut rng)
+            ));
+        }
+        assert!(has_rare(&pool.draw_offers(3, &rules, 1, 1, true, &mut rng)));
+        // the timer starts over after a rare
+        assert!(!has_rare(
+            &pool.draw_offers(3, &rules, 1, 1, true, &mut rng)
+        ));
+    }
+
+    #[test]
+    fn untracked_draws_leave_the_pity_timer_alone() {
+        let (mut pool, rules) = pool_and_rules();
+        let mut rng = StdRng::seed_from_u64(0);
+        pool.guarantee_rare();
+        for _ in 0..rules.rare_pity + 1 {
+            assert!(!has_rare(
+                &pool.draw_offers(3, &rules, 1, 1, false, &mut rng)
+            ));
+        }
+        // the guarantee is still there for the next tracked draw
+        assert!(has_rare(&pool.draw_offers(3, &rules, 1, 1, true, &mut rng)));
+        assert!(!has_rare(
+            &pool.draw_offers(3, &rules, 1, 1, true, &mut rng)
+        ));
+    }
+
+    #[test]
+    fn guaranteed_rare_is_offered_next() {
+        let (mut pool, rules) = pool_and_rules();
+        let mut rng = StdRng::seed_from_u64(0);
+        pool.guarantee_rare();
+        assert!(has_rare(&pool.draw_offers(3, &rules, 1, 1, true, &mut rng)));
+        assert!(!has_rare(
+            &pool.draw_offers(3, &rules, 1, 1, true, &mut rng)
+        ));
+    }
+
+    fn gems(names: &[&str]) -> Vec<SpellComponent> {
+        names.iter().map(|name| find_spell(name).unwrap()).collect()
+    }
+
+    fn names(spells: &[SpellComponent]) -> Vec<String> {
+        spells.iter().map(|spell| spell.data.get_name()).collect()
+    }
+
+    fn wand_and_stash(wand: &[&str], stash: &[&str]) -> (SpellInventory, WandStash) {
+        let inventory = SpellInventory {
+            spells: gems(wand),
+            ..SpellInventory::default()
+        };
+        let stash = WandStash {
+            spells: gems(stash),
+        };
+        (inventory, stash)
+    }
+
+    fn apply(
+        (inventory, stash): &mut (SpellInventory, WandStash),
+        recipes: &FusionRecipes,
+        edit: WandEdit,
+    ) -> bool {
+        inventory.apply_edit(stash, recipes, edit)
+    }
+
+    #[test]
+    fn moved_gems_end_up_at_the_target_index() {
+        let mut gems = wand_and_stash(&["Zap", "Bang", "DMG UP"], &[]);
+        assert!(apply(
+            &mut gems,
+            &FusionRecipes::default(),
+            WandEdit::Move { from: 0, to: 2 }
+        ));
+        assert_eq!(names(&gems.0.spells), ["Bang", "DMG UP", "Zap"]);
+    }
+
+    #[test]
+    fn unstashing_at_the_end_of_the_wand_appends() {
+        let mut gems = wand_and_stash(&["Zap"], &["Bang"]);
+        let recipes = FusionRecipes::default();
+        assert!(!apply(
+            &mut gems,
+            &recipes,
+            WandEdit::Unstash { from: 0, to: 2 }
+        ));
+        assert!(apply(
+            &mut gems,
+            &recipes,
+            WandEdit::Unstash { from: 0, to: 1 }
+        ));
+        assert_eq!(names(&gems.0.spells), ["Zap", "Bang"]);
+        assert!(gems.1.spells.is_empty());
+    }
+
+    #[test]
+    fn full_wands_and_last_gems_stay_put() {
+        let recipes = FusionRecipes::default();
+        let mut gems = wand_and_stash(&["Zap"], &[]);
+        assert!(!apply(&mut gems, &recipes, WandEdit::Stash(0)));
+
+        let mut gems = wand_and_stash(&["Zap"; STARTING_WAND_SLOTS], &["Bang"]);
+        assert!(!apply(
+            &mut gems,
+            &recipes,
+            WandEdit::Unstash { from: 0, to: 0 }
+        ));
+    }
+
+    #[test]
+    fn fused_gem_takes_the_place_of_the_first_ingredient_in_the_wand() {
+        let recipes = FusionRecipes(vec![FusionRecipe {
+            ingredients: vec![String::from("Zap"), String::from("Bang")],
+            result: String::from("Shock Nova"),
+        }]);
+
+        let mut gems = wand_and_stash(&["DMG UP", "Bang", "Burst 2", "Zap"], &[]);
+        assert!(apply(&mut gems, &recipes, WandEdit::Fuse(0)));
+        assert_eq!(names(&gems.0.spells), ["DMG UP", "Shock Nova", "Burst 2"]);
+
+        // stashed ingredients are used up first, the rest of the stash keeps its order
+        let mut gems = wand_and_stash(&["Bang", "DMG UP"], &["Zap", "Burst 2", "Zap"]);
+        assert!(apply(&mut gems, &recipes, WandEdit::Fuse(0)));
+        assert_eq!(names(&gems.0.spells), ["Shock Nova", "DMG UP"]);
+        assert_eq!(names(&gems.1.spells), ["Burst 2", "Zap"]);
+
+        let mut gems = wand_and_stash(&["DMG UP"], &["Bang", "Zap"]);
+        assert!(apply(&mut gems, &recipes, WandEdit::Fuse(0)));
+        assert_eq!(names(&gems.0.spells), ["DMG UP"]);
+        assert_eq!(names(&gems.1.spells), ["Shock Nova"]);
+    }
+}
@@ -15,6 +15,7 @@ use bevy::time::Time;
 use leafwing_input_manager::action_state::ActionState;
 
 use crate::game::input::PlayerAction;
+use crate::game::player_mods::death::player_not_dying;
 use crate::game::projectiles::ProjectileCollisionEvent;
 use crate::game::spell_system::casting::{
     InstantCaster, SequentialCaster, SpellCastValues, SpellCaster,
@@ -30,7 +31,7 @@ pub(super) fn plugin(app: &mut App) {
             tick_timer_trigger
                 .in_set(AppSet::TickTimers)
                 .run_if(in_state(GameState::Running)),
-            (do_timer_trigger, do_player_trigger)
+            (do_timer_trigger, do_player_trigger.run_if(player_not_dying))
                 .in_set(AppSet::Update)
                 .run_if(in_state(GameState::Running)),
             //todo: more triggers
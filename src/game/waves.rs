@@ -0,0 +1,382 @@
+//! The wave schedule, an asset describing every wave of a run.
+//!
+//! Waves are authored in [`WAVE_SCHEDULE_PATH`], and waves after the last authored one
+//! keep growing by the schedule's endless increments. Dev builds reload the schedule
+//! whenever the file changes, so waves can be tuned while playing.
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+use serde::Deserialize;
+
+use crate::game::assets::ron_loader::RonAssetLoader;
+
+#[cfg(feature = "dev")]
+use crate::game::enemy::Wave;
+
+pub const WAVE_SCHEDULE_PATH: &str = "waves/schedule.waves.ron";
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<WaveSchedule>();
+    app.register_asset_loader(RonAssetLoader::<WaveSchedule>::new(&["waves.ron"]));
+    app.register_type::<WaveDefinition>();
+    #[cfg(feature = "dev")]
+    app.add_systems(
+        Update,
+        reload_wave_schedule.run_if(resource_exists::<WaveScheduleHandle>),
+    );
+}
+
+#[derive(Asset, TypePath, Deserialize, Debug, Clone, Default)]
+pub struct WaveSchedule {
+    pub waves: Vec<WaveDefinition>,
+    pub endless: EndlessScaling,
+    /// Every wave that's a multiple of this starts with a boss, 0 for no bosses.
+    #[serde(default)]
+    pub boss_every: u32,
+}
+
+#[derive(Deserialize, Debug, Clone, Reflect)]
+pub struct WaveDefinition {
+    /// Seconds until the wave ends.
+    pub duration: f32,
+    /// Enemies spawned every second.
+    pub spawn_rate: u32,
+    /// Spawning stops while this many enemies are alive.
+    pub max_enemies: u32,
+    #[serde(default)]
+    pub kinds: KindCounts,
+    #[serde(default)]
+    pub stats: EnemyScaling,
+    #[serde(default)]
+    pub events: Vec<WaveEvent>,
+    /// Gem names making up the wand of every ranged enemy, built like the player's wand.
+    #[serde(default = "default_enemy_wand")]
+    pub enemy_wand: Vec<String>,
+    /// Every spawn batch picks one of these, scattered if there are none.
+    #[serde(default)]
+    pub formations: Vec<SpawnFormation>,
+}
+
+impl WaveDefinition {
+    /// A short description of the enemies in the wave, for the intermission banner.
+    pub fn composition(&self) -> String {
+        let kinds = &self.kinds;
+        let mut parts = vec![String::from("Basic")];
+        for (name, count) in [
+            ("Ranged", kinds.ranged),
+            ("Tanks", kinds.tanks),
+            ("Exploders", kinds.exploders),
+            ("Splitters", kinds.splitters),
+            ("Healers", kinds.healers),
+            ("Summoners", kinds.summoners),
+        ] {
+            if count > 0 {
+                parts.push(name.to_string());
+            }
+        }
+        let swarms = self
+            .events
+            .iter()
+            .filter(|event| matches!(event, WaveEvent::Swarm { .. }))
+            .count();
+        if swarms > 0 {
+            parts.push(format!("{} Swarms", swarms));
+        }
+        if self
+            .events
+            .iter()
+            .any(|event| matches!(event, WaveEvent::Boss { .. }))
+        {
+            parts.push(String::from("Boss"));
+        }
+        format!("Up to {} enemies: {}", self.max_enemies, parts.join(", "))
+    }
+}
+
+fn default_enemy_wand() -> Vec<String> {
+    vec![String::from("Enemy Bolt")]
+}
+
+impl Default for WaveDefinition {
+    fn default() -> Self {
+        WaveDefinition {
+            duration: 45.,
+            spawn_rate: 10,
+            max_enemies: 30,
+            kinds: KindCounts {
+                ranged: 1,
+                tanks: 1,
+                ..default()
+            },
+            stats: EnemyScaling::default(),
+            events: Vec::new(),
+            enemy_wand: default_enemy_wand(),
+            formations: Vec::new(),
+        }
+    }
+}
+
+/// How many enemies of every spawn batch are of each kind, the rest are basic enemies.
+#[derive(Deserialize, Debug, Clone, Default, Reflect)]
+#[serde(default)]
+pub struct KindCounts {
+    pub ranged: u32,
+    pub tanks: u32,
+    pub exploders: u32,
+    pub splitters: u32,
+    pub healers: u32,
+    pub summoners: u32,
+}
+
+/// Multipliers for the stats of the enemies spawned in a wave.
+#[derive(Deserialize, Debug, Clone, Reflect)]
+#[serde(default)]
+pub struct EnemyScaling {
+    pub health: f32,
+    pub damage: f32,
+    pub experience: f32,
+}
+
+impl Default for EnemyScaling {
+    fn default() -> Self {
+        EnemyScaling {
+            health: 1.,
+            damage: 1.,
+            experience: 1.,
+        }
+    }
+}
+
+/// How a group of enemies is placed around the player when it spawns.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, Reflect)]
+pub enum SpawnFormation {
+    /// Anywhere off screen.
+    #[default]
+    Scattered,
+    /// A ring closing in on the player from every side.
+    Ring,
+    /// A line along the arena edge furthest from the player.
+    Line,
+    /// A tight pack somewhere off screen.
+    Pack,
+}
+
+/// Something that happens once during a wave.
+#[derive(Deserialize, Debug, Clone, Reflect)]
+pub enum WaveEvent {
+    /// Spawns `count` basic enemies at once, ignoring the spawn rate.
+    Swarm {
+        at: f32,
+        count: u32,
+        #[serde(default)]
+        formation: SpawnFormation,
+    },
+    /// Spawns a boss, normal spawning stops until it's dead.
+    Boss { at: f32 },
+}
+
+impl WaveEvent {
+    /// Seconds into the wave the event happens.
+    pub fn at(&self) -> f32 {
+        match self {
+            WaveEvent::Swarm { at, .. } | WaveEvent::Boss { at } => *at,
+        }
+    }
+}
+
+/// Added to the last authored wave for every wave after it.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct EndlessScaling {
+    pub duration: f32,
+    pub max_duration: f32,
+    pub spawn_rate: f32,
+    pub max_enemies: f32,
+    pub ranged: f32,
+    pub tanks: f32,
+    #[serde(default)]
+    pub exploders: f32,
+    #[serde(default)]
+    pub splitters: f32,
+    #[serde(default)]
+    pub healers: f32,
+    #[serde(default)]
+    pub summoners: f32,
+    pub health: f32,
+    pub damage: f32,
+    pub experience: f32,
+}
+
+impl WaveSchedule {
+    pub fn definition(&self, number: u32) -> WaveDefinition {
+        let mut definition = self.authored_or_endless(number);
+        if self.boss_every > 0 && number.is_multiple_of(self.boss_every) {
+            definition.events.push(WaveEvent::Boss { at: 0. });
+        }
+        definition
+    }
+
+    fn authored_or_endless(&self, number: u32) -> WaveDefinition {
+        let index = number.saturating_sub(1) as usize;
+        if let Some(definition) = self.waves.get(index) {
+            return definition.clone();
+        }
+        let Some(last) = self.waves.last() else {
+            return WaveDefinition::default();
+        };
+
+        let extra = (index + 1 - self.waves.len()) as f32;
+        let endless = &self.endless;
+        let grow = |base: u32, per_wave: f32| base + (per_wave * extra).round() as u32;
+        WaveDefinition {
+            duration: (last.duration + endless.duration * extra)
+                .min(endless.max_duration.max(last.duration)),
+            spawn_rate: grow(last.spawn_rate, endless.spawn_rate),
+            max_enemies: grow(last.max_enemies, endless.max_enemies),
+            kinds: KindCounts {
+                ranged: grow(last.kinds.ranged, endless.ranged),
+                tanks: grow(last.kinds.tanks, endless.tanks),
+                exploders: grow(last.kinds.exploders, endless.exploders),
+                splitters: grow(last.kinds.splitters, endless.splitters),
+                healers: grow(last.kinds.healers, endless.healers),
+                summoners: grow(last.kinds.summoners, endless.summoners),
+            },
+            stats: EnemyScaling {
+                health: last.stats.health + endless.health * extra,
+                damage: last.stats.damage + endless.damage * extra,
+                experience: last.stats.experience + endless.experience * extra,
+            },
+            events: last.events.clone(),
+            enemy_wand: last.enemy_wand.clone(),
+            formations: last.formations.clone(),
+        }
+    }
+}
+
+/// The schedule used by every run, loaded on the loading screen.
+#[derive(Resource, Debug)]
+pub struct WaveScheduleHandle(pub Handle<WaveSchedule>);
+
+/// Looks up waves in the loaded schedule, falling back to [`WaveDefinition::default`]
+/// if it failed to load.
+#[derive(SystemParam)]
+pub struct Waves<'w> {
+    schedules: Res<'w, Assets<WaveSchedule>>,
+    handle: Option<Res<'w, WaveScheduleHandle>>,
+}
+
+impl Waves<'_> {
+    pub fn definition(&self, number: u32) -> WaveDefinition {
+        self.handle
+            .as_ref()
+            .and_then(|handle| self.schedules.get(&handle.0))
+            .map_or_else(WaveDefinition::default, |schedule| {
+                schedule.definition(number)
+            })
+    }
+}
+
+#[cfg(feature = "dev")]
+fn reload_wave_schedule(
+    mut events: EventReader<AssetEvent<WaveSchedule>>,
+    waves: Waves,
+    mut wave: ResMut<Wave>,
+) {
+    for event in events.read() {
+        let AssetEvent::Modified { id } = event else {
+            continue;
+        };
+        if waves
+            .handle
+            .as_ref()
+            .is_some_and(|handle| handle.0.id() == *id)
+        {
+            info!("Reloaded the wave schedule");
+            let number = wave.number();
+            wave.redefine(waves.definition(number));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule() -> WaveSchedule {
+        WaveSchedule {
+            waves: vec![WaveDefinition {
+                duration: 40.,
+                spawn_rate: 10,
+                max_enemies: 50,
+                kinds: KindCounts {
+                    ranged: 2,
+                    ..default()
+                },
+                ..default()
+            }],
+            endless: EndlessScaling {
+                duration: 5.,
+                max_duration: 50.,
+                spawn_rate: 2.5,
+                max_enemies: 10.,
+                ranged: 0.5,
+                health: 0.5,
+                ..default()
+            },
+            boss_every: 0,
+        }
+    }
+
+    #[test]
+    fn authored_waves_are_used_as_written() {
+        let definition = schedule().definition(1);
+        assert_eq!(definition.spawn_rate, 10);
+        assert_eq!(definition.max_enemies, 50);
+        assert_eq!(definition.kinds.ranged, 2);
+    }
+
+    #[test]
+    fn waves_after_the_last_grow_from_it() {
+        let definition = schedule().definition(3);
+        assert_eq!(definition.duration, 50.);
+        assert_eq!(definition.spawn_rate, 15);
+        assert_eq!(definition.max_enemies, 70);
+        assert_eq!(definition.kinds.ranged, 3);
+        assert_eq!(definition.kinds.tanks, 0);
+        assert_eq!(definition.stats.health, 2.);
+        assert_eq!(definition.stats.damage, 1.);
+    }
+
+    #[test]
+    fn endless_duration_stops_at_the_max() {
+        assert_eq!(schedule().definition(10).duration, 50.);
+    }
+
+    #[test]
+    fn bosses_start_every_boss_wave() {
+        let mut schedule = schedule();
+        schedule.boss_every = 5;
+        let has_boss = |number| {
+            schedule
+                .definition(number)
+                .events
+                .iter()
+                .any(|event| matches!(event, WaveEvent::Boss { at } if *at == 0.))
+        };
+        assert!(!has_boss(1));
+        assert!(!has_boss(4));
+        assert!(has_boss(5));
+        assert!(has_boss(10));
+    }
+
+    #[test]
+    fn an_empty_schedule_falls_back_to_the_default_wave() {
+        let definition = WaveSchedule::default().definition(7);
+        assert_eq!(definition.spawn_rate, WaveDefinition::default().spawn_rate);
+    }
+
+    #[test]
+    fn shipped_schedule_parses() {
+        let schedule: WaveSchedule =
+            ron::de::from_str(include_str!("../../assets/waves/schedule.waves.ron")).unwrap();
+        assert!(!schedule.waves.is_empty());
+    }
+}
@@ -23,7 +23,13 @@ impl Plugin for AppPlugin {
         // Order new `AppStep` variants by adding them here:
         app.configure_sets(
             Update,
-            (AppSet::TickTimers, AppSet::RecordInput, AppSet::Update).chain(),
+            (
+                AppSet::TickTimers,
+                AppSet::RecordInput,
+                AppSet::ReplayInput,
+                AppSet::Update,
+            )
+                .chain(),
         );
 
         // Spawn the main camera.
@@ -95,6 +101,8 @@ enum AppSet {
     TickTimers,
     /// Record player input.
     RecordInput,
+    /// Record the player's input to a replay, or overwrite it while playing one back.
+    ReplayInput,
     /// Do everything else (consider splitting this into further variants).
     Update,
 }
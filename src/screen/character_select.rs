@@ -0,0 +1,145 @@
+//! The character select screen between the title screen and a run.
+
+use bevy::{ecs::system::EntityCommands, prelude::*, ui::Val::*};
+
+use super::{GameState, Screen};
+use crate::{
+    game::{
+        audio::soundtrack::Soundtrack,
+        characters::{Characters, Progress, SelectedCharacter},
+    },
+    ui::{palette::*, prelude::*, DefaultButtonSound},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::CharacterSelect), enter_character_select);
+    app.add_systems(
+        OnExit(Screen::CharacterSelect),
+        exit_character_select.run_if(in_state(Screen::Playing)),
+    );
+
+    app.register_type::<CharacterSelectAction>();
+    app.add_systems(
+        Update,
+        handle_character_select_action.run_if(in_state(Screen::CharacterSelect)),
+    );
+}
+
+#[derive(Component, Debug, Clone, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum CharacterSelectAction {
+    /// Starts a run as the character with this name.
+    Select(String),
+    Back,
+}
+
+fn enter_character_select(mut commands: Commands, characters: Characters, progress: Res<Progress>) {
+    commands
+        .ui_root()
+        .insert(StateScoped(Screen::CharacterSelect))
+        .with_children(|children| {
+            children.header("Choose a Character");
+
+            children
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Percent(90.0),
+                        flex_direction: FlexDirection::Row,
+                        flex_wrap: FlexWrap::Wrap,
+                        justify_content: JustifyContent::Center,
+                        column_gap: Px(10.0),
+                        row_gap: Px(10.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|children| {
+                    for character in characters.all() {
+                        let locked = character.unlock.filter(|unlock| !unlock.is_met(&progress));
+                        let details = match locked {
+                            Some(unlock) => format!("Locked\n{}", unlock.description()),
+                            None => format!(
+                                "{}\nWand: {}\n{}",
+                                character.description,
+                                character.wand.gems.join(", "),
+                                character.passive.description()
+                            ),
+                        };
+                        let text = format!("{}\n{}", character.name, details);
+                        let mut card = character_card(children, text);
+                        if locked.is_none() {
+                            card.insert((
+                                InteractionPalette {
+                                    none: NODE_BACKGROUND,
+                                    hovered: BUTTON_HOVERED_BACKGROUND,
+                                    pressed: BUTTON_PRESSED_BACKGROUND,
+                                },
+                                DefaultButtonSound,
+                                CharacterSelectAction::Select(character.name),
+                            ));
+                        }
+                    }
+                });
+
+            children.button("Back").insert(CharacterSelectAction::Back);
+        });
+}
+
+/// A card describing a character, only playable characters get an action.
+fn character_card<'a>(children: &'a mut ChildBuilder, text: String) -> EntityCommands<'a> {
+    let mut entity = children.spawn((
+        Name::new("Character Card"),
+        ButtonBundle {
+            style: Style {
+                width: Px(260.0),
+                padding: UiRect::all(Px(10.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                border: UiRect::all(Px(3.0)),
+                ..default()
+            },
+            border_color: BorderColor(NODE_BACKGROUND.1),
+            border_radius: BorderRadius::all(Percent(10.)),
+            background_color: BackgroundColor(NODE_BACKGROUND.0),
+            ..default()
+        },
+    ));
+    entity.with_children(|children| {
+        children.spawn(
+            TextBundle::from_section(
+                text,
+                TextStyle {
+                    font_size: 24.,
+                    color: BUTTON_TEXT,
+                    ..default()
+                },
+            )
+            .with_text_justify(JustifyText::Center),
+        );
+    });
+    entity
+}
+
+fn exit_character_select(mut commands: Commands) {
+    commands.trigger(Soundtrack::Disable);
+}
+
+fn handle_character_select_action(
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+    mut selected: ResMut<SelectedCharacter>,
+    mut button_query: InteractionQuery<&CharacterSelectAction>,
+) {
+    for (interaction, action) in &mut button_query {
+        if matches!(interaction, Interaction::Pressed) {
+            match action {
+                CharacterSelectAction::Select(name) => {
+                    selected.0 = name.clone();
+                    next_screen.set(Screen::Playing);
+                    next_game_state.set(GameState::Running);
+                }
+                CharacterSelectAction::Back => next_screen.set(Screen::Title),
+            }
+        }
+    }
+}
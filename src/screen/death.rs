@@ -1,8 +1,17 @@
+//! The run summary shown once the player has died.
+
 use bevy::prelude::*;
 
 use super::{GameState, Screen};
 use crate::{
-    game::{audio::sfx::Sfx, enemy::ClearWave},
+    game::{
+        enemy::{ClearWave, Wave},
+        levelling::PlayerLevel,
+        rng::RunSeed,
+        run_stats::RunStats,
+        spawn::player::Player,
+        spell_system::storage::SpellInventory,
+    },
     ui::prelude::*,
 };
 
@@ -22,17 +31,69 @@ pub(super) fn plugin(app: &mut App) {
 #[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
 #[reflect(Component)]
 enum DeathAction {
+    /// Starts another run with the same seed, after picking a character.
+    Retry,
     Menu,
 }
 
-fn enter_death(mut commands: Commands) {
+fn enter_death(
+    mut commands: Commands,
+    seed: Res<RunSeed>,
+    wave: Res<Wave>,
+    stats: Res<RunStats>,
+    spell_inventory: Res<SpellInventory>,
+    level_query: Query<&PlayerLevel, With<Player>>,
+) {
     commands.trigger(ClearWave);
-    commands.trigger(Sfx::WizardDies);
+
+    let level = level_query.get_single().map_or(1, |level| level.level);
+    let seconds = stats.time as u32;
+    let kills = stats.kills_by_kind();
+    let total_kills: u32 = kills.iter().map(|(_, count)| count).sum();
+    let kills = kills
+        .iter()
+        .map(|(kind, count)| format!("{:?}: {}", kind, count))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let damage = stats
+        .damage_by_gem()
+        .iter()
+        .map(|(gem, amount)| format!("{}: {:.0}", gem, amount))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let wand = spell_inventory
+        .spells
+        .iter()
+        .map(|spell| spell.data.get_name())
+        .collect::<Vec<_>>()
+        .join(", ");
+
     commands
         .ui_root()
         .insert(StateScoped(GameState::Death))
+        .insert(BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)))
         .with_children(|children| {
-            children.label("You Died.");
+            children.header("You Died.");
+            children.label(format!(
+                "Wave {} - Level {} - Survived {}:{:02}",
+                wave.number(),
+                level,
+                seconds / 60,
+                seconds % 60
+            ));
+            children.label(format!("Enemies killed: {}", total_kills));
+            if !kills.is_empty() {
+                children.label(kills);
+            }
+            if !damage.is_empty() {
+                children.label("Damage dealt");
+                children.label(damage);
+            }
+            children.label(format!("Wand: {}", wand));
+            children.label(format!("Seed: {}", *seed));
+            children
+                .button("Retry Same Seed")
+                .insert(DeathAction::Retry);
             children.button("Main Menu").insert(DeathAction::Menu);
         });
 }
@@ -41,11 +102,17 @@ fn handle_death_action(
     mut next_screen: ResMut<NextState<Screen>>,
     mut next_game_state: ResMut<NextState<GameState>>,
     mut button_query: InteractionQuery<&DeathAction>,
+    mut seed: ResMut<RunSeed>,
 ) {
     for (interaction, action) in &mut button_query {
         if matches!(interaction, Interaction::Pressed) {
             match action {
+                DeathAction::Retry => {
+                    next_screen.set(Screen::CharacterSelect);
+                    next_game_state.set(GameState::Running);
+                }
                 DeathAction::Menu => {
+                    seed.reroll();
                     next_screen.set(Screen::Title);
                     next_game_state.set(GameState::Running);
                 }
@@ -4,35 +4,53 @@ use bevy::{
         AccessibilityNode,
     },
     color::palettes::css::BLUE,
+    ecs::system::SystemParam,
     input::mouse::{MouseScrollUnit, MouseWheel},
     prelude::*,
+    time::TimeUpdateStrategy,
 };
 use palette::*;
 use prelude::{InteractionPalette, InteractionQuery};
 
 use crate::{
+    config::{SKIP_EXPERIENCE, SKIP_HEAL},
     game::{
         assets::{ImageAsset, ImageAssets},
+        enemy::Wave,
+        levelling::{OfferCharges, PlayerLevel, SpawnExperience},
+        player_mods::{
+            health::HealEvent,
+            stats::{PlayerStats, StatCard},
+        },
+        replay::{finish_playback, is_playing_back, GemChoice, ReplayInput, ReplayMode},
+        rng::{GameRng, RngStream},
+        spawn::player::Player,
         spell_system::{
-            storage::{RebuildWand, SpellAddPos, SpellInventory, SpellPool},
+            fusion::FusionRecipes,
+            rarity::{OfferRules, SpellRarity},
+            storage::{RebuildWand, SpellAddPos, SpellInventory, SpellPool, WandStash},
             SpellComponent,
         },
+        Damageable,
     },
     ui::*,
 };
 
-use super::GameState;
+use super::{wand_editor::OpenWandEditor, GameState, Screen};
 
 pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<Offers>();
+    app.add_systems(OnEnter(Screen::Playing), reset_offers);
     app.add_systems(OnEnter(GameState::GemSelection), gem_menu);
     app.add_systems(
         Update,
         (
+            spawn_offers.run_if(resource_changed::<Offers>),
+            update_charge_labels.run_if(resource_changed::<OfferCharges>),
             handle_gem_select_action,
-            handle_gem_back_placement_action,
-            handle_gem_front_placement_action,
-            handle_gem_discard_action,
+            handle_offer_action.run_if(not(is_playing_back)),
             handle_mouse_scroll,
+            play_back_gem_choice.run_if(is_playing_back),
         )
             .run_if(in_state(GameState::GemSelection)),
     );
@@ -44,12 +62,54 @@ enum LevelUpAction {
     Selected,
     PlaceBack,
     PlaceFront,
+    Reroll,
+    Banish,
     DiscardGem,
+    Skip,
+    EditWand,
+    TakeStatCard,
+}
+
+/// The gems currently offered, kept around so they can be rerolled or banished.
+#[derive(Resource, Default)]
+struct Offers {
+    gems: Vec<(SpellComponent, SpellRarity)>,
+    /// A passive upgrade offered next to the gems, only sometimes.
+    stat_card: Option<StatCard>,
+    /// Still waiting for a choice, e.g. while the wand editor is open.
+    open: bool,
+}
+
+fn reset_offers(mut offers: ResMut<Offers>) {
+    *offers = Offers::default();
+}
+
+/// Parent of the offered gem cards, which are respawned whenever [`Offers`] changes.
+#[derive(Component)]
+struct OfferContainer;
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+enum ChargeLabel {
+    Reroll,
+    Banish,
+}
+
+impl ChargeLabel {
+    fn text(&self, charges: &OfferCharges) -> String {
+        match self {
+            ChargeLabel::Reroll => format!("Reroll ({})", charges.rerolls),
+            ChargeLabel::Banish => format!("Banish ({})", charges.banishes),
+        }
+    }
 }
 
 #[derive(Component)]
 struct SelectedGem;
 
+/// Index of an offered gem, so the choice can be recorded and played back.
+#[derive(Component, Debug, Clone, Copy)]
+struct OfferedGem(usize);
+
 #[derive(Component, Default)]
 struct ScrollingList {
     position: f32,
@@ -59,6 +119,7 @@ struct ScrollingList {
 fn spawn_gem(
     commands: &mut Commands,
     spell_index: &SpellComponent,
+    rarity: SpellRarity,
     images: &Res<ImageAssets>,
     texture_atlas_layouts: &mut ResMut<Assets<TextureAtlasLayout>>,
 ) -> (Entity, Entity, Entity, SpellComponent) {
@@ -76,7 +137,13 @@ fn spawn_gem(
                 margin: UiRect::all(Val::Px(5.0)),
                 ..default()
             },
-            text: Text::from_section(spell_index.data.get_name(), TextStyle { ..default() }),
+            text: Text::from_section(
+                spell_index.data.get_name(),
+                TextStyle {
+                    color: rarity.color(),
+                    ..default()
+                },
+            ),
             ..default()
         })
         .id();
@@ -128,11 +195,17 @@ fn spawn_gem(
 
 fn gem_menu(
     mut commands: Commands,
-    spell_inventory: Res<SpellInventory>,
-    pool: ResMut<SpellPool>,
+    mut actions: OfferActions,
     images: Res<ImageAssets>,
     mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
 ) {
+    // coming back from the wand editor keeps the gems that were offered
+    if actions.offers.open {
+        actions.offers.set_changed();
+    } else {
+        actions.draw();
+    }
+
     let ui_container = NodeBundle {
         style: Style {
             width: Val::Percent(100.0),
@@ -183,7 +256,11 @@ fn gem_menu(
                 ..default()
             },
             text: Text::from_section(
-                "Place in front/back (container is scrollable)",
+                if actions.spell_inventory.is_full() {
+                    "Wand is full, skip or buy a slot in the shop"
+                } else {
+                    "Place in front/back (container is scrollable)"
+                },
                 TextStyle {
                     font_size: 60.,
                     ..default()
@@ -277,21 +354,6 @@ fn gem_menu(
         ..default()
     };
 
-    let continue_button = ButtonBundle {
-        style: Style {
-            width: Val::Percent(20.0),
-            height: Val::Percent(10.0),
-            justify_content: JustifyContent::Center,
-            align_items: AlignItems::Center,
-            border: UiRect::all(Val::Percent(0.5)),
-            ..default()
-        },
-        border_color: BorderColor(NODE_BACKGROUND.1),
-        border_radius: BorderRadius::all(Val::Percent(10.)),
-        background_color: BackgroundColor(NODE_BACKGROUND.0),
-        ..default()
-    };
-
     // Only ui_container has to be scoped, as everything else
     // is a child of it
     let ui_container_entity = commands
@@ -299,7 +361,7 @@ fn gem_menu(
         .insert(StateScoped(GameState::GemSelection))
         .id();
 
-    let gem_container_entity = commands.spawn(gem_container).id();
+    let gem_container_entity = commands.spawn(gem_container).insert(OfferContainer).id();
     let mid_section_container_entity = commands.spawn(mid_section_container).id();
     let scrolling_container_entity = commands.spawn(scrolling_container).id();
     let moving_panel_entity = commands
@@ -336,31 +398,84 @@ fn gem_menu(
         .insert(LevelUpAction::PlaceFront)
         .id();
     let text_place_front_button_entity = commands.spawn(text_place_front_button).id();
-    let continue_button_entity = commands
-        .spawn(continue_button)
-        .insert(InteractionPalette {
-            none: NODE_BACKGROUND,
-            hovered: BUTTON_HOVERED_BACKGROUND,
-            pressed: BUTTON_PRESSED_BACKGROUND,
-        })
-        .insert(TextBundle {
+    let action_row_entity = commands
+        .spawn(NodeBundle {
             style: Style {
-                margin: UiRect::all(Val::Px(5.0)),
+                width: Val::Percent(100.0),
+                height: Val::Percent(10.0),
+                flex_direction: FlexDirection::Row,
+                justify_content: JustifyContent::Center,
                 ..default()
             },
-            text: Text::from_section("Discard", TextStyle { ..default() }),
             ..default()
         })
-        .insert(GemDiscardButtonSound)
-        .insert(LevelUpAction::DiscardGem)
         .id();
+    for (action, label) in [
+        (LevelUpAction::Reroll, Some(ChargeLabel::Reroll)),
+        (LevelUpAction::Banish, Some(ChargeLabel::Banish)),
+        (LevelUpAction::DiscardGem, None),
+        (LevelUpAction::Skip, None),
+        (LevelUpAction::EditWand, None),
+    ] {
+        let text = match (label, action) {
+            (Some(label), _) => label.text(&actions.charges),
+            (None, LevelUpAction::EditWand) => "Edit Wand".to_string(),
+            (None, LevelUpAction::DiscardGem) => "Discard".to_string(),
+            (None, _) => "Skip (heal or XP)".to_string(),
+        };
+        let text_entity = commands
+            .spawn(TextBundle {
+                style: Style {
+                    margin: UiRect::all(Val::Px(5.0)),
+                    ..default()
+                },
+                text: Text::from_section(text, TextStyle { ..default() }),
+                ..default()
+            })
+            .id();
+        if let Some(label) = label {
+            commands.entity(text_entity).insert(label);
+        }
+        let button_entity = commands
+            .spawn(ButtonBundle {
+                style: Style {
+                    width: Val::Percent(16.0),
+                    height: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    border: UiRect::all(Val::Percent(0.5)),
+                    margin: UiRect::horizontal(Val::Px(10.0)),
+                    ..default()
+                },
+                border_color: BorderColor(NODE_BACKGROUND.1),
+                border_radius: BorderRadius::all(Val::Percent(10.)),
+                background_color: BackgroundColor(NODE_BACKGROUND.0),
+                ..default()
+            })
+            .insert(InteractionPalette {
+                none: NODE_BACKGROUND,
+                hovered: BUTTON_HOVERED_BACKGROUND,
+                pressed: BUTTON_PRESSED_BACKGROUND,
+            })
+            .insert(action)
+            .id();
+        if matches!(action, LevelUpAction::Reroll | LevelUpAction::EditWand) {
+            commands.entity(button_entity).insert(DefaultButtonSound);
+        } else {
+            commands.entity(button_entity).insert(GemDiscardButtonSound);
+        }
+        commands.entity(button_entity).push_children(&[text_entity]);
+        commands
+            .entity(action_row_entity)
+            .push_children(&[button_entity]);
+    }
 
     commands.entity(ui_container_entity).push_children(&[
         select_spell_text_entity,
         gem_container_entity,
         scroll_text_entity,
         mid_section_container_entity,
-        continue_button_entity,
+        action_row_entity,
     ]);
     commands
         .entity(mid_section_container_entity)
@@ -382,7 +497,7 @@ fn gem_menu(
     // For rending spells that the player currently has
     let layout = TextureAtlasLayout::from_grid(UVec2::splat(32), 12, 4, None, None);
     let texture_atlas_layout = texture_atlas_layouts.add(layout);
-    for spell in spell_inventory.spells.iter() {
+    for spell in actions.spell_inventory.spells.iter() {
         let spell_container = NodeBundle {
             style: Style {
                 width: Val::Px(250.),
@@ -442,10 +557,22 @@ fn gem_menu(
             .entity(spell_container_entity)
             .push_children(&[spell_image_entity, spell_name_entity]);
     }
+}
+
+fn spawn_offers(
+    mut commands: Commands,
+    offers: Res<Offers>,
+    container_query: Query<Entity, With<OfferContainer>>,
+    images: Res<ImageAssets>,
+    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+) {
+    let Ok(gem_container_entity) = container_query.get_single() else {
+        return;
+    };
+    commands.entity(gem_container_entity).despawn_descendants();
 
-    let generated_spell_table = pool.get_x_random_unique_spell_components(3);
     // For rendering the random gems on screen
-    for spell_index in generated_spell_table.iter().take(2 + 1) {
+    for (offer_index, (spell_index, rarity)) in offers.gems.iter().enumerate() {
         let select_gem_button = ButtonBundle {
             style: Style {
                 width: Val::Percent(25.0),
@@ -462,7 +589,7 @@ fn gem_menu(
                 border: UiRect::all(Val::Percent(0.5)),
                 ..default()
             },
-            border_color: BorderColor(NODE_BACKGROUND.1),
+            border_color: BorderColor(rarity.color()),
             border_radius: BorderRadius::all(Val::Percent(10.)),
             background_color: BackgroundColor(NODE_BACKGROUND.0),
             ..default()
@@ -471,6 +598,7 @@ fn gem_menu(
         let (name_entity, gem_entity, text_entity, spell) = spawn_gem(
             &mut commands,
             spell_index,
+            *rarity,
             &images,
             &mut texture_atlas_layouts,
         );
@@ -480,6 +608,7 @@ fn gem_menu(
             .insert(GemPickUpButtonSound)
             .insert(LevelUpAction::Selected)
             .insert(spell)
+            .insert(OfferedGem(offer_index))
             .id();
         commands
             .entity(gem_container_entity)
@@ -490,6 +619,62 @@ fn gem_menu(
             text_entity,
         ]);
     }
+
+    if let Some(card) = offers.stat_card {
+        commands
+            .entity(gem_container_entity)
+            .with_children(|children| {
+                children
+                    .spawn((
+                        Name::new("Stat Card"),
+                        ButtonBundle {
+                            style: Style {
+                                width: Val::Percent(25.0),
+                                height: Val::Percent(100.0),
+                                flex_direction: FlexDirection::Column,
+                                align_items: AlignItems::Center,
+                                justify_content: JustifyContent::Center,
+                                margin: UiRect::horizontal(Val::Px(10.0)),
+                                border: UiRect::all(Val::Percent(0.5)),
+                                ..default()
+                            },
+                            border_color: BorderColor(NODE_BACKGROUND.1),
+                            border_radius: BorderRadius::all(Val::Percent(10.)),
+                            background_color: BackgroundColor(NODE_BACKGROUND.0),
+                            ..default()
+                        },
+                        InteractionPalette {
+                            none: NODE_BACKGROUND,
+                            hovered: BUTTON_HOVERED_BACKGROUND,
+                            pressed: BUTTON_PRESSED_BACKGROUND,
+                        },
+                        GemPickUpButtonSound,
+                        LevelUpAction::TakeStatCard,
+                    ))
+                    .with_children(|card_children| {
+                        card_children.spawn(TextBundle::from_section(
+                            card.stat.name(),
+                            TextStyle {
+                                color: HEADER_TEXT,
+                                ..default()
+                            },
+                        ));
+                        card_children.spawn(TextBundle::from_section(
+                            card.description(),
+                            TextStyle::default(),
+                        ));
+                    });
+            });
+    }
+}
+
+fn update_charge_labels(
+    charges: Res<OfferCharges>,
+    mut label_query: Query<(&mut Text, &ChargeLabel)>,
+) {
+    for (mut text, label) in &mut label_query {
+        text.sections[0].value = label.text(&charges);
+    }
 }
 
 fn handle_gem_select_action(
@@ -519,50 +704,174 @@ fn handle_gem_select_action(
     }
 }
 
-fn handle_gem_front_placement_action(
-    mut commands: Commands,
-    mut button_query: InteractionQuery<&LevelUpAction>,
-    mut spell_inventory: ResMut<SpellInventory>,
-    mut next_gamestate: ResMut<NextState<GameState>>,
-    selected_gem_query: Query<&SpellComponent, With<SelectedGem>>,
-) {
-    for (interaction, action) in &mut button_query.iter_mut() {
-        if matches!(interaction, Interaction::Pressed) && action == &LevelUpAction::PlaceFront {
-            if let Ok(spell) = selected_gem_query.get_single() {
-                spell_inventory.push_spell(spell.clone());
-                commands.trigger(RebuildWand);
-                next_gamestate.set(GameState::Running);
+/// Everything needed to act on the offered gems, shared by the buttons and replay playback.
+#[derive(SystemParam)]
+struct OfferActions<'w, 's> {
+    commands: Commands<'w, 's>,
+    offers: ResMut<'w, Offers>,
+    pool: ResMut<'w, SpellPool>,
+    offer_rules: Res<'w, OfferRules>,
+    charges: ResMut<'w, OfferCharges>,
+    wave: Res<'w, Wave>,
+    rng: ResMut<'w, GameRng>,
+    spell_inventory: ResMut<'w, SpellInventory>,
+    stash: ResMut<'w, WandStash>,
+    replay: ResMut<'w, ReplayMode>,
+    next_gamestate: ResMut<'w, NextState<GameState>>,
+    player_query: Query<
+        'w,
+        's,
+        (
+            &'static PlayerLevel,
+            &'static Damageable,
+            &'static Transform,
+            &'static mut PlayerStats,
+        ),
+        With<Player>,
+    >,
+}
+
+impl OfferActions<'_, '_> {
+    fn draw(&mut self) {
+        let level = self
+            .player_query
+            .get_single()
+            .map_or(1, |(level, ..)| level.level);
+        self.offers.open = true;
+        let rng = self.rng.stream(RngStream::GemOffers);
+        self.offers.gems =
+            self.pool
+                .draw_offers(3, &self.offer_rules, self.wave.number(), level, true, rng);
+        self.offers.stat_card = StatCard::roll(rng);
+    }
+
+    /// Applies the choice and records it to the replay, unless it isn't possible.
+    fn apply(&mut self, choice: GemChoice) {
+        match choice {
+            GemChoice::Start(index) | GemChoice::End(index) => {
+                let Some((spell, _)) = self.offers.gems.get(index).cloned() else {
+                    return;
+                };
+                if self.spell_inventory.is_full() {
+                    return;
+                }
+                if matches!(choice, GemChoice::Start(_)) {
+                    self.spell_inventory
+                        .insert_spell(spell, SpellAddPos::Index(0));
+                } else {
+                    self.spell_inventory.push_spell(spell);
+                }
+                self.commands.trigger(RebuildWand);
+                self.offers.open = false;
+                self.next_gamestate.set(GameState::Running);
+            }
+            GemChoice::Reroll => {
+                if self.charges.rerolls == 0 {
+                    return;
+                }
+                self.charges.rerolls -= 1;
+                self.draw();
+            }
+            GemChoice::Banish(index) => {
+                if self.charges.banishes == 0 || index >= self.offers.gems.len() {
+                    return;
+                }
+                self.charges.banishes -= 1;
+                let (spell, _) = self.offers.gems.remove(index);
+                info!("Banished {} for the rest of the run", spell.data.get_name());
+                self.pool.banish(&spell);
+            }
+            GemChoice::StatCard => {
+                let Some(card) = self.offers.stat_card else {
+                    return;
+                };
+                let Ok((.., mut stats)) = self.player_query.get_single_mut() else {
+                    return;
+                };
+                info!("Took stat card: {}", card.description());
+                stats.apply(card);
+                self.offers.open = false;
+                self.next_gamestate.set(GameState::Running);
+            }
+            GemChoice::Discard => {
+                self.offers.open = false;
+                self.next_gamestate.set(GameState::Running);
+            }
+            GemChoice::Skip => {
+                // heal if there is anything to heal, experience otherwise
+                if let Ok((_, health, transform, _)) = self.player_query.get_single() {
+                    if health.health < health.max_health {
+                        self.commands.trigger(HealEvent(SKIP_HEAL));
+                    } else {
+                        self.commands.trigger(SpawnExperience {
+                            amount: SKIP_EXPERIENCE,
+                            position: transform.translation,
+                        });
+                    }
+                }
+                self.offers.open = false;
+                self.next_gamestate.set(GameState::Running);
             }
         }
+        self.replay.record_gem_choice(choice);
     }
 }
 
-fn handle_gem_back_placement_action(
-    mut commands: Commands,
+fn handle_offer_action(
     mut button_query: InteractionQuery<&LevelUpAction>,
-    mut spell_inventory: ResMut<SpellInventory>,
-    mut next_gamestate: ResMut<NextState<GameState>>,
-    selected_gem_query: Query<&SpellComponent, With<SelectedGem>>,
+    selected_gem_query: Query<&OfferedGem, With<SelectedGem>>,
+    mut actions: OfferActions,
 ) {
-    for (interaction, action) in &mut button_query.iter_mut() {
-        if matches!(interaction, Interaction::Pressed) && action == &LevelUpAction::PlaceBack {
-            if let Ok(spell) = selected_gem_query.get_single() {
-                spell_inventory.insert_spell(spell.clone(), SpellAddPos::Index(0));
-                commands.trigger(RebuildWand);
-                next_gamestate.set(GameState::Running);
-            }
+    for (interaction, action) in &mut button_query {
+        if !matches!(interaction, Interaction::Pressed) {
+            continue;
+        }
+        if action == &LevelUpAction::EditWand {
+            actions
+                .commands
+                .trigger(OpenWandEditor(GameState::GemSelection));
+            continue;
         }
+        let selected = selected_gem_query
+            .get_single()
+            .ok()
+            .map(|offered| offered.0);
+        let choice = match (action, selected) {
+            (LevelUpAction::PlaceBack, Some(index)) => GemChoice::Start(index),
+            (LevelUpAction::PlaceFront, Some(index)) => GemChoice::End(index),
+            (LevelUpAction::Banish, Some(index)) => GemChoice::Banish(index),
+            (LevelUpAction::TakeStatCard, _) => GemChoice::StatCard,
+            (LevelUpAction::Reroll, _) => GemChoice::Reroll,
+            (LevelUpAction::DiscardGem, _) => GemChoice::Discard,
+            (LevelUpAction::Skip, _) => GemChoice::Skip,
+            _ => continue,
+        };
+        actions.apply(choice);
     }
 }
 
-fn handle_gem_discard_action(
-    mut button_query: InteractionQuery<&LevelUpAction>,
-    mut next_gamestate: ResMut<NextState<GameState>>,
+/// Makes the same choices the player made when the replay was recorded.
+fn play_back_gem_choice(
+    mut actions: OfferActions,
+    recipes: Res<FusionRecipes>,
+    mut time_strategy: ResMut<TimeUpdateStrategy>,
 ) {
-    for (interaction, action) in &mut button_query.iter_mut() {
-        if matches!(interaction, Interaction::Pressed) && action == &LevelUpAction::DiscardGem {
-            next_gamestate.set(GameState::Running);
+    match actions.replay.next_input() {
+        Some(ReplayInput::GemChoice(choice)) => actions.apply(choice),
+        Some(ReplayInput::WandEdit(edit)) => {
+            actions
+                .spell_inventory
+                .apply_edit(&mut actions.stash, &recipes, edit);
+            actions.commands.trigger(RebuildWand);
+        }
+        Some(input) => {
+            warn!(
+                "Replay is out of sync, expected a gem choice but got {:?}",
+                input
+            );
+            finish_playback(&mut actions.replay, &mut time_strategy);
         }
+        None => finish_playback(&mut actions.replay, &mut time_strategy),
     }
 }
 
@@ -0,0 +1,118 @@
+//! The countdown banner between waves, showing what the next wave brings.
+
+use bevy::prelude::*;
+
+use super::{wand_editor::OpenWandEditor, GameState};
+use crate::{
+    game::{
+        enemy::{Wave, WaveState},
+        replay::ReplayMode,
+    },
+    ui::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<IntermissionAction>();
+    app.add_systems(
+        Update,
+        (handle_intermission_action, update_intermission_banner)
+            .chain()
+            .run_if(in_state(GameState::Running)),
+    );
+}
+
+#[derive(Component)]
+struct IntermissionBanner {
+    seconds: u32,
+}
+
+/// The header holding the banner's countdown text.
+#[derive(Component)]
+struct IntermissionCountdown;
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum IntermissionAction {
+    EditWand,
+}
+
+/// Spawns the banner when the intermission starts, keeps its countdown up to date,
+/// and removes it once the wave starts.
+fn update_intermission_banner(
+    mut commands: Commands,
+    wave_state: Res<WaveState>,
+    wave: Res<Wave>,
+    replay: Res<ReplayMode>,
+    mut banner_query: Query<(Entity, &mut IntermissionBanner)>,
+    countdown_query: Query<&Children, With<IntermissionCountdown>>,
+    mut text_query: Query<&mut Text>,
+) {
+    let seconds = match wave_state.as_ref() {
+        WaveState::Intermission(timer) => timer.remaining_secs().ceil() as u32,
+        WaveState::Active => {
+            for (entity, _) in &banner_query {
+                commands.entity(entity).despawn_recursive();
+            }
+            return;
+        }
+    };
+    let countdown = |seconds: u32| format!("Wave {} in {}", wave.number(), seconds);
+
+    let Ok((_, mut banner)) = banner_query.get_single_mut() else {
+        spawn_intermission_banner(&mut commands, countdown(seconds), &wave, &replay, seconds);
+        return;
+    };
+    if banner.seconds == seconds {
+        return;
+    }
+    banner.seconds = seconds;
+    for children in &countdown_query {
+        for &child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut(child) {
+                text.sections[0].value = countdown(seconds);
+            }
+        }
+    }
+}
+
+fn spawn_intermission_banner(
+    commands: &mut Commands,
+    countdown: String,
+    wave: &Wave,
+    replay: &ReplayMode,
+    seconds: u32,
+) {
+    commands
+        .ui_root()
+        .insert((
+            Name::new("Intermission Banner"),
+            IntermissionBanner { seconds },
+            // leaving the running state takes the banner down, it comes back on return
+            StateScoped(GameState::Running),
+        ))
+        .with_children(|children| {
+            children.header(countdown).insert(IntermissionCountdown);
+            children.label(wave.definition().composition());
+            // edits would put a replay out of sync
+            if !matches!(replay, ReplayMode::Playback(_)) {
+                children
+                    .button("Edit Wand")
+                    .insert(IntermissionAction::EditWand);
+            }
+        });
+}
+
+fn handle_intermission_action(
+    mut commands: Commands,
+    mut button_query: InteractionQuery<&IntermissionAction>,
+) {
+    for (interaction, action) in &mut button_query {
+        if matches!(interaction, Interaction::Pressed) {
+            match action {
+                IntermissionAction::EditWand => {
+                    commands.trigger(OpenWandEditor(GameState::Running));
+                }
+            }
+        }
+    }
+}
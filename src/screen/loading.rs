@@ -1,11 +1,14 @@
 //! A loading screen during which game assets are loaded.
 //! This reduces stuttering, especially for audio on WASM.
 
-use bevy::prelude::*;
+use bevy::{asset::LoadState, prelude::*};
 
 use super::Screen;
 use crate::game::assets::particles::ParticleAssets;
 use crate::game::assets::spell_gfx::SpellGFXAssets;
+use crate::game::characters::{CharacterRosterHandle, CHARACTERS_PATH};
+use crate::game::spell_system::fusion::{FusionRecipeBookHandle, FUSION_RECIPES_PATH};
+use crate::game::waves::{WaveScheduleHandle, WAVE_SCHEDULE_PATH};
 use crate::{
     game::assets::{ImageAssets, SfxAssets, SoundtrackAssets},
     ui::prelude::*,
@@ -31,6 +34,11 @@ fn enter_loading(mut commands: Commands, asset_server: Res<AssetServer>) {
     commands.insert_resource(SoundtrackAssets::new(&asset_server));
     commands.insert_resource(SpellGFXAssets::new(&asset_server));
     commands.insert_resource(ParticleAssets::new(&asset_server));
+    commands.insert_resource(WaveScheduleHandle(asset_server.load(WAVE_SCHEDULE_PATH)));
+    commands.insert_resource(CharacterRosterHandle(asset_server.load(CHARACTERS_PATH)));
+    commands.insert_resource(FusionRecipeBookHandle(
+        asset_server.load(FUSION_RECIPES_PATH),
+    ));
 }
 
 fn check_all_loaded(
@@ -41,13 +49,30 @@ fn check_all_loaded(
     sfxs: Res<SfxAssets>,
     soundtracks: Res<SoundtrackAssets>,
     spellgfx: Res<SpellGFXAssets>,
+    wave_schedule: Res<WaveScheduleHandle>,
+    character_roster: Res<CharacterRosterHandle>,
+    fusion_recipes: Res<FusionRecipeBookHandle>,
+    asset_server: Res<AssetServer>,
     mut next_screen: ResMut<NextState<Screen>>,
     mut next_load_state: ResMut<NextState<LoadingState>>,
 ) {
     let all_loaded = images.all_loaded(&image_assets)
         && sfxs.all_loaded(&audio_assets)
         && soundtracks.all_loaded(&audio_assets)
-        && spellgfx.all_loaded(&image_assets);
+        && spellgfx.all_loaded(&image_assets)
+        // a broken schedule, roster or recipe book falls back to the defaults instead of blocking the game
+        && matches!(
+            asset_server.load_state(&wave_schedule.0),
+            LoadState::Loaded | LoadState::Failed(_)
+        )
+        && matches!(
+            asset_server.load_state(&character_roster.0),
+            LoadState::Loaded | LoadState::Failed(_)
+        )
+        && matches!(
+            asset_server.load_state(&fusion_recipes.0),
+            LoadState::Loaded | LoadState::Failed(_)
+        );
     if all_loaded {
         next_screen.set(Screen::Title);
         next_load_state.set(LoadingState::Loaded);
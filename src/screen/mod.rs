@@ -1,14 +1,19 @@
 //! The game's main screen states and transitions between them.
 
+mod character_select;
 mod credits;
 mod death;
 mod fullscreen;
 mod gem_selection;
+mod intermission;
 mod loading;
 mod pause;
 mod playing;
+mod settings;
+mod shop;
 mod splash;
 mod title;
+mod wand_editor;
 
 use bevy::prelude::*;
 
@@ -21,10 +26,15 @@ pub(super) fn plugin(app: &mut App) {
         splash::plugin,
         loading::plugin,
         title::plugin,
+        character_select::plugin,
         credits::plugin,
+        settings::plugin,
         playing::plugin,
         pause::plugin,
         gem_selection::plugin,
+        shop::plugin,
+        intermission::plugin,
+        wand_editor::plugin,
         death::plugin,
         fullscreen::plugin,
     ));
@@ -37,7 +47,9 @@ pub enum Screen {
     Splash,
     Loading,
     Title,
+    CharacterSelect,
     Credits,
+    Settings,
     Playing,
 }
 
@@ -49,5 +61,7 @@ pub enum GameState {
     Running,
     Paused,
     GemSelection,
+    Shop,
+    WandEditor,
     Death,
 }
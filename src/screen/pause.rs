@@ -2,7 +2,10 @@
 //! Runs if player presses "escape" on keyboard
 //! TODO: Add ability for controller players to use this?
 
-use super::{GameState, Screen};
+use super::{settings::setting_toggles, wand_editor::OpenWandEditor, GameState, Screen};
+use crate::game::replay::ReplayMode;
+use crate::game::rng::RunSeed;
+use crate::game::settings::Settings;
 use crate::ui::prelude::*;
 use bevy::prelude::*;
 
@@ -23,15 +26,21 @@ pub(super) fn plugin(app: &mut App) {
 #[reflect(Component)]
 enum PauseAction {
     Continue,
+    EditWand,
     Menu,
 }
 
-fn enter_pause(mut commands: Commands) {
+fn enter_pause(mut commands: Commands, replay: Res<ReplayMode>, settings: Res<Settings>) {
     commands
         .ui_root()
         .insert(StateScoped(GameState::Paused))
         .with_children(|children| {
             children.button("Continue").insert(PauseAction::Continue);
+            // edits would put a replay out of sync
+            if !matches!(*replay, ReplayMode::Playback(_)) {
+                children.button("Edit Wand").insert(PauseAction::EditWand);
+            }
+            setting_toggles(children, &settings);
             children
                 .button("Quit to Main Menu")
                 .insert(PauseAction::Menu);
@@ -39,9 +48,11 @@ fn enter_pause(mut commands: Commands) {
 }
 
 fn handle_pause_action(
+    mut commands: Commands,
     mut next_screen: ResMut<NextState<Screen>>,
     mut next_pause_state: ResMut<NextState<GameState>>,
     mut button_query: InteractionQuery<&PauseAction>,
+    mut seed: ResMut<RunSeed>,
 ) {
     for (interaction, action) in &mut button_query {
         if matches!(interaction, Interaction::Pressed) {
@@ -49,7 +60,11 @@ fn handle_pause_action(
                 PauseAction::Continue => {
                     next_pause_state.set(GameState::Running);
                 }
+                PauseAction::EditWand => {
+                    commands.trigger(OpenWandEditor(GameState::Paused));
+                }
                 PauseAction::Menu => {
+                    seed.reroll();
                     next_screen.set(Screen::Title);
                     next_pause_state.set(GameState::Running);
                 }
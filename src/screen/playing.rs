@@ -1,6 +1,6 @@
 //! The screen state for the main game loop.
 
-use bevy::color::palettes::css::{DARK_RED, RED};
+use bevy::color::palettes::css::{DARK_RED, LIGHT_BLUE, RED};
 use bevy::color::palettes::tailwind::GREEN_400;
 use bevy::{input::common_conditions::input_just_pressed, prelude::*};
 
@@ -8,7 +8,9 @@ use super::{GameState, Screen};
 use crate::game::{
     audio::soundtrack::Soundtrack,
     enemy::{StartWave, Wave, WaveState, WaveText},
+    gold::GoldText,
     levelling::{compute_next_level, LevelText, PlayerLevel},
+    player_mods::dash::Dash,
     spawn::map::SpawnLevel,
     spawn::player::Player,
     Damageable,
@@ -23,6 +25,7 @@ pub(super) fn plugin(app: &mut App) {
         (
             update_level_bar,
             update_health_bar,
+            update_dash_bar,
             update_wave_bar.run_if(resource_equals(WaveState::Active)),
         )
             .run_if(in_state(Screen::Playing)),
@@ -51,6 +54,9 @@ struct LevelBar;
 #[derive(Component)]
 struct HealthBar;
 
+#[derive(Component)]
+struct DashBar;
+
 #[derive(Component)]
 struct WaveBar;
 
@@ -88,6 +94,17 @@ fn spawn_playing_gui(mut commands: Commands) {
         ..default()
     };
 
+    // fills back up while the dash cools down
+    let dash_bar = NodeBundle {
+        style: Style {
+            width: Val::Percent(100.),
+            height: Val::Percent(1.),
+            ..default()
+        },
+        background_color: BackgroundColor(Color::from(LIGHT_BLUE)),
+        ..default()
+    };
+
     let level_text = TextBundle {
         style: Style {
             width: Val::Percent(50.),
@@ -105,6 +122,22 @@ fn spawn_playing_gui(mut commands: Commands) {
         ..default()
     };
 
+    let gold_text = TextBundle {
+        style: Style {
+            margin: UiRect::horizontal(Val::Percent(2.0)),
+            ..default()
+        },
+        text: Text::from_section(
+            "Gold 0",
+            TextStyle {
+                font_size: *UiScale(40.),
+                color: Color::srgb(1.0, 0.8, 0.2),
+                ..default()
+            },
+        ),
+        ..default()
+    };
+
     // Wave Banner
 
     let wave_banner = NodeBundle {
@@ -160,7 +193,13 @@ fn spawn_playing_gui(mut commands: Commands) {
         .insert(HealthBar)
         .insert(Name::new("Health Bar"))
         .id();
+    let dash_bar_entity = commands
+        .spawn(dash_bar)
+        .insert(DashBar)
+        .insert(Name::new("Dash Bar"))
+        .id();
     let level_text_entity = commands.spawn(level_text).insert(LevelText).id();
+    let gold_text_entity = commands.spawn(gold_text).insert(GoldText).id();
 
     let wave_banner_entity = commands.spawn(wave_banner).id();
     let wave_bar_entity = commands
@@ -176,7 +215,9 @@ fn spawn_playing_gui(mut commands: Commands) {
     commands.entity(ui_container_entity).push_children(&[
         level_bar_entity,
         health_bar_entity,
+        dash_bar_entity,
         level_text_entity,
+        gold_text_entity,
         wave_banner_entity,
     ]);
 }
@@ -191,7 +232,7 @@ fn update_level_bar(
             let percent_fill = ((total_exp_in_level as f32 - player_level.exp_to_level_up as f32)
                 / total_exp_in_level as f32)
                 * 100.;
-            style.width = Val::Percent(percent_fill as f32);
+            style.width = Val::Percent(percent_fill);
         };
     }
 }
@@ -208,6 +249,17 @@ fn update_health_bar(
     }
 }
 
+fn update_dash_bar(
+    mut dash_bar_query: Query<&mut Style, With<DashBar>>,
+    dash_query: Query<&Dash, With<Player>>,
+) {
+    for mut style in &mut dash_bar_query {
+        if let Ok(dash) = dash_query.get_single() {
+            style.width = Val::Percent(dash.cooldown.fraction() * 100.);
+        };
+    }
+}
+
 fn update_wave_bar(mut wave_bar_query: Query<&mut Style, With<WaveBar>>, wave: Res<Wave>) {
     for mut style in &mut wave_bar_query {
         style.width = Val::Percent(wave.timer.fraction_remaining() * 100.);
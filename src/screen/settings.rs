@@ -0,0 +1,106 @@
+//! A settings screen that can be accessed from the title screen, the same toggles
+//! are also in the pause menu.
+
+use bevy::prelude::*;
+
+use super::Screen;
+use crate::{game::settings::Settings, ui::prelude::*};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Settings), enter_settings);
+
+    app.register_type::<SettingsAction>();
+    app.register_type::<SettingToggle>();
+    app.add_systems(
+        Update,
+        handle_settings_action.run_if(in_state(Screen::Settings)),
+    );
+    // toggles can be on more than one screen
+    app.add_systems(Update, handle_setting_toggles);
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum SettingsAction {
+    Back,
+}
+
+/// A button flipping one of the [`Settings`].
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+pub(super) enum SettingToggle {
+    DamageNumbers,
+    HealthBars,
+}
+
+impl SettingToggle {
+    const ALL: [SettingToggle; 2] = [SettingToggle::DamageNumbers, SettingToggle::HealthBars];
+
+    fn value(self, settings: &mut Settings) -> &mut bool {
+        match self {
+            SettingToggle::DamageNumbers => &mut settings.damage_numbers,
+            SettingToggle::HealthBars => &mut settings.health_bars,
+        }
+    }
+
+    fn text(self, enabled: bool) -> String {
+        let name = match self {
+            SettingToggle::DamageNumbers => "Damage Numbers",
+            SettingToggle::HealthBars => "Health Bars",
+        };
+        format!("{}: {}", name, if enabled { "On" } else { "Off" })
+    }
+}
+
+/// Spawns a button for every setting.
+pub(super) fn setting_toggles(children: &mut ChildBuilder, settings: &Settings) {
+    let mut settings = settings.clone();
+    for toggle in SettingToggle::ALL {
+        let enabled = *toggle.value(&mut settings);
+        children.button(toggle.text(enabled)).insert(toggle);
+    }
+}
+
+fn enter_settings(mut commands: Commands, settings: Res<Settings>) {
+    commands
+        .ui_root()
+        .insert(StateScoped(Screen::Settings))
+        .with_children(|children| {
+            children.header("Settings");
+            setting_toggles(children, &settings);
+            children.button("Back").insert(SettingsAction::Back);
+        });
+}
+
+fn handle_settings_action(
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut button_query: InteractionQuery<&SettingsAction>,
+) {
+    for (interaction, action) in &mut button_query {
+        if matches!(interaction, Interaction::Pressed) {
+            match action {
+                SettingsAction::Back => next_screen.set(Screen::Title),
+            }
+        }
+    }
+}
+
+fn handle_setting_toggles(
+    mut settings: ResMut<Settings>,
+    button_query: InteractionQuery<(&SettingToggle, &Children)>,
+    mut text_query: Query<&mut Text>,
+) {
+    for (interaction, (toggle, children)) in &button_query {
+        if !matches!(interaction, Interaction::Pressed) {
+            continue;
+        }
+        let value = toggle.value(&mut settings);
+        *value = !*value;
+        let label = toggle.text(*value);
+        for &child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut(child) {
+                text.sections[0].value = label.clone();
+            }
+        }
+    }
+}
@@ -0,0 +1,330 @@
+//! The shop between waves, where gold buys gems, wand slots (up to [`MAX_WAND_SLOTS`]) and heals.
+
+use bevy::{
+    ecs::system::{EntityCommands, SystemParam},
+    prelude::*,
+    time::TimeUpdateStrategy,
+    ui::Val::*,
+};
+
+use super::GameState;
+use crate::{
+    config::{
+        GEM_PRICES, HEAL_PRICE, MAX_WAND_SLOTS, REMOVE_GEM_PRICE, SHOP_GEM_COUNT, SHOP_HEAL,
+        SHOP_PRICE_PER_WAVE, WAND_SLOT_PRICE,
+    },
+    game::{
+        enemy::Wave,
+        gold::Gold,
+        levelling::PlayerLevel,
+        player_mods::health::HealEvent,
+        replay::{finish_playback, is_playing_back, ReplayInput, ReplayMode, ShopChoice},
+        rng::{GameRng, RngStream},
+        spawn::player::Player,
+        spell_system::{
+            rarity::{OfferRules, SpellRarity},
+            storage::{RebuildWand, SpellInventory, SpellPool},
+            SpellComponent,
+        },
+        Damageable,
+    },
+    ui::{palette::*, prelude::*, DefaultButtonSound},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<ShopStock>();
+    app.add_systems(OnEnter(GameState::Shop), stock_shop);
+    app.add_systems(
+        Update,
+        (
+            handle_shop_action.run_if(not(is_playing_back)),
+            play_back_shop_choice.run_if(is_playing_back),
+            spawn_shop_menu.run_if(resource_changed::<ShopStock>),
+        )
+            .chain()
+            .run_if(in_state(GameState::Shop)),
+    );
+}
+
+/// The gems for sale, restocked every time the shop opens.
+#[derive(Resource, Default)]
+struct ShopStock {
+    gems: Vec<(SpellComponent, SpellRarity)>,
+}
+
+#[derive(Component)]
+struct ShopMenu;
+
+#[derive(Component, Debug, Clone, Copy)]
+struct ShopButton(ShopChoice);
+
+/// Scales a base price with the wave number.
+fn wave_price(base: u32, wave: u32) -> u32 {
+    (base as f32 * (1. + SHOP_PRICE_PER_WAVE * wave.saturating_sub(1) as f32)).round() as u32
+}
+
+fn stock_shop(
+    mut stock: ResMut<ShopStock>,
+    mut pool: ResMut<SpellPool>,
+    offer_rules: Res<OfferRules>,
+    wave: Res<Wave>,
+    mut rng: ResMut<GameRng>,
+    level_query: Query<&PlayerLevel, With<Player>>,
+) {
+    let level = level_query.get_single().map_or(1, |level| level.level);
+    stock.gems = pool.draw_offers(
+        SHOP_GEM_COUNT,
+        &offer_rules,
+        wave.number(),
+        level,
+        false,
+        rng.stream(RngStream::Shop),
+    );
+}
+
+/// Everything needed to act in the shop, shared by the buttons and replay playback.
+#[derive(SystemParam)]
+struct ShopActions<'w, 's> {
+    commands: Commands<'w, 's>,
+    stock: ResMut<'w, ShopStock>,
+    spell_inventory: ResMut<'w, SpellInventory>,
+    wave: Res<'w, Wave>,
+    replay: ResMut<'w, ReplayMode>,
+    next_gamestate: ResMut<'w, NextState<GameState>>,
+    player_query: Query<'w, 's, (&'static mut Gold, &'static Damageable), With<Player>>,
+}
+
+impl ShopActions<'_, '_> {
+    fn price(&self, choice: ShopChoice) -> u32 {
+        let base = match choice {
+            ShopChoice::BuyGem(index) => self
+                .stock
+                .gems
+                .get(index)
+                .map_or(0, |(_, rarity)| GEM_PRICES[*rarity as usize]),
+            ShopChoice::BuySlot => WAND_SLOT_PRICE,
+            ShopChoice::BuyHeal => HEAL_PRICE,
+            ShopChoice::RemoveGem(_) => REMOVE_GEM_PRICE,
+            ShopChoice::Leave => 0,
+        };
+        wave_price(base, self.wave.number())
+    }
+
+    /// Applies the choice and records it to the replay, unless it can't be afforded.
+    fn apply(&mut self, choice: ShopChoice) {
+        let price = self.price(choice);
+        let Ok((mut gold, health)) = self.player_query.get_single_mut() else {
+            return;
+        };
+        if gold.0 < price {
+            return;
+        }
+
+        match choice {
+            ShopChoice::BuyGem(index) => {
+                if self.spell_inventory.is_full() || index >= self.stock.gems.len() {
+                    return;
+                }
+                let (spell, _) = self.stock.gems.remove(index);
+                self.spell_inventory.push_spell(spell);
+                self.commands.trigger(RebuildWand);
+            }
+            ShopChoice::BuySlot => {
+                if self.spell_inventory.slots >= MAX_WAND_SLOTS {
+                    return;
+                }
+                self.spell_inventory.slots += 1;
+            }
+            ShopChoice::BuyHeal => {
+                if health.health >= health.max_health {
+                    return;
+                }
+                self.commands.trigger(HealEvent(SHOP_HEAL));
+            }
+            ShopChoice::RemoveGem(index) => {
+                // keep at least one gem, so the wand can still shoot
+                if index >= self.spell_inventory.spells.len()
+                    || self.spell_inventory.spells.len() <= 1
+                {
+                    return;
+                }
+                self.spell_inventory.spells.remove(index);
+                self.commands.trigger(RebuildWand);
+            }
+            ShopChoice::Leave => self.next_gamestate.set(GameState::Running),
+        }
+
+        gold.0 -= price;
+        // redraw the menu with the new prices and wand
+        self.stock.set_changed();
+        self.replay.record_shop_choice(choice);
+    }
+}
+
+fn handle_shop_action(mut button_query: InteractionQuery<&ShopButton>, mut actions: ShopActions) {
+    for (interaction, button) in &mut button_query {
+        if matches!(interaction, Interaction::Pressed) {
+            actions.apply(button.0);
+        }
+    }
+}
+
+/// Makes the same choices the player made when the replay was recorded.
+fn play_back_shop_choice(mut actions: ShopActions, mut time_strategy: ResMut<TimeUpdateStrategy>) {
+    match actions.replay.next_input() {
+        Some(ReplayInput::ShopChoice(choice)) => actions.apply(choice),
+        Some(input) => {
+            warn!(
+                "Replay is out of sync, expected a shop choice but got {:?}",
+                input
+            );
+            finish_playback(&mut actions.replay, &mut time_strategy);
+        }
+        None => finish_playback(&mut actions.replay, &mut time_strategy),
+    }
+}
+
+fn spawn_shop_menu(
+    mut commands: Commands,
+    stock: Res<ShopStock>,
+    spell_inventory: Res<SpellInventory>,
+    wave: Res<Wave>,
+    gold_query: Query<&Gold, With<Player>>,
+    menu_query: Query<Entity, With<ShopMenu>>,
+) {
+    for menu in &menu_query {
+        commands.entity(menu).despawn_recursive();
+    }
+    let gold = gold_query.get_single().map_or(0, |gold| gold.0);
+    let price = |base: u32| wave_price(base, wave.number());
+
+    commands
+        .ui_root()
+        .insert((ShopMenu, StateScoped(GameState::Shop)))
+        .insert(BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)))
+        .with_children(|children| {
+            children.header("Shop");
+            children.label(format!("Gold: {}", gold));
+
+            shop_row(children).with_children(|children| {
+                for (index, (spell, rarity)) in stock.gems.iter().enumerate() {
+                    let base = GEM_PRICES[*rarity as usize];
+                    shop_button(
+                        children,
+                        format!(
+                            "{}\n{} - {}g",
+                            spell.data.get_name(),
+                            rarity.name(),
+                            price(base)
+                        ),
+                        ShopChoice::BuyGem(index),
+                    )
+                    .insert((
+                        BorderColor(rarity.color()),
+                        InteractionPalette {
+                            none: (NODE_BACKGROUND.0, rarity.color()),
+                            hovered: (BUTTON_HOVERED_BACKGROUND.0, rarity.color()),
+                            pressed: (BUTTON_PRESSED_BACKGROUND.0, rarity.color()),
+                        },
+                    ));
+                }
+            });
+
+            shop_row(children).with_children(|children| {
+                // slots are sold out once the wand has the most it can have
+                if spell_inventory.slots < MAX_WAND_SLOTS {
+                    shop_button(
+                        children,
+                        format!(
+                            "Wand Slot ({}/{}) - {}g",
+                            spell_inventory.spells.len(),
+                            spell_inventory.slots,
+                            price(WAND_SLOT_PRICE)
+                        ),
+                        ShopChoice::BuySlot,
+                    );
+                }
+                shop_button(
+                    children,
+                    format!("Heal - {}g", price(HEAL_PRICE)),
+                    ShopChoice::BuyHeal,
+                );
+            });
+
+            children.label(format!(
+                "Remove a gem from the wand - {}g",
+                price(REMOVE_GEM_PRICE)
+            ));
+            shop_row(children).with_children(|children| {
+                for (index, spell) in spell_inventory.spells.iter().enumerate() {
+                    shop_button(
+                        children,
+                        spell.data.get_name(),
+                        ShopChoice::RemoveGem(index),
+                    );
+                }
+            });
+
+            children
+                .button("Next Wave")
+                .insert(ShopButton(ShopChoice::Leave));
+        });
+}
+
+fn shop_row<'a>(children: &'a mut ChildBuilder) -> EntityCommands<'a> {
+    children.spawn(NodeBundle {
+        style: Style {
+            width: Percent(90.0),
+            flex_direction: FlexDirection::Row,
+            flex_wrap: FlexWrap::Wrap,
+            justify_content: JustifyContent::Center,
+            column_gap: Px(10.0),
+            row_gap: Px(10.0),
+            ..default()
+        },
+        ..default()
+    })
+}
+
+/// A smaller version of the button widget, so a whole shelf fits on screen.
+fn shop_button<'a>(
+    children: &'a mut ChildBuilder,
+    text: impl Into<String>,
+    choice: ShopChoice,
+) -> EntityCommands<'a> {
+    let mut entity = children.spawn((
+        Name::new("Shop Button"),
+        ButtonBundle {
+            style: Style {
+                min_width: Px(160.0),
+                padding: UiRect::all(Px(10.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                border: UiRect::all(Px(3.0)),
+                ..default()
+            },
+            border_color: BorderColor(NODE_BACKGROUND.1),
+            border_radius: BorderRadius::all(Val::Percent(10.)),
+            background_color: BackgroundColor(NODE_BACKGROUND.0),
+            ..default()
+        },
+        InteractionPalette {
+            none: NODE_BACKGROUND,
+            hovered: BUTTON_HOVERED_BACKGROUND,
+            pressed: BUTTON_PRESSED_BACKGROUND,
+        },
+        DefaultButtonSound,
+        ShopButton(choice),
+    ));
+    entity.with_children(|children| {
+        children.spawn(TextBundle::from_section(
+            text,
+            TextStyle {
+                font_size: 30.,
+                color: BUTTON_TEXT,
+                ..default()
+            },
+        ));
+    });
+    entity
+}
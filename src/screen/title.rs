@@ -2,8 +2,16 @@
 
 use bevy::prelude::*;
 
-use super::{GameState, Screen};
+#[cfg(not(target_family = "wasm"))]
+use super::GameState;
+use super::Screen;
 use crate::game::audio::soundtrack::SoundtrackMarker;
+use crate::game::rng::RunSeed;
+#[cfg(not(target_family = "wasm"))]
+use crate::game::{
+    characters::SelectedCharacter,
+    replay::{Replay, ReplayMode, REPLAY_PATH},
+};
 use crate::{game::audio::soundtrack::Soundtrack, ui::prelude::*};
 
 pub(super) fn plugin(app: &mut App) {
@@ -14,20 +22,40 @@ pub(super) fn plugin(app: &mut App) {
     );
 
     app.register_type::<TitleAction>();
-    app.add_systems(Update, handle_title_action.run_if(in_state(Screen::Title)));
+    app.add_systems(
+        Update,
+        (
+            handle_title_action,
+            update_seed_label.run_if(resource_changed::<RunSeed>),
+        )
+            .run_if(in_state(Screen::Title)),
+    );
 }
 
 #[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
 #[reflect(Component)]
 enum TitleAction {
     Play,
+    NewSeed,
+    /// Plays back the replay of the last run.
+    #[cfg(not(target_family = "wasm"))]
+    WatchReplay,
+    Settings,
     Credits,
     /// Exit doesn't work well with embedded applications.
     #[cfg(not(target_family = "wasm"))]
     Exit,
 }
 
-fn enter_title(mut commands: Commands, query: Query<Entity, With<SoundtrackMarker>>) {
+/// Marks the label showing the seed the next run will be played with.
+#[derive(Component)]
+struct SeedLabel;
+
+fn enter_title(
+    mut commands: Commands,
+    query: Query<Entity, With<SoundtrackMarker>>,
+    seed: Res<RunSeed>,
+) {
     if query.is_empty() {
         commands.trigger(Soundtrack::MainMenu);
     }
@@ -35,7 +63,14 @@ fn enter_title(mut commands: Commands, query: Query<Entity, With<SoundtrackMarke
         .ui_root()
         .insert(StateScoped(Screen::Title))
         .with_children(|children| {
+            children.label(format!("Seed: {}", *seed)).insert(SeedLabel);
             children.button("Play").insert(TitleAction::Play);
+            children.button("New Seed").insert(TitleAction::NewSeed);
+            #[cfg(not(target_family = "wasm"))]
+            children
+                .button("Watch Replay")
+                .insert(TitleAction::WatchReplay);
+            children.button("Settings").insert(TitleAction::Settings);
             children.button("Credits").insert(TitleAction::Credits);
 
             #[cfg(not(target_family = "wasm"))]
@@ -50,17 +85,32 @@ fn exit_title(mut commands: Commands) {
 
 fn handle_title_action(
     mut next_screen: ResMut<NextState<Screen>>,
-    mut next_pause_state: ResMut<NextState<GameState>>,
+    #[cfg(not(target_family = "wasm"))] mut next_pause_state: ResMut<NextState<GameState>>,
     mut button_query: InteractionQuery<&TitleAction>,
+    mut seed: ResMut<RunSeed>,
+    #[cfg(not(target_family = "wasm"))] mut replay: ResMut<ReplayMode>,
+    #[cfg(not(target_family = "wasm"))] mut character: ResMut<SelectedCharacter>,
     #[cfg(not(target_family = "wasm"))] mut app_exit: EventWriter<AppExit>,
 ) {
     for (interaction, action) in &mut button_query {
         if matches!(interaction, Interaction::Pressed) {
             match action {
-                TitleAction::Play => {
-                    next_screen.set(Screen::Playing);
-                    next_pause_state.set(GameState::Running);
-                }
+                TitleAction::Play => next_screen.set(Screen::CharacterSelect),
+                TitleAction::NewSeed => seed.reroll(),
+                #[cfg(not(target_family = "wasm"))]
+                TitleAction::WatchReplay => match Replay::load(REPLAY_PATH) {
+                    Ok(recorded) => {
+                        seed.0 = recorded.seed;
+                        if let Some(name) = &recorded.character {
+                            character.0 = name.clone();
+                        }
+                        *replay = ReplayMode::playback(recorded);
+                        next_screen.set(Screen::Playing);
+                        next_pause_state.set(GameState::Running);
+                    }
+                    Err(e) => warn!("Failed to load replay {}: {}", REPLAY_PATH, e),
+                },
+                TitleAction::Settings => next_screen.set(Screen::Settings),
                 TitleAction::Credits => next_screen.set(Screen::Credits),
 
                 #[cfg(not(target_family = "wasm"))]
@@ -71,3 +121,17 @@ fn handle_title_action(
         }
     }
 }
+
+fn update_seed_label(
+    seed: Res<RunSeed>,
+    label_query: Query<&Children, With<SeedLabel>>,
+    mut text_query: Query<&mut Text>,
+) {
+    for children in &label_query {
+        for &child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut(child) {
+                text.sections[0].value = format!("Seed: {}", *seed);
+            }
+        }
+    }
+}
@@ -0,0 +1,524 @@
+//! The wand editor, where the gems already in the wand can be dragged around.
+//! Dropping a gem on another one swaps them, dropping it between two gems moves it there,
+//! and dropping it on the stash takes it out of the wand until it's dragged back in.
+//! Owned gems can also be fused here, following the [`FusionRecipes`].
+
+use bevy::{
+    color::palettes::css::BLUE, ecs::system::SystemParam, prelude::*, ui::RelativeCursorPosition,
+    ui::Val::*,
+};
+
+use super::GameState;
+use crate::{
+    config::STASH_SLOTS,
+    game::{
+        assets::{ImageAsset, ImageAssets},
+        replay::ReplayMode,
+        spell_system::{
+            fusion::FusionRecipes,
+            storage::{RebuildWand, SpellInventory, WandEdit, WandStash},
+            SpellComponent,
+        },
+    },
+    ui::{palette::*, prelude::*, DefaultButtonSound},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<WandEditorReturn>();
+    app.init_resource::<Dragging>();
+    app.register_type::<EditorAction>();
+    app.observe(open_wand_editor);
+    app.add_systems(OnEnter(GameState::WandEditor), refresh_editor);
+    app.add_systems(
+        Update,
+        (
+            start_drag,
+            end_drag,
+            handle_editor_action,
+            spawn_editor_menu
+                .run_if(resource_changed::<SpellInventory>.or_else(resource_changed::<WandStash>)),
+        )
+            .chain()
+            .run_if(in_state(GameState::WandEditor)),
+    );
+}
+
+/// Opens the wand editor, which goes back to the given state once it's closed.
+#[derive(Event, Debug)]
+pub struct OpenWandEditor(pub GameState);
+
+#[derive(Resource, Debug, Default)]
+struct WandEditorReturn(GameState);
+
+/// The slot a gem is being dragged from.
+#[derive(Resource, Debug, Default)]
+struct Dragging(Option<EditorSlot>);
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+enum EditorSlot {
+    /// A slot of the wand, which might be empty.
+    Wand(usize),
+    /// The gap in front of the wand slot with this index.
+    Gap(usize),
+    Stash(usize),
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum EditorAction {
+    /// Fuses the gems of the recipe with this index.
+    Fuse(usize),
+    Done,
+}
+
+#[derive(Component)]
+struct EditorMenu;
+
+fn open_wand_editor(
+    trigger: Trigger<OpenWandEditor>,
+    mut return_state: ResMut<WandEditorReturn>,
+    mut next_gamestate: ResMut<NextState<GameState>>,
+) {
+    return_state.0 = trigger.event().0;
+    next_gamestate.set(GameState::WandEditor);
+}
+
+fn refresh_editor(mut dragging: ResMut<Dragging>, mut spell_inventory: ResMut<SpellInventory>) {
+    dragging.0 = None;
+    spell_inventory.set_changed();
+}
+
+/// The edit that dropping the gem dragged from `from` onto `to` makes.
+fn edit_for_drop(
+    from: EditorSlot,
+    to: EditorSlot,
+    wand_len: usize,
+    stash_len: usize,
+) -> Option<WandEdit> {
+    match (from, to) {
+        (EditorSlot::Wand(a), EditorSlot::Wand(b)) if a == b => None,
+        (EditorSlot::Wand(a), EditorSlot::Wand(b)) if b < wand_len => Some(WandEdit::Swap(a, b)),
+        (EditorSlot::Wand(a), EditorSlot::Wand(_)) => Some(WandEdit::Move {
+            from: a,
+            to: wand_len - 1,
+        }),
+        (EditorSlot::Wand(a), EditorSlot::Gap(gap)) => {
+            // the gem is taken out before it's put back in
+            let to = if gap > a { gap - 1 } else { gap };
+            (to != a).then_some(WandEdit::Move { from: a, to })
+        }
+        (EditorSlot::Wand(a), EditorSlot::Stash(s)) if s < stash_len => {
+            Some(WandEdit::SwapStashed { stash: s, wand: a })
+        }
+        (EditorSlot::Wand(a), EditorSlot::Stash(_)) => Some(WandEdit::Stash(a)),
+        (EditorSlot::Stash(s), EditorSlot::Wand(b)) if b < wand_len => {
+            Some(WandEdit::SwapStashed { stash: s, wand: b })
+        }
+        (EditorSlot::Stash(s), EditorSlot::Wand(_)) => Some(WandEdit::Unstash {
+            from: s,
+            to: wand_len,
+        }),
+        (EditorSlot::Stash(s), EditorSlot::Gap(gap)) => {
+            Some(WandEdit::Unstash { from: s, to: gap })
+        }
+        _ => None,
+    }
+}
+
+/// Everything needed to edit the wand, shared by dragging and the fuse buttons.
+#[derive(SystemParam)]
+struct WandEdits<'w, 's> {
+    commands: Commands<'w, 's>,
+    spell_inventory: ResMut<'w, SpellInventory>,
+    stash: ResMut<'w, WandStash>,
+    recipes: Res<'w, FusionRecipes>,
+    replay: ResMut<'w, ReplayMode>,
+}
+
+impl WandEdits<'_, '_> {
+    /// Applies the edit and records it to the replay, unless it isn't possible.
+    fn apply(&mut self, edit: WandEdit) {
+        if self
+            .spell_inventory
+            .apply_edit(&mut self.stash, &self.recipes, edit)
+        {
+            self.replay.record_wand_edit(edit);
+            self.commands.trigger(RebuildWand);
+        } else {
+            // redraw anyway, to clear the highlight of a dragged gem
+            self.spell_inventory.set_changed();
+        }
+    }
+}
+
+fn start_drag(
+    mut slot_query: InteractionQuery<(&EditorSlot, &mut BackgroundColor)>,
+    mut dragging: ResMut<Dragging>,
+    spell_inventory: Res<SpellInventory>,
+    stash: Res<WandStash>,
+) {
+    for (interaction, (slot, mut background)) in &mut slot_query {
+        let has_gem = match slot {
+            EditorSlot::Wand(i) => *i < spell_inventory.spells.len(),
+            EditorSlot::Stash(i) => *i < stash.spells.len(),
+            EditorSlot::Gap(_) => false,
+        };
+        if matches!(interaction, Interaction::Pressed) && has_gem {
+            dragging.0 = Some(*slot);
+            background.0 = Color::from(BLUE);
+        }
+    }
+}
+
+fn end_drag(
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut dragging: ResMut<Dragging>,
+    mut edits: WandEdits,
+    slot_query: Query<(&EditorSlot, &RelativeCursorPosition)>,
+) {
+    if !mouse.just_released(MouseButton::Left) {
+        return;
+    }
+    let Some(from) = dragging.0.take() else {
+        return;
+    };
+    let target = slot_query
+        .iter()
+        .find(|(_, cursor)| cursor.mouse_over())
+        .map(|(slot, _)| *slot);
+
+    let edit = target.and_then(|to| {
+        edit_for_drop(
+            from,
+            to,
+            edits.spell_inventory.spells.len(),
+            edits.stash.spells.len(),
+        )
+    });
+    match edit {
+        Some(edit) => edits.apply(edit),
+        None => edits.spell_inventory.set_changed(),
+    }
+}
+
+fn handle_editor_action(
+    mut button_query: InteractionQuery<&EditorAction>,
+    mut edits: WandEdits,
+    return_state: Res<WandEditorReturn>,
+    mut next_gamestate: ResMut<NextState<GameState>>,
+) {
+    for (interaction, action) in &mut button_query {
+        if matches!(interaction, Interaction::Pressed) {
+            match action {
+                EditorAction::Fuse(recipe) => edits.apply(WandEdit::Fuse(*recipe)),
+                EditorAction::Done => next_gamestate.set(return_state.0),
+            }
+        }
+    }
+}
+
+fn spawn_editor_menu(
+    mut commands: Commands,
+    spell_inventory: Res<SpellInventory>,
+    stash: Res<WandStash>,
+    recipes: Res<FusionRecipes>,
+    images: Res<ImageAssets>,
+    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    mut atlas_layout: Local<Option<Handle<TextureAtlasLayout>>>,
+    menu_query: Query<Entity, With<EditorMenu>>,
+) {
+    for menu in &menu_query {
+        commands.entity(menu).despawn_recursive();
+    }
+    let atlas_layout = atlas_layout
+        .get_or_insert_with(|| {
+            texture_atlas_layouts.add(TextureAtlasLayout::from_grid(
+                UVec2::splat(32),
+                12,
+                4,
+                None,
+                None,
+            ))
+        })
+        .clone();
+    let icons = images[&ImageAsset::SpellIcons].clone_weak();
+    let groups = spell_inventory.cast_groups();
+    let group_of = |index: usize| groups.iter().position(|group| group.contains(&index));
+
+    commands
+        .ui_root()
+        .insert((EditorMenu, StateScoped(GameState::WandEditor)))
+        .insert(BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)))
+        .with_children(|children| {
+            children.header("Wand");
+            children.label("Drag gems to swap, move or stash them");
+
+            slot_row(children).with_children(|children| {
+                for i in 0..spell_inventory.slots {
+                    if i <= spell_inventory.spells.len() {
+                        spawn_gap(children, i);
+                    }
+                    let spell = spell_inventory.spells.get(i);
+                    let caption = match (spell, group_of(i)) {
+                        (None, _) => String::new(),
+                        (Some(_), Some(group)) => format!("Cast {}", group + 1),
+                        (Some(_), None) => "Never cast".to_string(),
+                    };
+                    spawn_slot(
+                        children,
+                        EditorSlot::Wand(i),
+                        spell,
+                        caption,
+                        &icons,
+                        &atlas_layout,
+                    );
+                }
+            });
+
+            children.label("Stash");
+            slot_row(children).with_children(|children| {
+                for i in 0..STASH_SLOTS {
+                    spawn_slot(
+                        children,
+                        EditorSlot::Stash(i),
+                        stash.spells.get(i),
+                        String::new(),
+                        &icons,
+                        &atlas_layout,
+                    );
+                }
+            });
+
+            // live preview of what the wand casts, in order
+            let mut preview = groups
+                .iter()
+                .enumerate()
+                .map(|(n, group)| {
+                    let names = spell_inventory.spells[group.clone()]
+                        .iter()
+                        .map(|spell| spell.data.get_name())
+                        .collect::<Vec<_>>();
+                    format!("Cast {}: {}", n + 1, names.join(" > "))
+                })
+                .collect::<Vec<_>>();
+            let cast = groups.last().map_or(0, |group| group.end);
+            if cast < spell_inventory.spells.len() {
+                let names = spell_inventory.spells[cast..]
+                    .iter()
+                    .map(|spell| spell.data.get_name())
+                    .collect::<Vec<_>>();
+                preview.push(format!("Never cast: {}", names.join(", ")));
+            }
+            children.spawn(TextBundle::from_section(
+                preview.join("\n"),
+                TextStyle {
+                    font_size: 24.,
+                    color: LABEL_TEXT,
+                    ..default()
+                },
+            ));
+
+            // only the recipes the player owns all the gems for
+            let fusable = recipes
+                .0
+                .iter()
+                .enumerate()
+                .filter(|(_, recipe)| {
+                    recipe
+                        .find_ingredients(&spell_inventory.spells, &stash.spells)
+                        .is_some()
+                })
+                .collect::<Vec<_>>();
+            if !fusable.is_empty() {
+                children.label("Fuse");
+                slot_row(children).with_children(|children| {
+                    for (index, recipe) in fusable {
+                        fuse_button(children, recipe.describe(), index);
+                    }
+                });
+            }
+
+            children.button("Done").insert(EditorAction::Done);
+        });
+}
+
+fn slot_row<'a>(children: &'a mut ChildBuilder) -> bevy::ecs::system::EntityCommands<'a> {
+    children.spawn(NodeBundle {
+        style: Style {
+            flex_direction: FlexDirection::Row,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        ..default()
+    })
+}
+
+fn fuse_button(children: &mut ChildBuilder, text: String, recipe: usize) {
+    children
+        .spawn((
+            Name::new("Fuse Button"),
+            ButtonBundle {
+                style: Style {
+                    padding: UiRect::all(Px(8.0)),
+                    margin: UiRect::horizontal(Px(5.0)),
+                    border: UiRect::all(Px(2.0)),
+                    ..default()
+                },
+                border_color: BorderColor(NODE_BACKGROUND.1),
+                border_radius: BorderRadius::all(Val::Percent(10.)),
+                background_color: BackgroundColor(NODE_BACKGROUND.0),
+                ..default()
+            },
+            InteractionPalette {
+                none: NODE_BACKGROUND,
+                hovered: BUTTON_HOVERED_BACKGROUND,
+                pressed: BUTTON_PRESSED_BACKGROUND,
+            },
+            DefaultButtonSound,
+            EditorAction::Fuse(recipe),
+        ))
+        .with_children(|children| {
+            children.spawn(TextBundle::from_section(
+                text,
+                TextStyle {
+                    font_size: 20.,
+                    color: BUTTON_TEXT,
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn spawn_gap(children: &mut ChildBuilder, index: usize) {
+    children.spawn((
+        Name::new("Wand Gap"),
+        ButtonBundle {
+            style: Style {
+                width: Px(12.0),
+                height: Px(100.0),
+                margin: UiRect::horizontal(Px(2.0)),
+                ..default()
+            },
+            background_color: BackgroundColor(Color::srgba(1.0, 1.0, 1.0, 0.1)),
+            ..default()
+        },
+        RelativeCursorPosition::default(),
+        EditorSlot::Gap(index),
+    ));
+}
+
+fn spawn_slot(
+    children: &mut ChildBuilder,
+    slot: EditorSlot,
+    spell: Option<&SpellComponent>,
+    caption: String,
+    icons: &Handle<Image>,
+    atlas_layout: &Handle<TextureAtlasLayout>,
+) {
+    children
+        .spawn((
+            Name::new("Wand Slot"),
+            ButtonBundle {
+                style: Style {
+                    width: Px(100.0),
+                    height: Px(120.0),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    border: UiRect::all(Px(2.0)),
+                    ..default()
+                },
+                border_color: BorderColor(NODE_BACKGROUND.1),
+                border_radius: BorderRadius::all(Val::Percent(10.)),
+                background_color: BackgroundColor(NODE_BACKGROUND.0),
+                ..default()
+            },
+            RelativeCursorPosition::default(),
+            slot,
+        ))
+        .with_children(|children| {
+            let Some(spell) = spell else {
+                return;
+            };
+            children.spawn((
+                ImageBundle {
+                    image: UiImage {
+                        texture: icons.clone(),
+                        ..default()
+                    },
+                    style: Style {
+                        width: Px(48.),
+                        height: Px(48.),
+                        ..default()
+                    },
+                    ..default()
+                },
+                TextureAtlas {
+                    layout: atlas_layout.clone(),
+                    index: spell.icon_id,
+                },
+            ));
+            for (text, font_size) in [(spell.data.get_name(), 18.), (caption, 14.)] {
+                children.spawn(TextBundle::from_section(
+                    text,
+                    TextStyle {
+                        font_size,
+                        color: BUTTON_TEXT,
+                        ..default()
+                    },
+                ));
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dropping_on_a_gap_moves_the_gem_in_front_of_that_slot() {
+        // three gems, so the gaps are 0 to 3 and gap 3 is after the last gem
+        let drop = |from, gap| edit_for_drop(EditorSlot::Wand(from), EditorSlot::Gap(gap), 3, 0);
+        assert_eq!(drop(0, 3), Some(WandEdit::Move { from: 0, to: 2 }));
+        assert_eq!(drop(0, 2), Some(WandEdit::Move { from: 0, to: 1 }));
+        assert_eq!(drop(2, 0), Some(WandEdit::Move { from: 2, to: 0 }));
+        // the gaps on either side of a gem leave it where it is
+        assert_eq!(drop(1, 1), None);
+        assert_eq!(drop(1, 2), None);
+    }
+
+    #[test]
+    fn dropping_on_a_slot_swaps_or_moves_to_the_end() {
+        let drop = |from, to| edit_for_drop(EditorSlot::Wand(from), EditorSlot::Wand(to), 3, 0);
+        assert_eq!(drop(0, 2), Some(WandEdit::Swap(0, 2)));
+        assert_eq!(drop(1, 1), None);
+        // an empty slot past the last gem
+        assert_eq!(drop(0, 4), Some(WandEdit::Move { from: 0, to: 2 }));
+    }
+
+    #[test]
+    fn stashed_gems_go_back_where_they_are_dropped() {
+        let drop = |to| edit_for_drop(EditorSlot::Stash(1), to, 3, 2);
+        assert_eq!(
+            drop(EditorSlot::Wand(0)),
+            Some(WandEdit::SwapStashed { stash: 1, wand: 0 })
+        );
+        assert_eq!(
+            drop(EditorSlot::Wand(5)),
+            Some(WandEdit::Unstash { from: 1, to: 3 })
+        );
+        assert_eq!(
+            drop(EditorSlot::Gap(3)),
+            Some(WandEdit::Unstash { from: 1, to: 3 })
+        );
+        assert_eq!(drop(EditorSlot::Stash(0)), None);
+    }
+
+    #[test]
+    fn dropping_on_the_stash_stashes_or_swaps() {
+        let drop = |to| edit_for_drop(EditorSlot::Wand(2), EditorSlot::Stash(to), 3, 1);
+        assert_eq!(drop(0), Some(WandEdit::SwapStashed { stash: 0, wand: 2 }));
+        assert_eq!(drop(1), Some(WandEdit::Stash(2)));
+    }
+}